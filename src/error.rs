@@ -24,6 +24,9 @@ pub enum Error {
     /// An error occurred while parsing JSON.
     #[error("An error occurred while parsing JSON: {0}")]
     Serde(#[from] serde_json::Error),
+    /// An error occurred while decoding or encoding an image.
+    #[error("An error occurred while processing an image: {0}")]
+    Image(#[from] image::ImageError),
 
     /// An error occurred while interacting with GitHub.
     #[error("No GitHub asset found for platform {0}/{1}")]
@@ -40,8 +43,149 @@ pub enum Error {
     /// An error occurred manipulating a path.
     #[error("An invalid path was provided: {0}")]
     Path(String),
+    /// An invalid proxy was provided.
+    #[error("An invalid proxy was provided: {0}")]
+    Proxy(String),
+    /// An invalid cookie file was provided.
+    #[error("Invalid cookies: {0}")]
+    Cookies(String),
+    /// An invalid format selector was provided.
+    #[error("Invalid format selector: {0}")]
+    Selector(String),
+    /// An invalid format sorting was provided.
+    #[error("Invalid format sorting: {0}")]
+    Sorting(String),
+    /// An invalid manifest was received, e.g. an HLS playlist.
+    #[error("Invalid manifest: {0}")]
+    Manifest(String),
+    /// Invalid captions were received, e.g. a malformed TTML document.
+    #[error("Invalid captions: {0}")]
+    Captions(String),
+    /// The connection was closed before the whole asset was received.
+    #[error("Incomplete download: received {0} of {1} bytes")]
+    Incomplete(u64, u64),
+    /// The operation was cancelled through its cancellation token.
+    #[error("The operation was cancelled")]
+    Cancelled,
 
     /// An unknown error occurred.
     #[error("An unknown error occurred: {0}")]
     Unknown(String),
 }
+
+impl Error {
+    /// Checks if the error is likely temporary, so the failed operation is worth retrying.
+    /// It is the case for network failures, server errors and incomplete downloads,
+    /// including the 'yt-dlp' failures reporting them, but not for cancellations, invalid inputs,
+    /// or any other 'yt-dlp' failure, e.g. videos that are unavailable.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Reqwest(error) => {
+                let status = error.status();
+                let retryable_status = status.is_some_and(|status| {
+                    status.is_server_error()
+                        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                });
+
+                match status {
+                    Some(_) => retryable_status,
+                    None => {
+                        error.is_timeout()
+                            || error.is_connect()
+                            || error.is_request()
+                            || error.is_body()
+                            || error.is_decode()
+                    }
+                }
+            }
+            Error::IO(error) => matches!(
+                error.kind(),
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::UnexpectedEof
+            ),
+            Error::Incomplete(_, _) => true,
+            Error::Command(message) => {
+                const TRANSIENT: [&str; 6] = [
+                    "HTTP Error 5",
+                    "HTTP Error 429",
+                    "timed out",
+                    "Connection reset",
+                    "Temporary failure in name resolution",
+                    "IncompleteRead",
+                ];
+
+                TRANSIENT
+                    .iter()
+                    .any(|transient| message.contains(transient))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(message: &str) -> Error {
+        Error::Command(format!("Process failed with code 1: ERROR: {}", message))
+    }
+
+    #[test]
+    fn command_network_failures_are_transient() {
+        let messages = [
+            "[youtube] dQw4w9WgXcQ: Unable to download API page: HTTP Error 503: Service Unavailable",
+            "[youtube] dQw4w9WgXcQ: Unable to download webpage: HTTP Error 429: Too Many Requests",
+            "unable to download video data: The read operation timed out",
+            "[Errno 104] Connection reset by peer",
+            "<urlopen error [Errno -3] Temporary failure in name resolution>",
+            "IncompleteRead(1024 bytes read, 2048 more expected)",
+        ];
+
+        for message in messages {
+            assert!(command(message).is_transient(), "{}", message);
+        }
+        assert!(Error::Command("Process timed out".to_string()).is_transient());
+    }
+
+    #[test]
+    fn command_other_failures_are_permanent() {
+        let messages = [
+            "[youtube] dQw4w9WgXcQ: Video unavailable",
+            "[youtube] dQw4w9WgXcQ: Private video. Sign in if you've been granted access to this video",
+            "Unsupported URL: https://example.com",
+            "'not a url' is not a valid URL",
+            "[youtube] dQw4w9WgXcQ: Sign in to confirm your age",
+            "[youtube:tab] @username: This channel does not have a shorts tab",
+            "[youtube] dQw4w9WgXcQ: HTTP Error 403: Forbidden",
+            "[youtube] dQw4w9WgXcQ: HTTP Error 404: Not Found",
+            "Requested format is not available",
+        ];
+
+        for message in messages {
+            assert!(!command(message).is_transient(), "{}", message);
+        }
+        assert!(!Error::Command("Failed to parse stdout".to_string()).is_transient());
+    }
+
+    #[test]
+    fn io_failures() {
+        let transient = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert!(Error::IO(transient).is_transient());
+
+        let permanent = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(!Error::IO(permanent).is_transient());
+    }
+
+    #[test]
+    fn other_errors() {
+        assert!(Error::Incomplete(1024, 2048).is_transient());
+        assert!(!Error::Cancelled.is_transient());
+        assert!(!Error::Unknown("Something went wrong".to_string()).is_transient());
+    }
+}
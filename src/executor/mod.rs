@@ -1,12 +1,22 @@
 //! A tool for executing commands.
 
 use crate::error::{Error, Result};
+use futures_util::Stream;
+use std::fmt::{Debug, Formatter};
 use std::path::PathBuf;
+use std::process::ExitStatus;
 use std::time::Duration;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
 
 /// Represents a command executor.
 ///
+/// On Unix, the process is spawned in its own process group, so that it is killed along with its children
+/// on cancellation or timeout. It therefore does not receive the signals sent by the terminal to the foreground group,
+/// e.g. SIGINT on Ctrl+C: the caller must handle them, and cancel the execution.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -18,18 +28,14 @@ use tokio::io::AsyncReadExt;
 /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let args = vec!["--update"];
 ///
-/// let executor = Executor {
-///     executable_path: PathBuf::from("yt-dlp"),
-///     timeout: Duration::from_secs(30),
-///     args: utils::to_owned(args),
-/// };
+/// let executor = Executor::new(PathBuf::from("yt-dlp"), Duration::from_secs(30), utils::to_owned(args));
 ///
 /// let output = executor.execute().await?;
 /// println!("Output: {}", output.stdout);
 ///
 /// # Ok(())
 /// # }
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct Executor {
     /// The path to the command executable.
     pub executable_path: PathBuf,
@@ -38,6 +44,23 @@ pub struct Executor {
 
     /// The arguments to pass to the command.
     pub args: Vec<String>,
+    /// The environment variables to set for the command, on top of the inherited ones.
+    /// Unlike the arguments, they are not visible to the other users of the system,
+    /// so they carry the credentials, which are never shown by the debug output.
+    pub env: Vec<(String, String)>,
+}
+
+impl Debug for Executor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let env: Vec<&str> = self.env.iter().map(|(key, _)| key.as_str()).collect();
+
+        f.debug_struct("Executor")
+            .field("executable_path", &self.executable_path)
+            .field("timeout", &self.timeout)
+            .field("args", &self.args)
+            .field("env", &env)
+            .finish()
+    }
 }
 
 /// Represents the output of a process.
@@ -51,7 +74,47 @@ pub struct ProcessOutput {
     pub code: i32,
 }
 
+/// Represents an event emitted by a running process, in the order it happened.
+///
+/// The last event of a stream is always the exit code of the process.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProcessEvent {
+    /// A line written by the process to its stdout.
+    Stdout(String),
+    /// A line written by the process to its stderr.
+    Stderr(String),
+    /// The process exited with the given code.
+    Exit(i32),
+}
+
 impl Executor {
+    /// Creates a new executor, without additional environment variables.
+    /// Prefer it to a struct literal, which breaks whenever a field is added.
+    ///
+    /// # Arguments
+    ///
+    /// * `executable_path` - The path to the command executable.
+    /// * `timeout` - The timeout for the process.
+    /// * `args` - The arguments to pass to the command.
+    pub fn new(executable_path: PathBuf, timeout: Duration, args: Vec<String>) -> Self {
+        Self {
+            executable_path,
+            timeout,
+            args,
+            env: Vec::new(),
+        }
+    }
+
+    /// Sets the environment variables to set for the command, on top of the inherited ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `env` - The names and values of the environment variables.
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = env;
+        self
+    }
+
     /// Executes the command and returns the output.
     ///
     /// # Errors
@@ -59,34 +122,68 @@ impl Executor {
     /// This function will return an error if the command could not be executed, or if the process timed out.
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
     pub async fn execute(&self) -> Result<ProcessOutput> {
+        self.execute_with_cancellation(&CancellationToken::new())
+            .await
+    }
+
+    /// Executes the command and returns the output, unless the given token is cancelled before the process exits.
+    /// On cancellation, the process and all of its children are killed.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation` - The token to cancel the execution with.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the command could not be executed, if the process timed out,
+    /// or [`Error::Cancelled`] if the token was cancelled.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use yt_dlp::utils;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use tokio_util::sync::CancellationToken;
+    /// # use yt_dlp::executor::Executor;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let args = vec!["--update"];
+    ///
+    /// let executor = Executor::new(PathBuf::from("yt-dlp"), Duration::from_secs(30), utils::to_owned(args));
+    ///
+    /// let cancellation = CancellationToken::new();
+    /// let canceller = cancellation.clone();
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(Duration::from_secs(5)).await;
+    ///     canceller.cancel();
+    /// });
+    ///
+    /// let output = executor.execute_with_cancellation(&cancellation).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn execute_with_cancellation(
+        &self,
+        cancellation: &CancellationToken,
+    ) -> Result<ProcessOutput> {
         #[cfg(feature = "tracing")]
         tracing::debug!("Executing command: {:?}", self);
 
-        let mut command = tokio::process::Command::new(&self.executable_path);
-        command.stdout(std::process::Stdio::piped());
-        command.stderr(std::process::Stdio::piped());
-
-        #[cfg(target_os = "windows")]
-        {
-            use std::os::windows::process::CommandExt;
-            command.creation_flags(0x08000000);
-        }
+        let mut child = self.spawn()?;
+        let mut stdout = Vec::new();
 
-        command.args(&self.args);
-        let mut child = command.spawn()?;
+        let exit_code = tokio::select! {
+            result = Self::wait(&mut child, &mut stdout, self.timeout) => result,
+            _ = cancellation.cancelled() => Err(Error::Cancelled),
+        };
 
-        // Continually read from stdout so that it does not fill up with large output and hang forever.
-        // We don't need to do this for stderr since only stdout has potentially giant JSON.
-        // This code was taken from youtube-dl-rs.
-        let mut stdout = Vec::new();
-        let child_stdout = child.stdout.take();
-        tokio::io::copy(&mut child_stdout.unwrap(), &mut stdout).await?;
-
-        let exit_code = match tokio::time::timeout(self.timeout, child.wait()).await {
-            Ok(result) => result?,
-            Err(_) => {
-                child.kill().await?;
-                return Err(Error::Command("Process timed out".to_string()));
+        let exit_code = match exit_code {
+            Ok(exit_code) => exit_code,
+            Err(error) => {
+                terminate(&mut child).await;
+                return Err(error);
             }
         };
 
@@ -114,4 +211,221 @@ impl Executor {
             code, stderr
         )))
     }
+
+    /// Executes the command and streams its output line by line, while the process is running.
+    /// Lines are split on both '\n' and '\r', so progress lines of 'yt-dlp' and 'ffmpeg' are emitted as they are updated.
+    ///
+    /// The stream ends with a [`ProcessEvent::Exit`] event, carrying the exit code of the process.
+    /// A non-zero exit code is not considered as an error, it is up to the caller to handle it.
+    /// If the stream is dropped before its end, the process and all of its children are killed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the command could not be executed.
+    /// The stream will yield an error if the output could not be read, or if the process timed out.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use yt_dlp::utils;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use futures_util::StreamExt;
+    /// # use yt_dlp::executor::{Executor, ProcessEvent};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let args = vec!["--newline", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"];
+    ///
+    /// let executor = Executor::new(PathBuf::from("yt-dlp"), Duration::from_secs(600), utils::to_owned(args));
+    ///
+    /// let mut events = executor.execute_stream().await?;
+    /// while let Some(event) = events.next().await {
+    ///     match event? {
+    ///         ProcessEvent::Stdout(line) => println!("{}", line),
+    ///         ProcessEvent::Stderr(line) => eprintln!("{}", line),
+    ///         ProcessEvent::Exit(code) => println!("Exited with code {}", code),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn execute_stream(
+        &self,
+    ) -> Result<impl Stream<Item = Result<ProcessEvent>> + Send + Unpin + 'static> {
+        self.execute_stream_with_cancellation(&CancellationToken::new())
+            .await
+    }
+
+    /// Executes the command and streams its output line by line, unless the given token is cancelled before the process exits.
+    /// On cancellation, the process and all of its children are killed, and the stream ends with [`Error::Cancelled`].
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation` - The token to cancel the execution with.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the command could not be executed.
+    /// The stream will yield an error if the output could not be read, if the process timed out, or if it was cancelled.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn execute_stream_with_cancellation(
+        &self,
+        cancellation: &CancellationToken,
+    ) -> Result<impl Stream<Item = Result<ProcessEvent>> + Send + Unpin + 'static> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Executing command with streamed output: {:?}", self);
+
+        let mut child = self.spawn()?;
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        let stdout = tokio::spawn(forward_lines(
+            child.stdout.take(),
+            sender.clone(),
+            ProcessEvent::Stdout,
+        ));
+        let stderr = tokio::spawn(forward_lines(
+            child.stderr.take(),
+            sender.clone(),
+            ProcessEvent::Stderr,
+        ));
+
+        let timeout = self.timeout;
+        let cancellation = cancellation.clone();
+
+        tokio::spawn(async move {
+            let process = async {
+                let _ = tokio::join!(stdout, stderr);
+                child.wait().await
+            };
+
+            let event = tokio::select! {
+                result = tokio::time::timeout(timeout, process) => match result {
+                    Ok(Ok(status)) => Ok(ProcessEvent::Exit(status.code().unwrap_or(-1))),
+                    Ok(Err(error)) => Err(Error::IO(error)),
+                    Err(_) => Err(Error::Command("Process timed out".to_string())),
+                },
+                _ = cancellation.cancelled() => Err(Error::Cancelled),
+                // The stream was dropped before the end of the process, which is not awaited anymore
+                _ = sender.closed() => Err(Error::Cancelled),
+            };
+
+            if event.is_err() {
+                terminate(&mut child).await;
+            }
+
+            let _ = sender.send(event);
+        });
+
+        let stream = futures_util::stream::unfold(receiver, |mut receiver| async move {
+            let event = receiver.recv().await?;
+            Some((event, receiver))
+        });
+
+        Ok(Box::pin(stream))
+    }
+
+    /// Spawns the command, with piped stdout and stderr.
+    /// On Unix, the process is placed in its own process group, so it can be killed along with its children.
+    /// The process is killed if its handle is dropped before it exits, e.g. when the runtime shuts down.
+    fn spawn(&self) -> Result<Child> {
+        let mut command = Command::new(&self.executable_path);
+        command.stdout(std::process::Stdio::piped());
+        command.stderr(std::process::Stdio::piped());
+        command.kill_on_drop(true);
+
+        #[cfg(unix)]
+        command.process_group(0);
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(0x08000000);
+        }
+
+        command.args(&self.args);
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        let child = command.spawn()?;
+
+        Ok(child)
+    }
+
+    /// Reads the whole stdout of the process, then waits for it to exit.
+    async fn wait(
+        child: &mut Child,
+        stdout: &mut Vec<u8>,
+        timeout: Duration,
+    ) -> Result<ExitStatus> {
+        // Continually read from stdout so that it does not fill up with large output and hang forever.
+        // We don't need to do this for stderr since only stdout has potentially giant JSON.
+        // This code was taken from youtube-dl-rs.
+        if let Some(mut reader) = child.stdout.take() {
+            tokio::io::copy(&mut reader, stdout).await?;
+        }
+
+        match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(Error::Command("Process timed out".to_string())),
+        }
+    }
+}
+
+/// Kills the process, and on Unix, all the processes of its group.
+/// Errors are ignored, since the process may already have exited.
+async fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: 'kill' has no memory safety requirements, and the group was created by 'spawn'.
+        unsafe {
+            libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+        }
+    }
+
+    let _ = child.kill().await;
+}
+
+/// Reads the given output line by line, and forwards each non-empty line to the sender.
+/// Lines are delimited by '\n' or '\r'.
+async fn forward_lines(
+    reader: Option<impl AsyncRead + Unpin>,
+    sender: UnboundedSender<Result<ProcessEvent>>,
+    event: fn(String) -> ProcessEvent,
+) {
+    let Some(mut reader) = reader else {
+        return;
+    };
+
+    let mut buffer = [0u8; 8192];
+    let mut line = Vec::new();
+
+    let send = |line: &mut Vec<u8>| {
+        if line.is_empty() {
+            return;
+        }
+
+        let content = String::from_utf8_lossy(line).into_owned();
+        line.clear();
+
+        let _ = sender.send(Ok(event(content)));
+    };
+
+    loop {
+        let read = match reader.read(&mut buffer).await {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) => {
+                let _ = sender.send(Err(Error::IO(error)));
+                return;
+            }
+        };
+
+        for byte in &buffer[..read] {
+            match byte {
+                b'\n' | b'\r' => send(&mut line),
+                _ => line.push(*byte),
+            }
+        }
+    }
+
+    send(&mut line);
 }
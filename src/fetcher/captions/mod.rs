@@ -0,0 +1,341 @@
+//! Tools for fetching captions from YouTube, and converting them to SRT or WebVTT.
+//!
+//! The caption tracks, either subtitles or automatic captions, are parsed into cues from any format served by YouTube,
+//! cleaned from the rolling lines of automatic captions, and written in the requested format.
+
+use crate::error::{Error, Result};
+use crate::fetcher::captions::parse::parse_captions;
+use crate::fetcher::captions::write::write_captions;
+use crate::fetcher::Fetcher;
+use crate::model::caption::{CaptionFormat, CaptionKind, CaptionTrack, Cue};
+use crate::model::Video;
+use crate::Youtube;
+use std::path::PathBuf;
+
+pub mod parse;
+pub mod write;
+
+/// The maximum gap between two cues for the second one to continue the first one, in seconds.
+const ROLLING_TOLERANCE: f64 = 0.1;
+
+impl Youtube {
+    /// Fetches the best caption track of the video in the given language, and returns its cues.
+    /// The subtitles are preferred over the automatic captions, see [`Video::best_caption_track`].
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to fetch the captions of.
+    /// * `language` - The language code of the captions, e.g. 'en' or 'fr'.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video has no captions in the language,
+    /// or if they could not be fetched or parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let cues = fetcher.fetch_captions(&video, "en").await?;
+    /// for cue in cues {
+    ///     println!("{} -> {}: {}", cue.start, cue.end, cue.text);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(video)))]
+    pub async fn fetch_captions(&self, video: &Video, language: &str) -> Result<Vec<Cue>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching {} captions of {}", language, video.id);
+
+        let track = best_track(video, language)?;
+
+        self.fetch_caption_track(&track).await
+    }
+
+    /// Fetches a caption track, and returns its cues.
+    /// The richest format of the track is fetched, and the rolling lines of automatic captions are de-duplicated.
+    ///
+    /// # Arguments
+    ///
+    /// * `track` - The caption track to fetch, e.g. one of [`Video::caption_tracks`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the track has no files in a known format, or if it could not be fetched or parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// for track in video.caption_tracks() {
+    ///     let cues = fetcher.fetch_caption_track(&track).await?;
+    ///     println!("{} ({:?}): {} cues", track.language, track.kind, cues.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub async fn fetch_caption_track(&self, track: &CaptionTrack<'_>) -> Result<Vec<Cue>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching {:?} caption track {}", track.kind, track.language);
+
+        let caption = track.best_caption().ok_or(Error::Captions(format!(
+            "the caption track '{}' has no files in a known format",
+            track.language
+        )))?;
+
+        let fetcher = Fetcher::new(&caption.url)
+            .with_cancellation(self.cancellation.clone())
+            .with_retry(self.retry.clone())
+            .with_client(self.client.clone());
+
+        let content = tokio::select! {
+            content = fetcher.fetch_text(&caption.url) => content?,
+            _ = self.cancellation.cancelled() => return Err(Error::Cancelled),
+        };
+
+        let cues = parse_captions(&content, caption.extension)?;
+        match track.kind {
+            CaptionKind::Automatic => Ok(dedupe(cues)),
+            CaptionKind::Manual => Ok(cues),
+        }
+    }
+
+    /// Downloads the best caption track of the video in the given language, converted to the given format.
+    /// The subtitles are preferred over the automatic captions, see [`Video::best_caption_track`].
+    /// The captions are saved in the output directory as '{video id}.{track language}.srt' or '.vtt'.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to download the captions of.
+    /// * `language` - The language code of the captions, e.g. 'en' or 'fr'.
+    /// * `format` - The format to save the captions in.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video has no captions in the language,
+    /// or if they could not be fetched, parsed, or written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::caption::CaptionFormat;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let captions_path = fetcher.download_captions(&video, "en", CaptionFormat::Srt).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(video)))]
+    pub async fn download_captions(
+        &self,
+        video: &Video,
+        language: &str,
+        format: CaptionFormat,
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Downloading {} captions of {}", language, video.id);
+
+        let track = best_track(video, language)?;
+        let output = format!("{}.{}.{}", video.id, track.language, format.extension());
+
+        self.download_caption_track(&track, output, format).await
+    }
+
+    /// Downloads a caption track, converted to the given format, and returns its path.
+    ///
+    /// # Arguments
+    ///
+    /// * `track` - The caption track to download, e.g. one of [`Video::caption_tracks`].
+    /// * `output` - The name of the file to save the captions to.
+    /// * `format` - The format to save the captions in.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the track has no files in a known format,
+    /// or if it could not be fetched, parsed, or written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::caption::CaptionFormat;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let track = video.best_caption_track("fr").unwrap();
+    /// let captions_path = fetcher
+    ///     .download_caption_track(&track, "captions.fr.vtt", CaptionFormat::WebVtt)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(output))
+    )]
+    pub async fn download_caption_track(
+        &self,
+        track: &CaptionTrack<'_>,
+        output: impl AsRef<str>,
+        format: CaptionFormat,
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Downloading {:?} caption track {}",
+            track.kind,
+            track.language
+        );
+
+        let cues = self.fetch_caption_track(track).await?;
+
+        let path = self.output_dir.join(output.as_ref());
+        tokio::fs::write(&path, write_captions(&cues, format)).await?;
+
+        Ok(path)
+    }
+}
+
+/// Returns the best caption track of the video in the language, or an error if there is none.
+fn best_track<'a>(video: &'a Video, language: &str) -> Result<CaptionTrack<'a>> {
+    video
+        .best_caption_track(language)
+        .ok_or(Error::Video(format!(
+            "The video {} has no captions in '{}'",
+            video.id, language
+        )))
+}
+
+/// Cleans the cues of a caption track, for them to be displayed one after the other.
+///
+/// Automatic captions roll: a cue repeats the last line of the previous one before adding a new line,
+/// and short cues repeat the text between two cues. The repeated lines are removed, the cues extended instead,
+/// and the overlapping cues are shortened. The empty cues are removed, and the cues starting together are merged.
+///
+/// # Arguments
+///
+/// * `cues` - The cues to clean, in chronological order.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::fetcher::captions::dedupe;
+/// # use yt_dlp::model::caption::{Cue, CueStyle};
+/// let cue = |start, end, text: &str| Cue {
+///     start,
+///     end,
+///     text: text.to_string(),
+///     style: CueStyle::default(),
+/// };
+///
+/// let cues = vec![
+///     cue(0.0, 2.0, "never gonna"),
+///     cue(2.0, 2.01, "never gonna"),
+///     cue(2.01, 4.0, "never gonna\ngive you up"),
+/// ];
+///
+/// let cleaned = dedupe(cues);
+/// assert_eq!(cleaned.len(), 2);
+/// assert_eq!(cleaned[1].text, "give you up");
+/// ```
+pub fn dedupe(cues: Vec<Cue>) -> Vec<Cue> {
+    let mut cleaned: Vec<Cue> = Vec::with_capacity(cues.len());
+
+    for mut cue in cues {
+        let lines: Vec<String> = cue
+            .text
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        cue.text = lines.join("\n");
+
+        if cue.text.is_empty() || cue.end <= cue.start {
+            continue;
+        }
+
+        let Some(previous) = cleaned.last_mut() else {
+            cleaned.push(cue);
+            continue;
+        };
+
+        if cue.start <= previous.start {
+            previous.text = format!("{}\n{}", previous.text, cue.text);
+            previous.end = previous.end.max(cue.end);
+            continue;
+        }
+
+        if cue.start <= previous.end + ROLLING_TOLERANCE {
+            let previous_lines: Vec<&str> = previous.text.lines().collect();
+            let repeated = (1..=lines.len().min(previous_lines.len()))
+                .rev()
+                .find(|count| previous_lines[previous_lines.len() - count..] == lines[..*count])
+                .unwrap_or_default();
+
+            if repeated == lines.len() {
+                previous.end = previous.end.max(cue.end);
+                continue;
+            }
+
+            cue.text = lines[repeated..].join("\n");
+            previous.end = previous.end.min(cue.start);
+        }
+
+        cleaned.push(cue);
+    }
+
+    cleaned
+}
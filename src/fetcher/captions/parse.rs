@@ -0,0 +1,729 @@
+//! Parsers of the caption formats served by YouTube, into a common list of cues.
+//!
+//! The supported formats are json3, srv1, srv2 and srv3 (the timed text formats of YouTube), TTML, WebVTT and SRT.
+
+use crate::error::{Error, Result};
+use crate::model::caption::{Cue, CueStyle, Extension};
+use roxmltree::{Document, Node};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The default frame rate of TTML documents, used by the times expressed in frames.
+const TTML_FRAME_RATE: f64 = 30.0;
+
+/// The default tick rate of TTML documents, used by the times expressed in ticks.
+const TTML_TICK_RATE: f64 = 1.0;
+
+/// Parses a caption track in the given format, and returns its cues in the order of the track.
+/// The cues are returned as is, use [`dedupe`](crate::fetcher::captions::dedupe) to clean the rolling lines of automatic captions.
+///
+/// # Arguments
+///
+/// * `content` - The content of the caption track.
+/// * `extension` - The format of the caption track.
+///
+/// # Errors
+///
+/// This function will return an error if the caption track is malformed.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::fetcher::captions::parse::parse_captions;
+/// # use yt_dlp::model::caption::Extension;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let content = "WEBVTT\n\n00:00:01.000 --> 00:00:02.500\nHello <b>world</b>\n";
+///
+/// let cues = parse_captions(content, Extension::Vtt)?;
+/// assert_eq!(cues[0].text, "Hello world");
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_captions(content: &str, extension: Extension) -> Result<Vec<Cue>> {
+    #[cfg(feature = "tracing")]
+    tracing::debug!("Parsing {:?} captions", extension);
+
+    let content = content.trim_start_matches('\u{feff}');
+
+    match extension {
+        Extension::Json3 => parse_json3(content),
+        Extension::Srv1 => parse_srv1(content),
+        Extension::Srv2 => parse_srv2(content),
+        Extension::Srv3 => parse_srv3(content),
+        Extension::Ttml => parse_ttml(content),
+        Extension::Vtt | Extension::Srt => parse_vtt(content),
+        Extension::Unknown => Err(Error::Captions(String::from(
+            "the format of the captions is unknown",
+        ))),
+    }
+}
+
+/// A json3 document, the JSON format of the timed text of YouTube.
+#[derive(Debug, Deserialize)]
+struct Json3 {
+    /// The styles of the segments, referenced by their index.
+    #[serde(default)]
+    pens: Vec<Json3Pen>,
+    /// The events of the track, either window definitions or cues.
+    #[serde(default)]
+    events: Vec<Json3Event>,
+}
+
+/// A style of a json3 document.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json3Pen {
+    /// If the text is bold, when set to 1.
+    b_attr: Option<u8>,
+    /// If the text is italic, when set to 1.
+    i_attr: Option<u8>,
+    /// If the text is underlined, when set to 1.
+    u_attr: Option<u8>,
+    /// The color of the text, as a RGB integer.
+    fc_fore_color: Option<u32>,
+}
+
+/// An event of a json3 document.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json3Event {
+    /// The start of the event, in milliseconds.
+    #[serde(default)]
+    t_start_ms: u64,
+    /// The duration of the event, in milliseconds.
+    #[serde(default)]
+    d_duration_ms: u64,
+    /// The style of the event, applying to segments without their own.
+    p_pen_id: Option<usize>,
+    /// The text segments of the event, none for window definitions.
+    segs: Option<Vec<Json3Segment>>,
+}
+
+/// A text segment of a json3 event.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json3Segment {
+    /// The text of the segment.
+    #[serde(default)]
+    utf8: String,
+    /// The style of the segment.
+    p_pen_id: Option<usize>,
+}
+
+impl Json3Pen {
+    /// Converts the pen to the styling of a cue.
+    fn style(&self) -> CueStyle {
+        CueStyle {
+            bold: self.b_attr == Some(1),
+            italic: self.i_attr == Some(1),
+            underline: self.u_attr == Some(1),
+            color: self.fc_fore_color.map(|color| format!("#{:06X}", color)),
+        }
+    }
+}
+
+/// Parses a json3 document, whose events with segments are the cues.
+fn parse_json3(content: &str) -> Result<Vec<Cue>> {
+    let document: Json3 = serde_json::from_str(content)?;
+
+    let pen_style = |pen: Option<usize>| {
+        pen.and_then(|pen| document.pens.get(pen))
+            .map(Json3Pen::style)
+            .unwrap_or_default()
+    };
+
+    let cues = document.events.iter().filter_map(|event| {
+        let segments = event.segs.as_ref()?;
+
+        let spans = segments.iter().map(|segment| {
+            let pen = segment.p_pen_id.or(event.p_pen_id);
+            (segment.utf8.clone(), pen_style(pen))
+        });
+
+        let start = event.t_start_ms as f64 / 1000.0;
+        Some(cue(
+            start,
+            start + event.d_duration_ms as f64 / 1000.0,
+            spans,
+        ))
+    });
+
+    Ok(cues.collect())
+}
+
+/// Parses a srv1 document, whose 'text' elements are the cues, timed in seconds.
+/// Their text is HTML escaped, on top of the XML escaping.
+fn parse_srv1(content: &str) -> Result<Vec<Cue>> {
+    let document = parse_xml(content)?;
+
+    let cues = elements(document.root(), "text").map(|text| {
+        let start = parse_seconds(text.attribute("start"));
+        let duration = parse_seconds(text.attribute("dur"));
+
+        let content = unescape_html(&node_text(text));
+        cue(start, start + duration, [(content, CueStyle::default())])
+    });
+
+    Ok(cues.collect())
+}
+
+/// Parses a srv2 document, whose 'text' elements are the cues, timed in milliseconds.
+fn parse_srv2(content: &str) -> Result<Vec<Cue>> {
+    let document = parse_xml(content)?;
+
+    let cues = elements(document.root(), "text").map(|text| {
+        let start = parse_seconds(text.attribute("t")) / 1000.0;
+        let duration = parse_seconds(text.attribute("d")) / 1000.0;
+
+        let content = unescape_html(&node_text(text));
+        cue(start, start + duration, [(content, CueStyle::default())])
+    });
+
+    Ok(cues.collect())
+}
+
+/// Parses a srv3 document, whose 'p' elements are the cues, timed in milliseconds.
+/// The cues may be split into 's' elements, and styled by the pens defined in the head of the document.
+fn parse_srv3(content: &str) -> Result<Vec<Cue>> {
+    let document = parse_xml(content)?;
+
+    let pens: HashMap<&str, CueStyle> = elements(document.root(), "pen")
+        .filter_map(|pen| {
+            let style = CueStyle {
+                bold: pen.attribute("b") == Some("1"),
+                italic: pen.attribute("i") == Some("1"),
+                underline: pen.attribute("u") == Some("1"),
+                color: pen.attribute("fc").map(str::to_uppercase),
+            };
+
+            Some((pen.attribute("id")?, style))
+        })
+        .collect();
+    let pen_style = |node: Node, default: &CueStyle| {
+        node.attribute("p")
+            .and_then(|pen| pens.get(pen))
+            .unwrap_or(default)
+            .clone()
+    };
+
+    let cues = elements(document.root(), "p").map(|paragraph| {
+        let start = parse_seconds(paragraph.attribute("t")) / 1000.0;
+        let duration = parse_seconds(paragraph.attribute("d")) / 1000.0;
+        let style = pen_style(paragraph, &CueStyle::default());
+
+        let spans = paragraph.children().filter_map(|child| {
+            if child.is_text() {
+                return Some((child.text().unwrap_or_default().to_string(), style.clone()));
+            }
+
+            match child.tag_name().name() {
+                "s" => Some((node_text(child), pen_style(child, &style))),
+                "br" => Some((String::from("\n"), style.clone())),
+                _ => None,
+            }
+        });
+
+        cue(start, start + duration, spans)
+    });
+
+    Ok(cues.collect())
+}
+
+/// Parses a TTML document, whose 'p' elements are the cues.
+/// The cues may contain 'span' and 'br' elements, and be styled inline or by the styles of the head of the document.
+fn parse_ttml(content: &str) -> Result<Vec<Cue>> {
+    let document = parse_xml(content)?;
+    let root = document.root_element();
+
+    let frame_rate = local_attribute(root, "frameRate")
+        .and_then(|rate| rate.parse::<f64>().ok())
+        .unwrap_or(TTML_FRAME_RATE);
+    let tick_rate = local_attribute(root, "tickRate")
+        .and_then(|rate| rate.parse::<f64>().ok())
+        .unwrap_or(TTML_TICK_RATE);
+
+    let styles: HashMap<&str, Node> = elements(root, "style")
+        .filter_map(|style| Some((local_attribute(style, "id")?, style)))
+        .collect();
+
+    let mut cues = Vec::new();
+    for paragraph in elements(root, "p") {
+        let time = |name: &str| {
+            inherited_attribute(paragraph, name)
+                .map(|time| parse_ttml_time(time, frame_rate, tick_rate))
+                .transpose()
+        };
+
+        let start = time("begin")?.unwrap_or_default();
+        let end = match (time("end")?, time("dur")?) {
+            (Some(end), _) => end,
+            (None, Some(duration)) => start + duration,
+            (None, None) => start,
+        };
+
+        let style = ttml_style(paragraph, &styles, &CueStyle::default());
+        let mut spans = Vec::new();
+        ttml_spans(paragraph, &styles, &style, &mut spans);
+
+        cues.push(cue(start, end, spans));
+    }
+
+    Ok(cues)
+}
+
+/// Collects the text of a TTML element, with the styling of each part, following the 'span' and 'br' elements.
+fn ttml_spans(
+    node: Node,
+    styles: &HashMap<&str, Node>,
+    style: &CueStyle,
+    spans: &mut Vec<(String, CueStyle)>,
+) {
+    for child in node.children() {
+        if child.is_text() {
+            let text = child.text().unwrap_or_default();
+            spans.push((collapse_whitespace(text), style.clone()));
+            continue;
+        }
+
+        match child.tag_name().name() {
+            "br" => spans.push((String::from("\n"), style.clone())),
+            "span" => {
+                let style = ttml_style(child, styles, style);
+                ttml_spans(child, styles, &style, spans);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Computes the styling of a TTML element, from its referenced styles and its inline styling attributes.
+fn ttml_style(node: Node, styles: &HashMap<&str, Node>, parent: &CueStyle) -> CueStyle {
+    let mut style = parent.clone();
+
+    let referenced = local_attribute(node, "style")
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|id| styles.get(id).copied());
+
+    for styling in referenced.chain(std::iter::once(node)) {
+        if let Some(weight) = local_attribute(styling, "fontWeight") {
+            style.bold = weight == "bold";
+        }
+        if let Some(font_style) = local_attribute(styling, "fontStyle") {
+            style.italic = font_style == "italic" || font_style == "oblique";
+        }
+        if let Some(decoration) = local_attribute(styling, "textDecoration") {
+            style.underline = decoration
+                .split_whitespace()
+                .any(|value| value == "underline");
+        }
+        if let Some(color) = local_attribute(styling, "color") {
+            style.color = Some(color.to_uppercase());
+        }
+    }
+
+    style
+}
+
+/// Parses a TTML time expression, either a clock time like '00:01:02.500' or '00:01:02:15',
+/// or an offset time like '62.5s', '62500ms', '1.5m', '0.1h', '1875f' or '625t'.
+fn parse_ttml_time(time: &str, frame_rate: f64, tick_rate: f64) -> Result<f64> {
+    let time = time.trim();
+    let invalid = || Error::Captions(format!("invalid TTML time '{}'", time));
+
+    if time.contains(':') {
+        let parts: Vec<&str> = time.split(':').collect();
+        let number = |part: &str| part.parse::<f64>().map_err(|_| invalid());
+
+        return match parts.as_slice() {
+            [hours, minutes, seconds] => {
+                Ok(number(hours)? * 3600.0 + number(minutes)? * 60.0 + number(seconds)?)
+            }
+            [hours, minutes, seconds, frames] => Ok(number(hours)? * 3600.0
+                + number(minutes)? * 60.0
+                + number(seconds)?
+                + number(frames)? / frame_rate),
+            _ => Err(invalid()),
+        };
+    }
+
+    let split = time
+        .find(|c: char| c.is_ascii_alphabetic())
+        .ok_or_else(invalid)?;
+    let (value, unit) = time.split_at(split);
+    let value = value.parse::<f64>().map_err(|_| invalid())?;
+
+    match unit {
+        "h" => Ok(value * 3600.0),
+        "m" => Ok(value * 60.0),
+        "s" => Ok(value),
+        "ms" => Ok(value / 1000.0),
+        "f" => Ok(value / frame_rate),
+        "t" => Ok(value / tick_rate),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses a WebVTT document, whose blocks with a timing line are the cues.
+/// The comments, styles and regions blocks are skipped, as well as the cues settings.
+/// SRT documents are parsed the same way, their numbers being cues identifiers.
+fn parse_vtt(content: &str) -> Result<Vec<Cue>> {
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut cues = Vec::new();
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| line.trim().is_empty());
+
+        let Some(mut timing) = lines.next() else {
+            continue;
+        };
+        if !timing.contains("-->") {
+            // The line is the identifier of the cue, or the header of a block which is not a cue
+            match lines.next() {
+                Some(line) if line.contains("-->") => timing = line,
+                _ => continue,
+            }
+        }
+
+        let (start, end) = timing.split_once("-->").unwrap_or_default();
+        let end = end.split_whitespace().next().unwrap_or_default();
+        let (start, end) = (parse_vtt_time(start)?, parse_vtt_time(end)?);
+
+        let text = lines.collect::<Vec<_>>().join("\n");
+        cues.push(cue(start, end, vtt_spans(&text)));
+    }
+
+    Ok(cues)
+}
+
+/// Parses a WebVTT timestamp, like '01:02:03.456' or '02:03.456', or a SRT timestamp, like '01:02:03,456'.
+fn parse_vtt_time(time: &str) -> Result<f64> {
+    let time = time.trim();
+    let invalid = || Error::Captions(format!("invalid timestamp '{}'", time));
+
+    let normalized = time.replace(',', ".");
+    let seconds = normalized
+        .split(':')
+        .try_fold(0.0, |total, part| {
+            part.parse::<f64>().map(|part| total * 60.0 + part)
+        })
+        .map_err(|_| invalid())?;
+
+    match time.split(':').count() {
+        2 | 3 => Ok(seconds),
+        _ => Err(invalid()),
+    }
+}
+
+/// Splits the text of a WebVTT cue into styled parts, following its 'b', 'i' and 'u' tags.
+/// The other tags, e.g. the classes, voices or the timestamps of the words of automatic captions, are removed.
+fn vtt_spans(text: &str) -> Vec<(String, CueStyle)> {
+    let mut spans = Vec::new();
+    let mut style = CueStyle::default();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('<') {
+        spans.push((unescape_html(&rest[..open]), style.clone()));
+
+        let Some(close) = rest[open..].find('>') else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        rest = &rest[open + close + 1..];
+
+        let (closing, name) = match tag.strip_prefix('/') {
+            Some(name) => (true, name),
+            None => (false, tag),
+        };
+        match name.split(['.', ' ']).next().unwrap_or_default() {
+            "b" => style.bold = !closing,
+            "i" => style.italic = !closing,
+            "u" => style.underline = !closing,
+            _ => {}
+        }
+    }
+    spans.push((unescape_html(rest), style));
+
+    spans
+}
+
+/// Creates a cue from its styled parts, the styling of the cue being the one shared by all its parts.
+fn cue(start: f64, end: f64, spans: impl IntoIterator<Item = (String, CueStyle)>) -> Cue {
+    let mut text = String::new();
+    let mut style: Option<CueStyle> = None;
+
+    for (span, span_style) in spans {
+        if !span.trim().is_empty() {
+            style = Some(match style {
+                Some(style) => style.common(&span_style),
+                None => span_style,
+            });
+        }
+
+        text.push_str(&span);
+    }
+
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    Cue {
+        start,
+        end,
+        text: lines.join("\n").trim().to_string(),
+        style: style.unwrap_or_default(),
+    }
+}
+
+/// Parses an XML document, e.g. a timed text or TTML document.
+fn parse_xml(content: &str) -> Result<Document<'_>> {
+    Document::parse(content).map_err(|e| Error::Captions(e.to_string()))
+}
+
+/// Returns the descendant elements of the node with the given name, ignoring their namespace.
+fn elements<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.descendants()
+        .filter(move |node| node.is_element() && node.tag_name().name() == name)
+}
+
+/// Returns the value of the attribute of the node with the given name, ignoring its namespace.
+fn local_attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|attribute| attribute.name() == name)
+        .map(|attribute| attribute.value())
+}
+
+/// Returns the value of the attribute of the node or of its closest ancestor having it, e.g. the timing of a TTML 'div'.
+fn inherited_attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.ancestors()
+        .filter(|node| node.is_element())
+        .find_map(|node| local_attribute(node, name))
+}
+
+/// Returns the text of a node and its descendants.
+fn node_text(node: Node) -> String {
+    node.descendants()
+        .filter(|node| node.is_text())
+        .filter_map(|node| node.text())
+        .collect()
+}
+
+/// Parses a number of seconds (or milliseconds), defaulting to 0.
+fn parse_seconds(value: Option<&str>) -> f64 {
+    value
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .unwrap_or_default()
+}
+
+/// Collapses the whitespaces of a text into single spaces, like XML documents are rendered.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut whitespace = false;
+
+    for character in text.chars() {
+        if character.is_whitespace() {
+            whitespace = true;
+            continue;
+        }
+
+        // The leading whitespace is kept, as it separates the text from the previous element, e.g. a 'span'
+        if whitespace {
+            collapsed.push(' ');
+        }
+        whitespace = false;
+        collapsed.push(character);
+    }
+
+    if whitespace {
+        collapsed.push(' ');
+    }
+
+    collapsed
+}
+
+/// Decodes the HTML entities of a text, e.g. '&amp;' or '&#39;'.
+fn unescape_html(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end])?, end)));
+
+        match entity {
+            Some((character, end)) => {
+                unescaped.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+
+    unescaped
+}
+
+/// Decodes an HTML entity, without its '&' and ';' delimiters.
+fn decode_entity(entity: &str) -> Option<char> {
+    let code = match entity {
+        "amp" => return Some('&'),
+        "lt" => return Some('<'),
+        "gt" => return Some('>'),
+        "quot" => return Some('"'),
+        "apos" => return Some('\''),
+        "nbsp" => return Some('\u{a0}'),
+        "lrm" => return Some('\u{200e}'),
+        "rlm" => return Some('\u{200f}'),
+        _ => entity.strip_prefix('#')?,
+    };
+
+    let code = match code.strip_prefix(['x', 'X']) {
+        Some(hexadecimal) => u32::from_str_radix(hexadecimal, 16).ok()?,
+        None => code.parse().ok()?,
+    };
+
+    char::from_u32(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::captions::dedupe;
+
+    /// Returns the start, end and text of the cues, the times being rounded to the millisecond.
+    fn timings(cues: &[Cue]) -> Vec<(f64, f64, &str)> {
+        let round = |time: f64| (time * 1000.0).round() / 1000.0;
+
+        cues.iter()
+            .map(|cue| (round(cue.start), round(cue.end), cue.text.as_str()))
+            .collect()
+    }
+
+    fn style(bold: bool, italic: bool, color: Option<&str>) -> CueStyle {
+        CueStyle {
+            bold,
+            italic,
+            underline: false,
+            color: color.map(String::from),
+        }
+    }
+
+    #[test]
+    fn json3_automatic_captions() {
+        let content = include_str!("../../../tests/fixtures/captions/auto.json3");
+        let cues = parse_captions(content, Extension::Json3).unwrap();
+
+        // The window definition is skipped, and the line breaks appended between the lines are empty cues
+        assert_eq!(
+            timings(&cues),
+            vec![
+                (1.36, 5.52, "we're no strangers to love"),
+                (3.91, 5.52, ""),
+                (3.92, 8.88, "you know the rules"),
+                (5.51, 8.88, ""),
+                (5.52, 10.96, "and so do i"),
+            ]
+        );
+
+        assert_eq!(
+            timings(&dedupe(cues)),
+            vec![
+                (1.36, 3.92, "we're no strangers to love"),
+                (3.92, 5.52, "you know the rules"),
+                (5.52, 10.96, "and so do i"),
+            ]
+        );
+    }
+
+    #[test]
+    fn srv3_pens() {
+        let content = include_str!("../../../tests/fixtures/captions/pens.srv3");
+        let cues = parse_captions(content, Extension::Srv3).unwrap();
+
+        assert_eq!(
+            timings(&cues),
+            vec![
+                (1.2, 3.5, "Never gonna give you up"),
+                (3.5, 5.5, "Never gonna let you down"),
+                (5.5, 7.0, "Never gonna run around\nand desert you"),
+                (7.0, 8.8, ">> [Music]"),
+            ]
+        );
+
+        assert_eq!(cues[0].style, style(true, false, Some("#FEFEFE")));
+        assert_eq!(cues[1].style, style(false, true, Some("#FFFF00")));
+        assert_eq!(cues[2].style, CueStyle::default());
+        // Only a part of the cue is styled
+        assert_eq!(cues[3].style, CueStyle::default());
+    }
+
+    #[test]
+    fn ttml_ticks_and_frames() {
+        let content = include_str!("../../../tests/fixtures/captions/ticks.ttml");
+        let cues = parse_captions(content, Extension::Ttml).unwrap();
+
+        // 10,000,000 ticks per second, and 25 frames per second
+        assert_eq!(
+            timings(&cues),
+            vec![
+                (1.2, 3.5, "Never gonna give you up"),
+                (3.48, 5.0, "Never gonna\nlet you down"),
+                (5.5, 7.5, "Never gonna run around"),
+            ]
+        );
+
+        assert_eq!(cues[0].style, CueStyle::default());
+        assert_eq!(cues[1].style, style(false, true, Some("#FFFF00")));
+        assert_eq!(cues[2].style, CueStyle::default());
+    }
+
+    #[test]
+    fn ttml_default_rates() {
+        assert_eq!(parse_ttml_time("30f", 30.0, 1.0).unwrap(), 1.0);
+        assert_eq!(parse_ttml_time("00:00:01:15", 30.0, 1.0).unwrap(), 1.5);
+        assert_eq!(parse_ttml_time("2t", 30.0, 1.0).unwrap(), 2.0);
+        assert_eq!(parse_ttml_time("1500ms", 30.0, 1.0).unwrap(), 1.5);
+        assert!(parse_ttml_time("1.5x", 30.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn vtt_word_timestamps() {
+        let content = include_str!("../../../tests/fixtures/captions/words.vtt");
+        let cues = parse_captions(content, Extension::Vtt).unwrap();
+
+        // The header is skipped, as well as the cue settings and the timestamps of the words
+        assert_eq!(
+            timings(&cues),
+            vec![
+                (1.36, 3.91, "we're no strangers to love"),
+                (3.91, 3.92, "we're no strangers to love"),
+                (3.92, 5.51, "we're no strangers to love\nyou know the rules"),
+                (5.51, 5.52, "you know the rules"),
+                (5.52, 8.87, "you know the rules\nand so do i"),
+            ]
+        );
+
+        assert_eq!(
+            timings(&dedupe(cues)),
+            vec![
+                (1.36, 3.92, "we're no strangers to love"),
+                (3.92, 5.52, "you know the rules"),
+                (5.52, 8.87, "and so do i"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_format() {
+        assert!(parse_captions("", Extension::Unknown).is_err());
+    }
+}
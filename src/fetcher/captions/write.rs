@@ -0,0 +1,231 @@
+//! Writers of cues into the SRT and WebVTT formats.
+
+use crate::model::caption::{CaptionFormat, Cue, CueStyle};
+use std::fmt::Write;
+
+/// Writes the cues in the given format.
+///
+/// # Arguments
+///
+/// * `cues` - The cues to write, in chronological order.
+/// * `format` - The format to write the cues in.
+pub fn write_captions(cues: &[Cue], format: CaptionFormat) -> String {
+    match format {
+        CaptionFormat::Srt => to_srt(cues),
+        CaptionFormat::WebVtt => to_webvtt(cues),
+    }
+}
+
+/// Writes the cues in the SubRip format, styled with the 'b', 'i', 'u' and 'font' tags.
+/// As a blank line ends a cue, the blank lines of the texts are dropped, and the cues without text are skipped.
+/// The arrows of the texts ('-->') are shortened, as SubRip has no escaping and they would be read as timings.
+///
+/// # Arguments
+///
+/// * `cues` - The cues to write, in chronological order.
+pub fn to_srt(cues: &[Cue]) -> String {
+    let mut srt = String::new();
+
+    let texts = cues
+        .iter()
+        .map(|cue| (cue, cue_text(&cue.text).replace("-->", "->")));
+    for (index, (cue, text)) in texts.filter(|(_, text)| !text.is_empty()).enumerate() {
+        let mut text = style_text(&text, &cue.style);
+        if let Some(color) = &cue.style.color {
+            text = format!("<font color=\"{}\">{}</font>", color, text);
+        }
+
+        let _ = write!(
+            srt,
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1,
+            format_timestamp(cue.start, ','),
+            format_timestamp(cue.end, ','),
+            text
+        );
+    }
+
+    srt
+}
+
+/// Writes the cues in the WebVTT format, without positioning, styled with the 'b', 'i' and 'u' tags.
+/// As a blank line ends a cue, the blank lines of the texts are dropped, and the cues without text are skipped.
+/// The special characters of the texts are escaped, including the '>' of the arrows ('-->').
+///
+/// # Arguments
+///
+/// * `cues` - The cues to write, in chronological order.
+pub fn to_webvtt(cues: &[Cue]) -> String {
+    let mut vtt = String::from("WEBVTT\n");
+
+    for cue in cues {
+        let text = cue_text(&cue.text)
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        if text.is_empty() {
+            continue;
+        }
+
+        let _ = write!(
+            vtt,
+            "\n{} --> {}\n{}\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            style_text(&text, &cue.style)
+        );
+    }
+
+    vtt
+}
+
+/// Returns the text of a cue without its blank lines, which would end the cue.
+fn cue_text(text: &str) -> String {
+    let lines: Vec<_> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+
+    lines.join("\n")
+}
+
+/// Formats a time in seconds as a timestamp, e.g. '01:02:03.456' for WebVTT or '01:02:03,456' for SRT.
+pub(crate) fn format_timestamp(seconds: f64, separator: char) -> String {
+    let milliseconds = (seconds.max(0.0) * 1000.0).round() as u64;
+
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        milliseconds / 3_600_000,
+        milliseconds / 60_000 % 60,
+        milliseconds / 1000 % 60,
+        separator,
+        milliseconds % 1000
+    )
+}
+
+/// Wraps the text with the bold, italic and underline tags of its style.
+fn style_text(text: &str, style: &CueStyle) -> String {
+    let mut text = text.to_string();
+
+    for (enabled, tag) in [
+        (style.underline, 'u'),
+        (style.italic, 'i'),
+        (style.bold, 'b'),
+    ] {
+        if enabled {
+            text = format!("<{tag}>{text}</{tag}>");
+        }
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cue(start: f64, end: f64, text: &str) -> Cue {
+        Cue {
+            start,
+            end,
+            text: text.to_string(),
+            style: CueStyle::default(),
+        }
+    }
+
+    #[test]
+    fn format_timestamps() {
+        assert_eq!(format_timestamp(0.0, '.'), "00:00:00.000");
+        assert_eq!(format_timestamp(3723.4567, ','), "01:02:03,457");
+        assert_eq!(format_timestamp(59.9996, '.'), "00:01:00.000");
+        assert_eq!(format_timestamp(-1.0, '.'), "00:00:00.000");
+        assert_eq!(format_timestamp(360000.0, '.'), "100:00:00.000");
+    }
+
+    #[test]
+    fn write_srt() {
+        let styled = Cue {
+            style: CueStyle {
+                bold: true,
+                italic: true,
+                underline: false,
+                color: Some("#FFFF00".to_string()),
+            },
+            ..cue(2.5, 4.0, "Second & <last>")
+        };
+
+        assert_eq!(
+            to_srt(&[cue(0.0, 2.5, "First line\nSecond line"), styled]),
+            "1\n00:00:00,000 --> 00:00:02,500\nFirst line\nSecond line\n\n\
+             2\n00:00:02,500 --> 00:00:04,000\n<font color=\"#FFFF00\"><b><i>Second & <last></i></b></font>\n\n"
+        );
+        assert_eq!(to_srt(&[]), "");
+    }
+
+    #[test]
+    fn write_webvtt() {
+        let styled = Cue {
+            style: CueStyle {
+                bold: false,
+                italic: true,
+                underline: true,
+                color: Some("#FFFF00".to_string()),
+            },
+            ..cue(2.5, 4.0, "Second & <last>")
+        };
+
+        // The color is not written, as it would need a style block
+        assert_eq!(
+            to_webvtt(&[cue(0.0, 2.5, "First line\nSecond line"), styled]),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nFirst line\nSecond line\n\n\
+             00:00:02.500 --> 00:00:04.000\n<i><u>Second &amp; &lt;last&gt;</u></i>\n"
+        );
+        assert_eq!(to_webvtt(&[]), "WEBVTT\n");
+    }
+
+    #[test]
+    fn drop_blank_lines() {
+        let cues = [
+            cue(0.0, 1.0, "Before\n\n  \nAfter\n"),
+            cue(1.0, 2.0, " \n"),
+            cue(2.0, 3.0, "Last"),
+        ];
+
+        // The cue without text is skipped, and the following ones are numbered continuously
+        assert_eq!(
+            to_srt(&cues),
+            "1\n00:00:00,000 --> 00:00:01,000\nBefore\nAfter\n\n\
+             2\n00:00:02,000 --> 00:00:03,000\nLast\n\n"
+        );
+        assert_eq!(
+            to_webvtt(&cues),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.000\nBefore\nAfter\n\n\
+             00:00:02.000 --> 00:00:03.000\nLast\n"
+        );
+    }
+
+    #[test]
+    fn escape_arrows() {
+        let cues = [cue(0.0, 1.0, "00:00:05,000 --> 00:00:06,000")];
+
+        assert_eq!(
+            to_srt(&cues),
+            "1\n00:00:00,000 --> 00:00:01,000\n00:00:05,000 -> 00:00:06,000\n\n"
+        );
+        assert_eq!(
+            to_webvtt(&cues),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.000\n00:00:05,000 --&gt; 00:00:06,000\n"
+        );
+    }
+
+    #[test]
+    fn write_in_format() {
+        let cues = [cue(0.0, 1.0, "Text")];
+
+        assert_eq!(write_captions(&cues, CaptionFormat::Srt), to_srt(&cues));
+        assert_eq!(
+            write_captions(&cues, CaptionFormat::WebVtt),
+            to_webvtt(&cues)
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Tools for fetching channels from YouTube, tab by tab.
+//!
+//! Each tab is fetched flat with 'yt-dlp', and the channel information is taken from the first tab fetched as a whole,
+//! or from the home page of the channel.
+//! The tabs a channel does not have are left empty.
+
+use crate::error::{Error, Result};
+use crate::model::channel::{Channel, ChannelTab};
+use crate::model::playlist::PlaylistEntry;
+use crate::Youtube;
+use futures_util::TryStreamExt;
+use serde::Deserialize;
+
+/// The options of the channel fetching.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::channel::ChannelOptions;
+/// # use yt_dlp::model::channel::ChannelTab;
+/// let options = ChannelOptions {
+///     tabs: vec![ChannelTab::Videos, ChannelTab::Streams],
+///     uploaded_after: Some(1704067200),
+///     uploaded_before: None,
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelOptions {
+    /// The tabs to fetch the entries of, all of them by default.
+    pub tabs: Vec<ChannelTab>,
+    /// Only keeps the videos uploaded at or after this date, as a Unix timestamp.
+    pub uploaded_after: Option<i64>,
+    /// Only keeps the videos uploaded before this date, as a Unix timestamp.
+    pub uploaded_before: Option<i64>,
+}
+
+impl Default for ChannelOptions {
+    fn default() -> Self {
+        Self {
+            tabs: ChannelTab::ALL.to_vec(),
+            uploaded_after: None,
+            uploaded_before: None,
+        }
+    }
+}
+
+impl ChannelOptions {
+    /// Checks if the videos are filtered by upload date.
+    pub fn has_date_filter(&self) -> bool {
+        self.uploaded_after.is_some() || self.uploaded_before.is_some()
+    }
+
+    /// Checks if an entry was uploaded in the requested dates.
+    /// The entries without upload date are kept, as they cannot be filtered.
+    fn matches(&self, entry: &PlaylistEntry) -> bool {
+        let Some(upload_date) = entry.upload_date else {
+            return true;
+        };
+
+        self.uploaded_after.is_none_or(|after| upload_date >= after)
+            && self
+                .uploaded_before
+                .is_none_or(|before| upload_date < before)
+    }
+}
+
+/// The entries of a fetched tab.
+#[derive(Debug, Deserialize)]
+struct TabEntries {
+    entries: Vec<PlaylistEntry>,
+}
+
+impl Youtube {
+    /// Fetch the channel information from the given URL, with the entries of the tabs of the options.
+    /// The tabs the channel does not have are left empty.
+    ///
+    /// When filtering by upload date, the upload dates of the videos are approximated by 'yt-dlp'
+    /// from their relative dates, e.g. '2 weeks ago', and the videos without upload date are kept.
+    /// The tabs of videos are listed newest first, so they are only listed up to the first video uploaded before
+    /// [`ChannelOptions::uploaded_after`].
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the channel, e.g. 'https://www.youtube.com/@username'.
+    /// * `options` - The tabs to fetch, and the upload dates of the videos to keep.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the URL is not a channel,
+    /// or if the channel information or its tabs could not be fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::channel::ChannelOptions;
+    /// # use yt_dlp::model::channel::ChannelTab;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/@RickAstleyYT");
+    /// let options = ChannelOptions {
+    ///     tabs: vec![ChannelTab::Videos, ChannelTab::Shorts],
+    ///     ..Default::default()
+    /// };
+    /// let channel = fetcher.fetch_channel(url, &options).await?;
+    ///
+    /// println!("{} has {} videos", channel.name, channel.videos.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub async fn fetch_channel(&self, url: String, options: &ChannelOptions) -> Result<Channel> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching channel information for {}", url);
+
+        let base_url = channel_url(&url);
+        let mut channel: Option<Channel> = None;
+        let mut tabs = Vec::with_capacity(options.tabs.len());
+
+        for tab in &options.tabs {
+            let tab_url = format!("{}/{}", base_url, tab.path());
+
+            let fetched = match (tab.has_videos(), options.uploaded_after) {
+                (true, Some(uploaded_after)) => self
+                    .fetch_recent_entries(tab_url, uploaded_after)
+                    .await
+                    .map(|entries| (None, entries)),
+                (has_videos, _) => self
+                    .fetch_tab(tab_url, has_videos && options.has_date_filter())
+                    .await
+                    .map(|(channel, entries)| (Some(channel), entries)),
+            };
+
+            let (tab_channel, mut entries) = match fetched {
+                Ok(fetched) => fetched,
+                Err(error) if is_missing_tab(&error) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("The channel has no {} tab, skipping", tab.path());
+
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+            if tab.has_videos() {
+                entries.retain(|entry| options.matches(entry));
+            }
+            if channel.is_none() {
+                channel = tab_channel;
+            }
+            tabs.push((*tab, entries));
+        }
+
+        let mut channel = match channel {
+            Some(channel) => channel,
+            None => {
+                // Without tabs fetched as a whole, the channel information is taken from its home page
+                let output = self.fetch_flat_json(base_url, Vec::new()).await?;
+
+                serde_json::from_str(&output).map_err(Error::Serde)?
+            }
+        };
+
+        for (tab, entries) in tabs {
+            *channel.entries_mut(tab) = entries;
+        }
+
+        Ok(channel)
+    }
+
+    /// Fetches a tab as a whole, and returns the channel information along with the entries of the tab.
+    async fn fetch_tab(
+        &self,
+        tab_url: String,
+        approximate_date: bool,
+    ) -> Result<(Channel, Vec<PlaylistEntry>)> {
+        let args = match approximate_date {
+            true => approximate_date_args(),
+            false => Vec::new(),
+        };
+
+        let output = self.fetch_flat_json(tab_url, args).await?;
+        let entries = serde_json::from_str::<TabEntries>(&output)
+            .map_err(Error::Serde)?
+            .entries;
+        let channel = serde_json::from_str(&output).map_err(Error::Serde)?;
+
+        Ok((channel, entries))
+    }
+
+    /// Fetches the entries of a tab uploaded at or after the given date, as a Unix timestamp.
+    /// As the tabs list their videos newest first, 'yt-dlp' stops listing the tab at the first older video,
+    /// instead of listing the whole tab before the entries are filtered.
+    async fn fetch_recent_entries(
+        &self,
+        tab_url: String,
+        uploaded_after: i64,
+    ) -> Result<Vec<PlaylistEntry>> {
+        let mut args = approximate_date_args();
+        args.extend([
+            String::from("--flat-playlist"),
+            String::from("--break-match-filters"),
+            format!("timestamp>={}", uploaded_after),
+        ]);
+
+        self.stream_json(tab_url, args).await?.try_collect().await
+    }
+}
+
+/// Returns the arguments of 'yt-dlp' approximating the upload dates of the videos of a tab from their relative dates,
+/// e.g. '2 weeks ago', as they are not listed otherwise.
+fn approximate_date_args() -> Vec<String> {
+    vec![
+        String::from("--extractor-args"),
+        String::from("youtubetab:approximate_date"),
+    ]
+}
+
+/// Checks if the error was raised by 'yt-dlp' because the channel does not have the fetched tab,
+/// e.g. 'This channel does not have a shorts tab'.
+fn is_missing_tab(error: &Error) -> bool {
+    matches!(error, Error::Command(message) if message.contains("does not have a"))
+}
+
+/// Returns the URL of the channel, without trailing slash nor tab.
+fn channel_url(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+
+    let without_tab = ChannelTab::ALL
+        .iter()
+        .map(|tab| tab.path())
+        .chain(["featured", "live", "community", "about"])
+        .find_map(|path| url.strip_suffix(path)?.strip_suffix('/'));
+
+    without_tab.unwrap_or(url).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an entry uploaded at the given date, if any.
+    fn entry(upload_date: Option<i64>) -> PlaylistEntry {
+        let entry = serde_json::json!({
+            "id": "dQw4w9WgXcQ",
+            "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "timestamp": upload_date,
+        });
+
+        serde_json::from_value(entry).unwrap()
+    }
+
+    #[test]
+    fn channel_urls() {
+        let url = "https://www.youtube.com/@RickAstleyYT";
+
+        assert_eq!(channel_url(url), url);
+        assert_eq!(channel_url("https://www.youtube.com/@RickAstleyYT/"), url);
+        assert_eq!(
+            channel_url("https://www.youtube.com/@RickAstleyYT/videos"),
+            url
+        );
+        assert_eq!(
+            channel_url("https://www.youtube.com/@RickAstleyYT/shorts/"),
+            url
+        );
+        assert_eq!(
+            channel_url("https://www.youtube.com/@RickAstleyYT/streams"),
+            url
+        );
+        assert_eq!(
+            channel_url("https://www.youtube.com/@RickAstleyYT/playlists"),
+            url
+        );
+        assert_eq!(
+            channel_url("https://www.youtube.com/@RickAstleyYT/featured"),
+            url
+        );
+        assert_eq!(
+            channel_url("https://www.youtube.com/@RickAstleyYT/about"),
+            url
+        );
+
+        // Only a whole tab segment is removed
+        assert_eq!(
+            channel_url("https://www.youtube.com/@myvideos"),
+            "https://www.youtube.com/@myvideos"
+        );
+        assert_eq!(
+            channel_url("https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw/live"),
+            "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw"
+        );
+    }
+
+    #[test]
+    fn match_upload_dates() {
+        let options = ChannelOptions {
+            uploaded_after: Some(1000),
+            uploaded_before: Some(2000),
+            ..ChannelOptions::default()
+        };
+        assert!(options.has_date_filter());
+
+        // The lower bound is inclusive, the upper one exclusive
+        assert!(!options.matches(&entry(Some(999))));
+        assert!(options.matches(&entry(Some(1000))));
+        assert!(options.matches(&entry(Some(1999))));
+        assert!(!options.matches(&entry(Some(2000))));
+
+        // The entries without upload date are kept
+        assert!(options.matches(&entry(None)));
+
+        let options = ChannelOptions {
+            uploaded_before: Some(2000),
+            ..ChannelOptions::default()
+        };
+        assert!(options.matches(&entry(Some(0))));
+        assert!(!options.matches(&entry(Some(3000))));
+
+        let options = ChannelOptions::default();
+        assert!(!options.has_date_filter());
+        assert!(options.matches(&entry(Some(0))));
+    }
+
+    #[test]
+    fn missing_tabs() {
+        let error = Error::Command(String::from(
+            "[youtube:tab] @RickAstleyYT: This channel does not have a shorts tab",
+        ));
+        assert!(is_missing_tab(&error));
+
+        assert!(!is_missing_tab(&Error::Command(String::from(
+            "Process failed with code 1"
+        ))));
+        assert!(!is_missing_tab(&Error::Cancelled));
+    }
+}
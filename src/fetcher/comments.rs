@@ -0,0 +1,248 @@
+//! Tools for fetching the comments of videos from YouTube.
+//!
+//! The comments are extracted by 'yt-dlp' along with the video information, within the limits of the options.
+
+use crate::error::{Error, Result};
+use crate::executor::Executor;
+use crate::model::comment::{Comment, CommentThread};
+use crate::{utils, Youtube};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// The timeout of the comments extraction, long enough to list the comments of popular videos.
+const COMMENTS_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The order the comments are extracted in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CommentSort {
+    /// The top comments first, as displayed by default on YouTube.
+    #[default]
+    Top,
+    /// The newest comments first.
+    New,
+}
+
+/// The options of the comments extraction.
+/// The limits are passed to 'yt-dlp', which stops extracting comments once they are reached.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::comments::{CommentOptions, CommentSort};
+/// let options = CommentOptions {
+///     max_comments: Some(100),
+///     max_depth: Some(1),
+///     sort: CommentSort::New,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommentOptions {
+    /// The maximum number of comments, including the replies.
+    pub max_comments: Option<usize>,
+    /// The maximum number of top-level comments.
+    pub max_parents: Option<usize>,
+    /// The maximum number of replies, in all the threads.
+    pub max_replies: Option<usize>,
+    /// The maximum number of replies in each thread.
+    pub max_replies_per_thread: Option<usize>,
+    /// The maximum depth of the comments, 1 only extracting the top-level comments.
+    pub max_depth: Option<usize>,
+    /// The order the comments are extracted in.
+    pub sort: CommentSort,
+}
+
+impl CommentOptions {
+    /// Builds the 'youtube' extractor arguments of 'yt-dlp' for these options,
+    /// e.g. 'youtube:max_comments=100,all,all,all,all;comment_sort=top'.
+    fn extractor_args(&self) -> String {
+        let limits = [
+            self.max_comments,
+            self.max_parents,
+            self.max_replies,
+            self.max_replies_per_thread,
+            self.max_depth,
+        ]
+        .map(|limit| limit.map_or(String::from("all"), |limit| limit.to_string()));
+
+        let sort = match self.sort {
+            CommentSort::Top => "top",
+            CommentSort::New => "new",
+        };
+
+        format!(
+            "youtube:max_comments={};comment_sort={}",
+            limits.join(","),
+            sort
+        )
+    }
+}
+
+/// The comments extracted along with the video information.
+#[derive(Debug, Deserialize)]
+struct Comments {
+    #[serde(default)]
+    comments: Option<Vec<Comment>>,
+}
+
+impl Youtube {
+    /// Fetch the comments of the video from the given URL, as a flat list.
+    /// The replies follow the comment they reply to, see [`CommentThread::from_comments`] to arrange them into threads.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to fetch the comments of.
+    /// * `options` - The limits and order of the comments.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the comments could not be fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::comments::CommentOptions;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let options = CommentOptions {
+    ///     max_comments: Some(50),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let comments = fetcher.fetch_comments(url, &options).await?;
+    /// for comment in comments.iter().filter(|comment| comment.is_pinned) {
+    ///     println!("{:?}: {}", comment.author, comment.text);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub async fn fetch_comments(
+        &self,
+        url: String,
+        options: &CommentOptions,
+    ) -> Result<Vec<Comment>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching comments for {}", url);
+
+        let comments_args = vec![
+            String::from("--no-progress"),
+            String::from("--dump-json"),
+            String::from("--write-comments"),
+            String::from("--extractor-args"),
+            options.extractor_args(),
+            url,
+        ];
+
+        let mut final_args = self.args.clone();
+        final_args.append(&mut self.network_args());
+        final_args.append(&mut utils::to_owned(comments_args));
+
+        let executor = Executor::new(self.libraries.youtube.clone(), COMMENTS_TIMEOUT, final_args)
+            .with_env(self.network_env()?);
+
+        let output = self
+            .retry
+            .run_with_cancellation(&self.cancellation, || {
+                executor.execute_with_cancellation(&self.cancellation)
+            })
+            .await?;
+        let comments: Comments = serde_json::from_str(&output.stdout).map_err(Error::Serde)?;
+
+        Ok(comments.comments.unwrap_or_default())
+    }
+
+    /// Fetch the comments of the video from the given URL, arranged into threads of replies.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to fetch the comments of.
+    /// * `options` - The limits and order of the comments.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the comments could not be fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::comments::{CommentOptions, CommentSort};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let options = CommentOptions {
+    ///     max_parents: Some(20),
+    ///     sort: CommentSort::Top,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let threads = fetcher.fetch_comment_threads(url, &options).await?;
+    /// for thread in &threads {
+    ///     println!("{} ({} replies)", thread.comment.text, thread.comment_count() - 1);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub async fn fetch_comment_threads(
+        &self,
+        url: String,
+        options: &CommentOptions,
+    ) -> Result<Vec<CommentThread>> {
+        let comments = self.fetch_comments(url, options).await?;
+
+        Ok(CommentThread::from_comments(comments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extractor_args_without_limits() {
+        assert_eq!(
+            CommentOptions::default().extractor_args(),
+            "youtube:max_comments=all,all,all,all,all;comment_sort=top"
+        );
+    }
+
+    #[test]
+    fn extractor_args_with_limits() {
+        let options = CommentOptions {
+            max_comments: Some(100),
+            max_replies: Some(20),
+            max_depth: Some(1),
+            sort: CommentSort::New,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.extractor_args(),
+            "youtube:max_comments=100,all,20,all,1;comment_sort=new"
+        );
+    }
+}
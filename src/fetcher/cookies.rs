@@ -0,0 +1,528 @@
+//! Cookies to authenticate the requests, e.g. for members-only or age-restricted videos.
+//!
+//! The cookies are read from, and written to, the Netscape 'cookies.txt' format used by 'yt-dlp' and most browser extensions.
+
+use crate::error::{Error, Result};
+use derive_more::Display;
+use reqwest::cookie::Jar;
+use reqwest::Url;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt;
+
+/// The header written at the top of Netscape cookie files.
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+
+/// The prefix of the domain of 'HttpOnly' cookies, in Netscape cookie files.
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// Where the cookies come from.
+#[derive(Debug, Clone, Display)]
+pub enum CookieSource {
+    /// A Netscape 'cookies.txt' file, e.g. exported from a browser.
+    #[display("File: {}", _0.display())]
+    File(PathBuf),
+    /// Cookies held in memory.
+    #[display("Jar: {} cookies", _0.cookies.len())]
+    Jar(CookieJar),
+}
+
+/// A single cookie, as stored in a Netscape cookie file.
+/// The value is never printed by the `Debug` implementation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Cookie {
+    /// The domain the cookie belongs to, e.g. '.youtube.com'.
+    pub domain: String,
+    /// Whether the cookie is also sent to the subdomains.
+    pub include_subdomains: bool,
+    /// The path the cookie is restricted to.
+    pub path: String,
+    /// Whether the cookie is only sent over HTTPS.
+    pub secure: bool,
+    /// Whether the cookie is hidden from scripts.
+    pub http_only: bool,
+    /// The expiration date, as a UNIX timestamp, or none for a session cookie.
+    pub expires: Option<u64>,
+    /// The name of the cookie.
+    pub name: String,
+    /// The value of the cookie.
+    pub value: String,
+}
+
+impl Cookie {
+    /// Creates a new secure session cookie, sent to the given domain and its subdomains.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The domain the cookie belongs to, e.g. '.youtube.com'.
+    /// * `name` - The name of the cookie.
+    /// * `value` - The value of the cookie.
+    pub fn new(domain: impl AsRef<str>, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        Self {
+            domain: domain.as_ref().to_string(),
+            include_subdomains: true,
+            path: String::from("/"),
+            secure: true,
+            http_only: false,
+            expires: None,
+            name: name.as_ref().to_string(),
+            value: value.as_ref().to_string(),
+        }
+    }
+
+    /// Checks if the cookie is expired.
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Parses a line of a Netscape cookie file.
+    /// The line is made of 7 fields separated by tabs: domain, subdomains flag, path, secure flag, expiration, name and value.
+    fn parse(line: &str) -> std::result::Result<Self, String> {
+        let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, include_subdomains, path, secure, expires, name, value] = fields[..] else {
+            return Err(format!(
+                "expected 7 tab-separated fields, found {}",
+                fields.len()
+            ));
+        };
+
+        if domain.is_empty() {
+            return Err(String::from("the domain is empty"));
+        }
+
+        let expires = match expires.parse::<i64>() {
+            Ok(expires) if expires > 0 => Some(expires as u64),
+            Ok(_) => None,
+            Err(_) => return Err(format!("invalid expiration date '{}'", expires)),
+        };
+
+        Ok(Self {
+            domain: domain.to_string(),
+            include_subdomains: parse_flag(include_subdomains)?,
+            path: path.to_string(),
+            secure: parse_flag(secure)?,
+            http_only,
+            expires,
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Formats the cookie as a line of a Netscape cookie file.
+    fn to_netscape(&self) -> String {
+        let prefix = if self.http_only { HTTP_ONLY_PREFIX } else { "" };
+
+        format!(
+            "{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            prefix,
+            self.domain,
+            format_flag(self.include_subdomains),
+            self.path,
+            format_flag(self.secure),
+            self.expires.unwrap_or_default(),
+            self.name,
+            self.value
+        )
+    }
+
+    /// Formats the cookie as a 'Set-Cookie' header, and returns the URL it was set from.
+    fn to_set_cookie(&self) -> Option<(String, Url)> {
+        let host = self.domain.trim_start_matches('.');
+        let scheme = if self.secure { "https" } else { "http" };
+        let url = Url::parse(&format!("{}://{}{}", scheme, host, self.path)).ok()?;
+
+        let mut header = format!("{}={}; Path={}", self.name, self.value, self.path);
+        if self.include_subdomains {
+            header.push_str(&format!("; Domain={}", host));
+        }
+        if self.secure {
+            header.push_str("; Secure");
+        }
+        if self.http_only {
+            header.push_str("; HttpOnly");
+        }
+
+        Some((header, url))
+    }
+}
+
+impl Debug for Cookie {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cookie")
+            .field("domain", &self.domain)
+            .field("path", &self.path)
+            .field("name", &self.name)
+            .field("expires", &self.expires)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A set of cookies, which can be loaded from or saved to a Netscape cookie file.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::cookies::{Cookie, CookieJar};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let jar = CookieJar::load("cookies.txt").await?;
+/// println!("Loaded {} cookies", jar.cookies.len());
+///
+/// let mut jar = CookieJar::default();
+/// jar.add(Cookie::new(".youtube.com", "SID", "secret"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookieJar {
+    /// The cookies of the jar.
+    pub cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    /// Adds a cookie to the jar, replacing the cookie with the same domain, path and name.
+    ///
+    /// # Arguments
+    ///
+    /// * `cookie` - The cookie to add.
+    pub fn add(&mut self, cookie: Cookie) {
+        self.cookies.retain(|existing| {
+            existing.domain != cookie.domain
+                || existing.path != cookie.path
+                || existing.name != cookie.name
+        });
+
+        self.cookies.push(cookie);
+    }
+
+    /// Parses the content of a Netscape cookie file.
+    /// The comments and empty lines are ignored, as well as the expired cookies.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the cookie file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a line is not a valid cookie.
+    pub fn parse(content: impl AsRef<str>) -> Result<Self> {
+        let mut jar = Self::default();
+
+        for (index, line) in content.as_ref().lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+
+            let comment = line.starts_with('#') && !line.starts_with(HTTP_ONLY_PREFIX);
+            if comment || line.trim().is_empty() {
+                continue;
+            }
+
+            let cookie = Cookie::parse(line)
+                .map_err(|reason| Error::Cookies(format!("line {}: {}", index + 1, reason)))?;
+
+            if !cookie.is_expired() {
+                jar.add(cookie);
+            }
+        }
+
+        Ok(jar)
+    }
+
+    /// Loads the cookies of a Netscape cookie file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the cookie file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read, or if it is not a valid cookie file.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path).await?;
+        Self::parse(content)
+    }
+
+    /// Saves the cookies to a Netscape cookie file, only readable by the current user on Unix,
+    /// even if the file already existed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the cookie file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be written.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = options.open(path).await?;
+
+        // The mode is only applied to the created files
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))
+            .await?;
+
+        file.write_all(self.to_netscape().as_bytes()).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+
+    /// Formats the cookies as a Netscape cookie file.
+    pub fn to_netscape(&self) -> String {
+        let mut content = format!("{}\n", NETSCAPE_HEADER);
+
+        for cookie in &self.cookies {
+            content.push_str(&cookie.to_netscape());
+            content.push('\n');
+        }
+
+        content
+    }
+
+    /// Returns the cookie store for the HTTP client, holding all the cookies of the jar.
+    pub fn to_reqwest(&self) -> Arc<Jar> {
+        let jar = Jar::default();
+
+        for (header, url) in self.cookies.iter().filter_map(Cookie::to_set_cookie) {
+            jar.add_cookie_str(&header, &url);
+        }
+
+        Arc::new(jar)
+    }
+}
+
+/// Parses a 'TRUE' or 'FALSE' field of a Netscape cookie file.
+fn parse_flag(flag: &str) -> std::result::Result<bool, String> {
+    match flag.to_ascii_uppercase().as_str() {
+        "TRUE" => Ok(true),
+        "FALSE" => Ok(false),
+        _ => Err(format!("invalid flag '{}', expected TRUE or FALSE", flag)),
+    }
+}
+
+/// Formats a boolean field of a Netscape cookie file.
+fn format_flag(flag: bool) -> &'static str {
+    if flag {
+        "TRUE"
+    } else {
+        "FALSE"
+    }
+}
+
+/// A private copy of the cookies, written for 'yt-dlp' in the temporary directory.
+/// 'yt-dlp' writes its cookie jar back to the file on exit, so the cookie files of the users are never given to it.
+///
+/// The file is only readable by the current user, and removed once the last fetcher holding it is dropped,
+/// or when its cookies are replaced.
+#[derive(Debug, Clone)]
+pub struct CookieFile {
+    /// The temporary file, shared by the clones of the fetcher.
+    file: Arc<NamedTempFile>,
+}
+
+impl CookieFile {
+    /// Writes the cookies of the jar to a new private file.
+    ///
+    /// # Arguments
+    ///
+    /// * `jar` - The cookies to write.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be created or written.
+    pub async fn write(jar: &CookieJar) -> Result<Self> {
+        let file = tempfile::Builder::new()
+            .prefix("yt-dlp-cookies-")
+            .suffix(".txt")
+            .tempfile()?;
+
+        jar.save(file.path()).await?;
+
+        Ok(Self {
+            file: Arc::new(file),
+        })
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAR_FUTURE: u64 = 4102444800;
+
+    #[test]
+    fn parse_cookie_file() {
+        let content = include_str!("../../tests/fixtures/cookies/cookies.txt");
+        let jar = CookieJar::parse(content).unwrap();
+
+        // The comments and expired cookies are skipped, and the last cookie with the same name replaces the first one
+        let names: Vec<_> = jar
+            .cookies
+            .iter()
+            .map(|cookie| (cookie.name.as_str(), cookie.value.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("HSID", "hidden"),
+                ("PREF", "f6=40000000&hl=en"),
+                ("VISITOR", "abc"),
+                ("SID", "replaced"),
+            ]
+        );
+
+        assert_eq!(
+            jar.cookies[0],
+            Cookie {
+                http_only: true,
+                expires: Some(FAR_FUTURE),
+                ..Cookie::new(".youtube.com", "HSID", "hidden")
+            }
+        );
+
+        // The flags are case-insensitive, and the expiration dates up to 0 are session cookies
+        let preferences = &jar.cookies[1];
+        assert_eq!(preferences.domain, "www.youtube.com");
+        assert!(!preferences.include_subdomains);
+        assert_eq!(preferences.path, "/watch");
+        assert!(!preferences.secure);
+        assert!(!preferences.http_only);
+        assert_eq!(preferences.expires, None);
+
+        let visitor = &jar.cookies[2];
+        assert!(!visitor.include_subdomains);
+        assert!(visitor.secure);
+        assert_eq!(visitor.expires, None);
+    }
+
+    #[test]
+    fn parse_windows_line_endings() {
+        let content =
+            "# Netscape HTTP Cookie File\r\n.youtube.com\tTRUE\t/\tTRUE\t0\tSID\tsecret\r\n";
+        let jar = CookieJar::parse(content).unwrap();
+
+        assert_eq!(
+            jar.cookies,
+            vec![Cookie::new(".youtube.com", "SID", "secret")]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let invalid = [
+            (
+                ".youtube.com\tTRUE\t/\tTRUE\t0\tSID",
+                "line 1: expected 7 tab-separated fields, found 6",
+            ),
+            (
+                ".youtube.com TRUE / TRUE 0 SID secret",
+                "line 1: expected 7 tab-separated fields, found 1",
+            ),
+            (
+                "\tTRUE\t/\tTRUE\t0\tSID\tsecret",
+                "line 1: the domain is empty",
+            ),
+            (
+                ".youtube.com\tYES\t/\tTRUE\t0\tSID\tsecret",
+                "line 1: invalid flag 'YES', expected TRUE or FALSE",
+            ),
+            (
+                ".youtube.com\tTRUE\t/\t1\t0\tSID\tsecret",
+                "line 1: invalid flag '1', expected TRUE or FALSE",
+            ),
+            (
+                "#\n.youtube.com\tTRUE\t/\tTRUE\tnever\tSID\tsecret",
+                "line 2: invalid expiration date 'never'",
+            ),
+        ];
+
+        for (content, expected) in invalid {
+            match CookieJar::parse(content) {
+                Err(Error::Cookies(reason)) => assert_eq!(reason, expected),
+                result => panic!("{:?} should not be parsed, got {:?}", content, result),
+            }
+        }
+    }
+
+    #[test]
+    fn netscape_round_trip() {
+        let mut jar = CookieJar::default();
+        jar.add(Cookie::new(".youtube.com", "SID", "secret"));
+        jar.add(Cookie {
+            include_subdomains: false,
+            path: String::from("/watch"),
+            secure: false,
+            http_only: true,
+            expires: Some(FAR_FUTURE),
+            ..Cookie::new("www.youtube.com", "HSID", "a=b;c")
+        });
+
+        let content = jar.to_netscape();
+        assert_eq!(
+            content,
+            "# Netscape HTTP Cookie File\n\
+             .youtube.com\tTRUE\t/\tTRUE\t0\tSID\tsecret\n\
+             #HttpOnly_www.youtube.com\tFALSE\t/watch\tFALSE\t4102444800\tHSID\ta=b;c\n"
+        );
+        assert_eq!(CookieJar::parse(content).unwrap(), jar);
+    }
+
+    #[test]
+    fn debug_hides_value() {
+        let cookie = Cookie::new(".youtube.com", "SID", "secret");
+
+        assert!(!format!("{:?}", cookie).contains("secret"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn save_private_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("cookies.txt");
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        let mut jar = CookieJar::default();
+        jar.add(Cookie::new(".youtube.com", "SID", "secret"));
+
+        jar.save(&path).await.unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(CookieJar::load(&path).await.unwrap(), jar);
+
+        // An existing file is made private too
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        jar.save(&path).await.unwrap();
+        assert_eq!(mode(&path), 0o600);
+
+        let file = CookieFile::write(&jar).await.unwrap();
+        assert_eq!(mode(file.path()), 0o600);
+        assert_eq!(CookieJar::load(file.path()).await.unwrap(), jar);
+
+        let path = file.path().to_path_buf();
+        drop(file);
+        assert!(!path.exists());
+    }
+}
@@ -7,17 +7,6 @@ use crate::utils::platform::{Architecture, Platform};
 use derive_more::Display;
 use std::path::{Path, PathBuf};
 
-#[cfg(target_os = "windows")]
-const FFMPEG_BUILD_URL: &'static str = "https://www.gyan.dev/ffmpeg/builds/ffmpeg-release-essentials.zip";
-
-#[cfg(all(target_os = "macos", target_arch = "x86_64"))]
-const FFMPEG_BUILD_URL: &'static str = "https://www.osxexperts.net/ffmpeg71intel.zip";
-#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
-const FFMPEG_BUILD_URL: &'static str = "https://www.osxexperts.net/ffmpeg71arm.zip";
-
-#[cfg(target_os = "linux")]
-const FFMPEG_BUILD_URL: &'static str = "https://johnvansickle.com/ffmpeg/releases/ffmpeg-release-{}-static.tar.xz";
-
 /// The ffmpeg fetcher is responsible for fetching the ffmpeg binary for the current platform and architecture.
 /// It can also extract the binary from the downloaded archive.
 ///
@@ -127,7 +116,7 @@ impl BuildFetcher {
             _ => return None,
         };
 
-        let name = url.split('/').last()?;
+        let name = url.split('/').next_back()?;
         let asset = Asset {
             name: name.to_string(),
             download_url: url.to_string(),
@@ -195,7 +184,7 @@ impl BuildFetcher {
                 _ => return Err(Error::Binary(platform, architecture)),
             };
 
-            return self.extract_archive(archive, destination.clone(), extracted).await
+            return self.extract_archive(archive.as_ref().to_path_buf(), destination.clone(), extracted).await
         }
     }
 
@@ -237,7 +226,7 @@ impl BuildFetcher {
     pub async fn extract_archive(&self, archive: PathBuf, destination: PathBuf, extracted: impl AsRef<str>) -> Result<PathBuf> {
         file_system::extract_tar_xz(archive.clone(), destination.clone()).await?;
 
-        let extracted = destination.join(extracted);
+        let extracted = destination.join(extracted.as_ref());
         let executable = extracted.join("ffmpeg");
 
         let parent = file_system::try_parent(&destination)?;
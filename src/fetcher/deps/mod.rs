@@ -1,10 +1,12 @@
 //! The fetchers for required dependencies.
 
 use crate::error::Result;
+use crate::fetcher::client::{default_client, ClientOptions, ProxyConfig};
 use crate::fetcher::deps::ffmpeg::BuildFetcher;
 use crate::fetcher::deps::youtube::GitHubFetcher;
 use crate::fetcher::Fetcher;
 use crate::utils::file_system;
+use crate::utils::retry::RetryPolicy;
 use crate::{ternary, utils};
 use derive_more::{Constructor, Display};
 use serde::Deserialize;
@@ -30,10 +32,14 @@ pub mod youtube;
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Constructor, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct LibraryInstaller {
     /// The destination directory for the libraries.
     pub destination: PathBuf,
+    /// The policy to retry failed downloads with.
+    pub retry: RetryPolicy,
+    /// The HTTP client to fetch the releases and download the assets with.
+    pub client: reqwest::Client,
 }
 
 /// The installed libraries.
@@ -63,6 +69,57 @@ pub struct Libraries {
 }
 
 impl LibraryInstaller {
+    /// Creates a new installer, writing the libraries to the given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The destination directory for the libraries.
+    pub fn new(destination: PathBuf) -> Self {
+        Self {
+            destination,
+            retry: RetryPolicy::default(),
+            client: default_client(),
+        }
+    }
+
+    /// Sets the policy to retry failed downloads with.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The policy to retry failed downloads with.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the HTTP client to fetch the releases and download the assets with.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The HTTP client to use, e.g. built from [`ClientOptions`].
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets the proxy to fetch the releases and download the assets through.
+    ///
+    /// # Arguments
+    ///
+    /// * `proxy` - The proxy to use.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the proxy is invalid, or if the HTTP client could not be built.
+    pub fn with_proxy(self, proxy: ProxyConfig) -> Result<Self> {
+        let options = ClientOptions {
+            proxy: Some(proxy),
+            ..ClientOptions::default()
+        };
+
+        Ok(self.with_client(options.build()?))
+    }
+
     /// Install yt-dlp from the main repository.
     pub async fn install_youtube(&self, custom_name: Option<String>) -> Result<PathBuf> {
         self.install_youtube_from_repo("yt-dlp", "yt-dlp", None, custom_name)
@@ -88,13 +145,17 @@ impl LibraryInstaller {
 
         file_system::create_dir(self.destination.clone())?;
 
-        let fetcher = GitHubFetcher::new(owner, repo);
+        let fetcher = GitHubFetcher::new(owner, repo).with_client(self.client.clone());
 
         let name = custom_name.unwrap_or(String::from("yt-dlp"));
         let path = self.destination.join(utils::find_executable(&name));
 
         let release = fetcher.fetch_release(auth_token).await?;
-        release.download(path.clone()).await?;
+        Fetcher::new(&release.asset_url)
+            .with_retry(self.retry.clone())
+            .with_client(self.client.clone())
+            .fetch_asset(path.clone())
+            .await?;
 
         Ok(path)
     }
@@ -114,7 +175,11 @@ impl LibraryInstaller {
         let archive = self.destination.join("ffmpeg-release.zip");
 
         let release = fetcher.fetch_binary().await?;
-        release.download(archive.clone()).await?;
+        Fetcher::new(&release.asset_url)
+            .with_retry(self.retry.clone())
+            .with_client(self.client.clone())
+            .fetch_asset(archive.clone())
+            .await?;
         let path = fetcher.extract_binary(archive).await?;
 
         if let Some(name) = custom_name {
@@ -1,13 +1,14 @@
 //! Fetch the latest release of 'yt-dlp' from a GitHub repository.
 
 use crate::error::{Error, Result};
+use crate::fetcher::client::default_client;
 use crate::fetcher::deps::{Asset, Release, WantedRelease};
 use crate::fetcher::Fetcher;
 use crate::utils::platform::Architecture;
 use crate::utils::platform::Platform;
 use derive_more::Display;
 
-const BASE_ASSET_NAME: &'static str = "yt-dlp";
+const BASE_ASSET_NAME: &str = "yt-dlp";
 
 /// The GitHub fetcher is responsible for fetching the latest release of 'yt-dlp' from a GitHub repository.
 /// It can also select the correct asset for the current platform and architecture.
@@ -33,6 +34,8 @@ pub struct GitHubFetcher {
     owner: String,
     /// The name of the GitHub repository.
     repo: String,
+    /// The HTTP client to query the GitHub API with.
+    client: reqwest::Client,
 }
 
 impl GitHubFetcher {
@@ -46,9 +49,20 @@ impl GitHubFetcher {
         Self {
             owner: owner.as_ref().to_string(),
             repo: repo.as_ref().to_string(),
+            client: default_client(),
         }
     }
 
+    /// Sets the HTTP client to query the GitHub API with.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The HTTP client to use.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
     /// Fetch the latest release of the GitHub repository, and select the correct asset for the current platform and architecture.
     ///
     /// # Arguments
@@ -115,7 +129,7 @@ impl GitHubFetcher {
             self.owner, self.repo
         );
 
-        let fetcher = Fetcher::new(&url);
+        let fetcher = Fetcher::new(&url).with_client(self.client.clone());
         let response = fetcher.fetch_json(auth_token).await?;
 
         let release: Release = serde_json::from_value(response)?;
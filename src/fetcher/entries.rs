@@ -0,0 +1,177 @@
+//! Tools for streaming the entries of playlists, channels and searches, as 'yt-dlp' fetches them.
+//!
+//! 'yt-dlp' prints a JSON document per entry, on its own line, which is parsed as soon as it arrives.
+//! The entries that could not be fetched are reported in the stream, without ending it.
+
+use crate::error::{Error, Result};
+use crate::executor::{Executor, ProcessEvent};
+use crate::model::playlist::PlaylistEntry;
+use crate::model::Video;
+use crate::{utils, Youtube};
+use futures_util::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// The timeout of the streamed fetching, long enough to resolve thousands of entries.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// The exit code of 'yt-dlp' when an option stopped it early, e.g. '--break-match-filters' or '--max-downloads'.
+const STOPPED_EXIT_CODE: i32 = 101;
+
+impl Youtube {
+    /// Fetch the videos of the given URL, a playlist, a channel tab or a search, and streams them as they are resolved.
+    /// The videos which could not be fetched, e.g. private or deleted ones, are reported as errors in the stream, which goes on.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL to fetch the videos of.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if 'yt-dlp' could not be executed.
+    /// The stream will yield an error for each entry which could not be fetched or parsed,
+    /// and if 'yt-dlp' failed, timed out, or was cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use futures_util::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/playlist?list=PLlaN88a7y2_plecYoJxvRFTLHVbIVAOoc");
+    /// let mut videos = fetcher.stream_video_infos(url).await?;
+    ///
+    /// while let Some(video) = videos.next().await {
+    ///     match video {
+    ///         Ok(video) => println!("{}: {}", video.id, video.title),
+    ///         Err(error) => eprintln!("Skipped an entry: {}", error),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub async fn stream_video_infos(
+        &self,
+        url: String,
+    ) -> Result<impl Stream<Item = Result<Video>> + Send + Unpin + 'static> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Streaming video information for {}", url);
+
+        self.stream_json(url, Vec::new()).await
+    }
+
+    /// Fetch the entries of the given URL flat, a playlist, a channel tab or a search, and streams them as they are listed.
+    /// The entries only hold the basic information of their videos, see [`Youtube::resolve_entry`].
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL to fetch the entries of.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if 'yt-dlp' could not be executed.
+    /// The stream will yield an error for each entry which could not be fetched or parsed,
+    /// and if 'yt-dlp' failed, timed out, or was cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use futures_util::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/@RickAstleyYT/videos");
+    /// let mut entries = fetcher.stream_playlist_entries(url).await?;
+    ///
+    /// while let Some(entry) = entries.next().await {
+    ///     let entry = entry?;
+    ///     println!("{:?}: {:?}", entry.playlist_index, entry.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub async fn stream_playlist_entries(
+        &self,
+        url: String,
+    ) -> Result<impl Stream<Item = Result<PlaylistEntry>> + Send + Unpin + 'static> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Streaming playlist entries for {}", url);
+
+        self.stream_json(url, vec![String::from("--flat-playlist")])
+            .await
+    }
+
+    /// Runs 'yt-dlp' with a JSON document printed per entry, and streams the parsed entries.
+    /// The errors printed by 'yt-dlp' are yielded in between, and the exit code is only reported if no error was,
+    /// and if 'yt-dlp' was not stopped early by an option.
+    pub(crate) async fn stream_json<T>(
+        &self,
+        url: String,
+        mut args: Vec<String>,
+    ) -> Result<impl Stream<Item = Result<T>> + Send + Unpin + 'static>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let mut final_args = self.args.clone();
+        final_args.append(&mut self.network_args());
+        final_args.append(&mut utils::to_owned(vec![
+            "--no-progress",
+            "--dump-json",
+            "--ignore-errors",
+            "--lazy-playlist",
+        ]));
+        final_args.append(&mut args);
+        final_args.push(url);
+
+        let executor = Executor::new(self.libraries.youtube.clone(), STREAM_TIMEOUT, final_args)
+            .with_env(self.network_env()?);
+
+        let events = executor
+            .execute_stream_with_cancellation(&self.cancellation)
+            .await?;
+
+        let entries = events
+            .scan(false, |reported, event| {
+                let entry = match event {
+                    Ok(ProcessEvent::Stdout(line)) if line.trim().is_empty() => None,
+                    Ok(ProcessEvent::Stdout(line)) => {
+                        Some(serde_json::from_str(&line).map_err(Error::Serde))
+                    }
+                    Ok(ProcessEvent::Stderr(line)) => line.strip_prefix("ERROR: ").map(|message| {
+                        *reported = true;
+                        Err(Error::Command(message.to_string()))
+                    }),
+                    Ok(ProcessEvent::Exit(0 | STOPPED_EXIT_CODE)) => None,
+                    Ok(ProcessEvent::Exit(code)) => (!*reported)
+                        .then(|| Err(Error::Command(format!("Process failed with code {}", code)))),
+                    Err(error) => Some(Err(error)),
+                };
+
+                futures_util::future::ready(Some(entry))
+            })
+            .filter_map(futures_util::future::ready);
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,1479 @@
+//! Native downloader of DASH streams, described by MPD manifests.
+//!
+//! A manifest is made of periods, each one holding adaptation sets (e.g. the video, or the audio in a language),
+//! which hold the representations of the stream at different qualities.
+//! The segments of a representation are listed explicitly, or generated from a template,
+//! and are downloaded concurrently and concatenated in order after the initialization segment.
+
+use crate::error::{Error, Result};
+use crate::fetcher::manifest::{manifest_error, parse_base, resolve, ByteRange};
+use crate::fetcher::progress::ProgressTracker;
+use crate::fetcher::Fetcher;
+use crate::model::format::{
+    CodecInfo, Container, DownloadInfo, Extension, FileInfo, Format, HttpHeaders, Protocol,
+    QualityInfo, RatesInfo, StoryboardInfo, VideoResolution,
+};
+use crate::ternary;
+use crate::utils::{date, file_system};
+use futures_util::StreamExt;
+use roxmltree::{Document, Node};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+/// A parsed DASH manifest.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::manifest::dash::Mpd;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let content = String::new();
+/// let url = "https://example.com/manifest.mpd";
+/// let mpd = Mpd::parse(content, url)?;
+///
+/// for format in mpd.formats(url) {
+///     println!("{}", format.format);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mpd {
+    /// Whether the manifest is static (on demand) or dynamic (live).
+    pub presentation_type: PresentationType,
+    /// The total duration of the stream, in seconds.
+    pub duration: Option<f64>,
+    /// The time the first segment of a live stream became available, e.g. '2024-01-01T00:00:00Z'.
+    pub availability_start_time: Option<String>,
+    /// The minimum time between two updates of a live manifest, in seconds.
+    pub minimum_update_period: Option<f64>,
+    /// The duration of the DVR window of a live stream, in seconds.
+    pub time_shift_buffer_depth: Option<f64>,
+    /// The periods of the stream, in order.
+    pub periods: Vec<Period>,
+}
+
+/// Whether the manifest describes an on-demand or a live stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PresentationType {
+    /// An on-demand stream, whose segments are all known.
+    #[default]
+    Static,
+    /// A live stream, whose manifest is updated with new segments.
+    Dynamic,
+}
+
+/// A period of the stream, e.g. a program or an advertisement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Period {
+    /// The identifier of the period.
+    pub id: Option<String>,
+    /// The start time of the period, in seconds.
+    pub start: Option<f64>,
+    /// The duration of the period, in seconds.
+    pub duration: Option<f64>,
+    /// The adaptation sets of the period.
+    pub adaptation_sets: Vec<AdaptationSet>,
+}
+
+/// A set of interchangeable representations, e.g. the video at different qualities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptationSet {
+    /// The identifier of the adaptation set.
+    pub id: Option<String>,
+    /// The type of content, e.g. 'video', 'audio' or 'text'.
+    pub content_type: Option<String>,
+    /// The language of the adaptation set, e.g. 'en'.
+    pub language: Option<String>,
+    /// The representations of the adaptation set.
+    pub representations: Vec<Representation>,
+}
+
+/// A representation of the stream, i.e. an encoding at a given quality.
+/// The attributes missing from the representation are inherited from its adaptation set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Representation {
+    /// The identifier of the representation, e.g. '137'.
+    pub id: String,
+    /// The bitrate of the representation, in bits per second.
+    pub bandwidth: u64,
+    /// The MIME type of the representation, e.g. 'video/mp4'.
+    pub mime_type: Option<String>,
+    /// The codecs of the representation, e.g. 'avc1.640028'.
+    pub codecs: Option<String>,
+    /// The width of the video, in pixels.
+    pub width: Option<u32>,
+    /// The height of the video, in pixels.
+    pub height: Option<u32>,
+    /// The frame rate of the video.
+    pub frame_rate: Option<f64>,
+    /// The audio sample rate, in Hz.
+    pub audio_sampling_rate: Option<u64>,
+    /// The number of audio channels.
+    pub audio_channels: Option<u32>,
+    /// The absolute base URL of the segments.
+    pub base_url: String,
+    /// How the segments of the representation are addressed.
+    pub segments: SegmentInfo,
+}
+
+/// How the segments of a representation are addressed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentInfo {
+    /// The representation is a single resource, at its base URL.
+    Single,
+    /// The segments are listed explicitly.
+    List {
+        /// The initialization segment, if any.
+        init: Option<DashSegment>,
+        /// The media segments, in order.
+        media: Vec<DashSegment>,
+        /// The duration of each segment, in seconds.
+        duration: Option<f64>,
+    },
+    /// The segments are generated from a template.
+    Template(SegmentTemplate),
+}
+
+/// A template generating the URLs of the segments, described by the 'SegmentTemplate' element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SegmentTemplate {
+    /// The template of the URL of the initialization segment, e.g. '$RepresentationID$/init.mp4'.
+    pub initialization: Option<String>,
+    /// The template of the URLs of the media segments, e.g. '$RepresentationID$/$Number%05d$.m4s'.
+    pub media: Option<String>,
+    /// The number of ticks per second of the durations and times.
+    pub timescale: u64,
+    /// The duration of each segment, in ticks, if there is no timeline.
+    pub duration: Option<u64>,
+    /// The number of the first segment.
+    pub start_number: u64,
+    /// The time of the start of the period, in ticks.
+    pub presentation_time_offset: u64,
+    /// The explicit times and durations of the segments.
+    pub timeline: Vec<TimelineEntry>,
+}
+
+/// An entry of a segment timeline, describing one or more consecutive segments of the same duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelineEntry {
+    /// The time of the first segment, in ticks, or none if it follows the previous segment.
+    pub start: Option<u64>,
+    /// The duration of each segment, in ticks.
+    pub duration: u64,
+    /// The number of repetitions after the first segment, -1 to repeat until the next entry or the end of the period.
+    pub repeat: i64,
+}
+
+/// A segment of a representation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashSegment {
+    /// The absolute URL of the segment.
+    pub url: String,
+    /// The range of the resource holding the segment, if not the whole resource.
+    pub byte_range: Option<ByteRange>,
+    /// The number of the segment, for the segments generated from a template.
+    pub number: Option<u64>,
+    /// The time of the segment, in ticks, for the segments generated from a template.
+    pub time: Option<u64>,
+    /// The duration of the segment, in seconds, if known.
+    pub duration: Option<f64>,
+}
+
+impl Mpd {
+    /// Parses a manifest, resolving its relative URLs against the URL it was fetched from.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the manifest.
+    /// * `url` - The URL the manifest was fetched from.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the content is not a valid manifest.
+    pub fn parse(content: impl AsRef<str>, url: impl AsRef<str>) -> Result<Self> {
+        let document = Document::parse(content.as_ref())
+            .map_err(|e| manifest_error(format!("invalid XML: {}", e)))?;
+
+        let root = document.root_element();
+        if root.tag_name().name() != "MPD" {
+            return Err(manifest_error("the root element is not 'MPD'"));
+        }
+
+        let base = base_url(root, url.as_ref())?;
+        let presentation_type = match root.attribute("type") {
+            Some("dynamic") => PresentationType::Dynamic,
+            _ => PresentationType::Static,
+        };
+        let duration = duration_attribute(root, "mediaPresentationDuration")?;
+
+        let period_nodes: Vec<Node> = children(root, "Period").collect();
+        let mut periods = Vec::with_capacity(period_nodes.len());
+        let mut next_start = Some(0.0);
+
+        for (index, node) in period_nodes.iter().enumerate() {
+            let start = duration_attribute(*node, "start")?.or(next_start);
+            let following_start = period_nodes
+                .get(index + 1)
+                .and_then(|next| next.attribute("start"))
+                .and_then(parse_duration);
+
+            let period_duration = duration_attribute(*node, "duration")?
+                .or(following_start
+                    .zip(start)
+                    .map(|(following, start)| following - start))
+                .or(duration.zip(start).map(|(total, start)| total - start));
+
+            periods.push(Period::parse(*node, &base, start, period_duration)?);
+            next_start = start
+                .zip(period_duration)
+                .map(|(start, duration)| start + duration);
+        }
+
+        Ok(Self {
+            presentation_type,
+            duration,
+            availability_start_time: root.attribute("availabilityStartTime").map(String::from),
+            minimum_update_period: duration_attribute(root, "minimumUpdatePeriod")?,
+            time_shift_buffer_depth: duration_attribute(root, "timeShiftBufferDepth")?,
+            periods,
+        })
+    }
+
+    /// Checks if the manifest describes a live stream.
+    pub fn is_live(&self) -> bool {
+        self.presentation_type == PresentationType::Dynamic
+    }
+
+    /// Returns the representations of the stream as formats, one per representation identifier.
+    /// The URL of the formats is the URL of the manifest, and their identifier is the identifier of the representation,
+    /// so they are downloaded with [`Fetcher::fetch_dash`] by [`Youtube::download_format`](crate::Youtube::download_format).
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL the manifest was fetched from.
+    pub fn formats(&self, url: impl AsRef<str>) -> Vec<Format> {
+        let mut seen = HashSet::new();
+        let mut formats = Vec::new();
+
+        for period in &self.periods {
+            for adaptation_set in &period.adaptation_sets {
+                for representation in &adaptation_set.representations {
+                    if seen.insert(representation.id.clone()) {
+                        let duration = period.duration.or(self.duration);
+                        formats.push(representation.to_format(
+                            adaptation_set,
+                            url.as_ref(),
+                            duration,
+                        ));
+                    }
+                }
+            }
+        }
+
+        formats
+    }
+
+    /// Returns the time elapsed since the start of the given period of a live stream, in seconds,
+    /// if the manifest has an availability start time.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of the manifest.
+    pub fn elapsed(&self, period: &Period) -> Option<f64> {
+        let start = self
+            .availability_start_time
+            .as_deref()
+            .and_then(parse_date_time)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_secs_f64();
+
+        Some(now - start - period.start.unwrap_or(0.0))
+    }
+
+    /// Returns the representation with the given identifier in each period, or the one with the highest bitrate.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the manifest has no periods,
+    /// or if a period has no representation to select, since the stream would be missing that period.
+    pub(crate) fn select(&self, id: Option<&str>) -> Result<Vec<(&Period, &Representation)>> {
+        if self.periods.is_empty() {
+            return Err(manifest_error("the manifest has no periods"));
+        }
+
+        self.periods
+            .iter()
+            .enumerate()
+            .map(|(index, period)| {
+                let representations = period
+                    .adaptation_sets
+                    .iter()
+                    .flat_map(|adaptation_set| &adaptation_set.representations);
+
+                let representation = match id {
+                    Some(id) => representations
+                        .filter(|representation| {
+                            representation.id == id
+                                || id.ends_with(&format!("-{}", representation.id))
+                        })
+                        .max_by_key(|representation| representation.id == id),
+                    None => representations.max_by_key(|representation| representation.bandwidth),
+                };
+
+                let period_id = period.id.clone().unwrap_or_else(|| index.to_string());
+                let representation = representation.ok_or_else(|| {
+                    manifest_error(format!(
+                        "the representation {} was not found in the period {}",
+                        id.unwrap_or("with the highest bitrate"),
+                        period_id
+                    ))
+                })?;
+
+                Ok((period, representation))
+            })
+            .collect()
+    }
+}
+
+impl Period {
+    /// Parses a 'Period' element.
+    fn parse(
+        node: Node,
+        parent_base: &str,
+        start: Option<f64>,
+        duration: Option<f64>,
+    ) -> Result<Self> {
+        let base = base_url(node, parent_base)?;
+        let template = child(node, "SegmentTemplate").map(SegmentTemplate::parse_partial);
+
+        let adaptation_sets = children(node, "AdaptationSet")
+            .map(|adaptation_set| AdaptationSet::parse(adaptation_set, &base, template.as_ref()))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            id: node.attribute("id").map(String::from),
+            start,
+            duration,
+            adaptation_sets,
+        })
+    }
+}
+
+impl AdaptationSet {
+    /// Parses an 'AdaptationSet' element, whose representations inherit its attributes.
+    fn parse(
+        node: Node,
+        parent_base: &str,
+        parent_template: Option<&PartialTemplate>,
+    ) -> Result<Self> {
+        let base = base_url(node, parent_base)?;
+        let template = child(node, "SegmentTemplate")
+            .map(SegmentTemplate::parse_partial)
+            .map(|template| template.inherit(parent_template))
+            .or(parent_template.cloned());
+
+        let representations = children(node, "Representation")
+            .map(|representation| {
+                Representation::parse(representation, node, &base, template.as_ref())
+            })
+            .collect::<Result<_>>()?;
+
+        let content_type = node.attribute("contentType").map(String::from).or(node
+            .attribute("mimeType")
+            .and_then(|mime_type| mime_type.split('/').next())
+            .map(String::from));
+
+        Ok(Self {
+            id: node.attribute("id").map(String::from),
+            content_type,
+            language: node.attribute("lang").map(String::from),
+            representations,
+        })
+    }
+}
+
+impl Representation {
+    /// Parses a 'Representation' element, inheriting the missing attributes from its adaptation set.
+    fn parse(
+        node: Node,
+        adaptation_set: Node,
+        parent_base: &str,
+        parent_template: Option<&PartialTemplate>,
+    ) -> Result<Self> {
+        let attribute = |name: &str| node.attribute(name).or(adaptation_set.attribute(name));
+        let number = |name: &str| attribute(name).and_then(|value| value.parse().ok());
+
+        let id = node
+            .attribute("id")
+            .ok_or(manifest_error("a representation has no 'id'"))?;
+        let bandwidth = node
+            .attribute("bandwidth")
+            .and_then(|value| value.parse().ok())
+            .ok_or(manifest_error(format!(
+                "the representation {} has no 'bandwidth'",
+                id
+            )))?;
+
+        let audio_channels = child(node, "AudioChannelConfiguration")
+            .or(child(adaptation_set, "AudioChannelConfiguration"))
+            .and_then(|configuration| configuration.attribute("value"))
+            .and_then(|value| value.parse().ok());
+
+        let base = base_url(node, parent_base)?;
+        let template = child(node, "SegmentTemplate")
+            .map(SegmentTemplate::parse_partial)
+            .map(|template| template.inherit(parent_template))
+            .or(parent_template.cloned());
+
+        let segment_list = child(node, "SegmentList").or(child(adaptation_set, "SegmentList"));
+        let segments = match (template, segment_list) {
+            (Some(template), _) => SegmentInfo::Template(template.complete()),
+            (None, Some(list)) => SegmentInfo::parse_list(list, &base)?,
+            (None, None) => SegmentInfo::Single,
+        };
+
+        Ok(Self {
+            id: id.to_string(),
+            bandwidth,
+            mime_type: attribute("mimeType").map(String::from),
+            codecs: attribute("codecs").map(String::from),
+            width: number("width"),
+            height: number("height"),
+            frame_rate: attribute("frameRate").and_then(parse_frame_rate),
+            audio_sampling_rate: attribute("audioSamplingRate")
+                .and_then(|value| value.parse().ok()),
+            audio_channels,
+            base_url: base,
+            segments,
+        })
+    }
+
+    /// Returns the initialization segment and the media segments of the representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `period_duration` - The duration of the period, in seconds, needed for templates without timeline.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the number of segments can't be determined.
+    pub fn segments(
+        &self,
+        period_duration: Option<f64>,
+    ) -> Result<(Option<DashSegment>, Vec<DashSegment>)> {
+        match &self.segments {
+            SegmentInfo::Single => Ok((
+                None,
+                vec![DashSegment {
+                    url: self.base_url.clone(),
+                    byte_range: None,
+                    number: None,
+                    time: None,
+                    duration: None,
+                }],
+            )),
+            SegmentInfo::List { init, media, .. } => Ok((init.clone(), media.clone())),
+            SegmentInfo::Template(template) => template.segments(self, period_duration),
+        }
+    }
+
+    /// Returns the initialization segment and the media segments of the representation of a live stream,
+    /// which are complete and still in the DVR window.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - The time elapsed since the start of the period, in seconds.
+    /// * `window` - The duration of the DVR window, in seconds, if limited.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the segments can't be determined.
+    pub fn live_segments(
+        &self,
+        elapsed: f64,
+        window: Option<f64>,
+    ) -> Result<(Option<DashSegment>, Vec<DashSegment>)> {
+        match &self.segments {
+            SegmentInfo::Template(template) => template.live_segments(self, elapsed, window),
+            _ => self.segments(None),
+        }
+    }
+
+    /// Converts the representation to a format, the way 'yt-dlp' would.
+    fn to_format(
+        &self,
+        adaptation_set: &AdaptationSet,
+        url: &str,
+        duration: Option<f64>,
+    ) -> Format {
+        let content_type = adaptation_set.content_type.as_deref().or(self
+            .mime_type
+            .as_deref()
+            .and_then(|mime_type| mime_type.split('/').next()));
+
+        let codecs: Vec<&str> = self
+            .codecs
+            .as_deref()
+            .map(|codecs| codecs.split(',').map(str::trim).collect())
+            .unwrap_or_default();
+        let (video_codec, audio_codec) = match (content_type, codecs.as_slice()) {
+            (Some("audio"), [audio, ..]) => (None, Some(audio.to_string())),
+            (_, [video, audio, ..]) => (Some(video.to_string()), Some(audio.to_string())),
+            (Some("video"), [video]) => (Some(video.to_string()), None),
+            _ => (None, None),
+        };
+
+        let (extension, container) = match self.mime_type.as_deref() {
+            Some("video/mp4") => (Extension::Mp4, Some(Container::Mp4)),
+            Some("audio/mp4") => (Extension::M4A, Some(Container::M4A)),
+            Some("video/webm") | Some("audio/webm") => (Extension::Webm, Some(Container::Webm)),
+            _ => (Extension::Unknown, None),
+        };
+
+        let resolution = match (self.width, self.height) {
+            (Some(width), Some(height)) => format!("{}x{}", width, height),
+            _ if video_codec.is_none() => String::from("audio only"),
+            _ => String::from("unknown"),
+        };
+        let aspect_ratio = self
+            .width
+            .zip(self.height)
+            .filter(|(_, height)| *height > 0)
+            .map(|(width, height)| (width as f64 / height as f64 * 100.0).round() / 100.0);
+
+        let total_rate = self.bandwidth as f64 / 1000.0;
+        let filesize_approx =
+            duration.map(|duration| (self.bandwidth as f64 * duration / 8.0) as i64);
+
+        Format {
+            format: format!("{} - {}", self.id, resolution),
+            format_id: self.id.clone(),
+            format_note: adaptation_set.language.clone(),
+            protocol: Protocol::HttpDashSegments,
+            language: adaptation_set.language.clone(),
+            has_drm: None,
+            container,
+            codec_info: CodecInfo {
+                audio_ext: match audio_codec {
+                    Some(_) => extension.clone(),
+                    None => Extension::None,
+                },
+                video_ext: match video_codec {
+                    Some(_) => extension.clone(),
+                    None => Extension::None,
+                },
+                audio_codec,
+                video_codec,
+                audio_channels: self.audio_channels.map(i64::from),
+                asr: self
+                    .audio_sampling_rate
+                    .and_then(|rate| i64::try_from(rate).ok()),
+            },
+            video_resolution: VideoResolution {
+                width: self.width.map(i64::from),
+                height: self.height.map(i64::from),
+                fps: self.frame_rate,
+                resolution,
+                aspect_ratio,
+            },
+            download_info: DownloadInfo {
+                url: url.to_string(),
+                ext: extension,
+                http_headers: HttpHeaders {
+                    user_agent: String::new(),
+                    accept: String::new(),
+                    accept_language: String::new(),
+                    sec_fetch_mode: String::new(),
+                },
+                manifest_url: None,
+                downloader_options: None,
+                cookies: None,
+            },
+            quality_info: QualityInfo {
+                quality: None,
+                dynamic_range: None,
+            },
+            file_info: FileInfo {
+                filesize_approx,
+                filesize: None,
+            },
+            storyboard_info: StoryboardInfo {
+                rows: None,
+                columns: None,
+                fragments: None,
+            },
+            rates_info: RatesInfo {
+                video_rate: ternary!(content_type == Some("video"), Some(total_rate), None),
+                audio_rate: ternary!(content_type == Some("audio"), Some(total_rate), None),
+                total_rate: Some(total_rate),
+            },
+        }
+    }
+}
+
+impl SegmentInfo {
+    /// Parses a 'SegmentList' element.
+    fn parse_list(node: Node, base: &str) -> Result<Self> {
+        let base = parse_base(base)?;
+        let timescale = node
+            .attribute("timescale")
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(1);
+        let duration = node
+            .attribute("duration")
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(|duration| duration as f64 / timescale.max(1) as f64);
+
+        let segment = |url: Option<&str>, range: Option<&str>| -> Result<DashSegment> {
+            Ok(DashSegment {
+                url: match url {
+                    Some(url) => resolve(&base, url)?,
+                    None => base.to_string(),
+                },
+                byte_range: range.map(parse_range).transpose()?,
+                number: None,
+                time: None,
+                duration,
+            })
+        };
+
+        let init = child(node, "Initialization")
+            .map(|init| segment(init.attribute("sourceURL"), init.attribute("range")))
+            .transpose()?
+            .map(|init| DashSegment {
+                duration: None,
+                ..init
+            });
+        let media = children(node, "SegmentURL")
+            .map(|media| segment(media.attribute("media"), media.attribute("mediaRange")))
+            .collect::<Result<_>>()?;
+
+        Ok(SegmentInfo::List {
+            init,
+            media,
+            duration,
+        })
+    }
+}
+
+/// A segment template whose attributes may be inherited from its parents.
+#[derive(Debug, Clone, Default)]
+struct PartialTemplate {
+    initialization: Option<String>,
+    media: Option<String>,
+    timescale: Option<u64>,
+    duration: Option<u64>,
+    start_number: Option<u64>,
+    presentation_time_offset: Option<u64>,
+    timeline: Option<Vec<TimelineEntry>>,
+}
+
+impl PartialTemplate {
+    /// Fills the missing attributes from the template of the parent element.
+    fn inherit(self, parent: Option<&PartialTemplate>) -> Self {
+        let Some(parent) = parent else {
+            return self;
+        };
+
+        Self {
+            initialization: self.initialization.or(parent.initialization.clone()),
+            media: self.media.or(parent.media.clone()),
+            timescale: self.timescale.or(parent.timescale),
+            duration: self.duration.or(parent.duration),
+            start_number: self.start_number.or(parent.start_number),
+            presentation_time_offset: self
+                .presentation_time_offset
+                .or(parent.presentation_time_offset),
+            timeline: self.timeline.or(parent.timeline.clone()),
+        }
+    }
+
+    /// Completes the template with the default values of the missing attributes.
+    fn complete(self) -> SegmentTemplate {
+        SegmentTemplate {
+            initialization: self.initialization,
+            media: self.media,
+            timescale: self.timescale.unwrap_or(1).max(1),
+            duration: self.duration,
+            start_number: self.start_number.unwrap_or(1),
+            presentation_time_offset: self.presentation_time_offset.unwrap_or(0),
+            timeline: self.timeline.unwrap_or_default(),
+        }
+    }
+}
+
+impl SegmentTemplate {
+    /// Parses a 'SegmentTemplate' element, whose missing attributes may be inherited.
+    fn parse_partial(node: Node) -> PartialTemplate {
+        let number = |name: &str| node.attribute(name).and_then(|value| value.parse().ok());
+
+        let timeline = child(node, "SegmentTimeline").map(|timeline| {
+            children(timeline, "S")
+                .map(|entry| TimelineEntry {
+                    start: entry.attribute("t").and_then(|value| value.parse().ok()),
+                    duration: entry
+                        .attribute("d")
+                        .and_then(|value| value.parse().ok())
+                        .unwrap_or(0),
+                    repeat: entry
+                        .attribute("r")
+                        .and_then(|value| value.parse().ok())
+                        .unwrap_or(0),
+                })
+                .collect()
+        });
+
+        PartialTemplate {
+            initialization: node.attribute("initialization").map(String::from),
+            media: node.attribute("media").map(String::from),
+            timescale: number("timescale"),
+            duration: number("duration"),
+            start_number: number("startNumber"),
+            presentation_time_offset: number("presentationTimeOffset"),
+            timeline,
+        }
+    }
+
+    /// Generates the initialization segment and the media segments of the given representation.
+    fn segments(
+        &self,
+        representation: &Representation,
+        period_duration: Option<f64>,
+    ) -> Result<(Option<DashSegment>, Vec<DashSegment>)> {
+        self.generate(representation, period_duration, false)
+    }
+
+    /// Generates the initialization segment and the media segments of the given representation of a live stream,
+    /// which are complete and still in the DVR window.
+    fn live_segments(
+        &self,
+        representation: &Representation,
+        elapsed: f64,
+        window: Option<f64>,
+    ) -> Result<(Option<DashSegment>, Vec<DashSegment>)> {
+        let (init, mut segments) = self.generate(representation, Some(elapsed), true)?;
+
+        if let Some(window) = window {
+            let window_start = self.presentation_time_offset as f64 / self.timescale as f64
+                + (elapsed - window).max(0.0);
+
+            segments.retain(|segment| {
+                let start = segment.time.unwrap_or(0) as f64 / self.timescale as f64;
+                start + segment.duration.unwrap_or(0.0) >= window_start
+            });
+        }
+
+        Ok((init, segments))
+    }
+
+    /// Generates the segments until the end of the period, or only the complete ones for a live stream.
+    fn generate(
+        &self,
+        representation: &Representation,
+        period_duration: Option<f64>,
+        live: bool,
+    ) -> Result<(Option<DashSegment>, Vec<DashSegment>)> {
+        let base = parse_base(&representation.base_url)?;
+        let segment = |template: &str,
+                       number: Option<u64>,
+                       time: Option<u64>,
+                       duration: Option<u64>|
+         -> Result<DashSegment> {
+            let url = fill_template(
+                template,
+                &representation.id,
+                representation.bandwidth,
+                number.unwrap_or(0),
+                time.unwrap_or(0),
+            );
+
+            Ok(DashSegment {
+                url: resolve(&base, &url)?,
+                byte_range: None,
+                number,
+                time,
+                duration: duration.map(|duration| duration as f64 / self.timescale as f64),
+            })
+        };
+
+        let init = self
+            .initialization
+            .as_deref()
+            .map(|template| segment(template, None, None, None))
+            .transpose()?;
+
+        let Some(media) = self.media.as_deref() else {
+            return Ok((init, Vec::new()));
+        };
+
+        let period_end = period_duration.map(|duration| {
+            self.presentation_time_offset + (duration * self.timescale as f64) as u64
+        });
+
+        let mut segments = Vec::new();
+        let mut number = self.start_number;
+
+        if !self.timeline.is_empty() {
+            let mut time = self.presentation_time_offset;
+
+            for (index, entry) in self.timeline.iter().enumerate() {
+                time = entry.start.unwrap_or(time);
+
+                let repeat = match entry.repeat {
+                    repeat if repeat >= 0 => repeat as u64,
+                    _ => {
+                        let end = self
+                            .timeline
+                            .get(index + 1)
+                            .and_then(|next| next.start)
+                            .or(period_end)
+                            .unwrap_or(time);
+
+                        let remaining = end.saturating_sub(time);
+                        let count = match live {
+                            true => remaining / entry.duration.max(1),
+                            false => remaining.div_ceil(entry.duration.max(1)),
+                        };
+
+                        count.saturating_sub(1)
+                    }
+                };
+
+                for _ in 0..=repeat {
+                    segments.push(segment(
+                        media,
+                        Some(number),
+                        Some(time),
+                        Some(entry.duration),
+                    )?);
+                    time += entry.duration;
+                    number += 1;
+                }
+            }
+        } else {
+            let duration = self
+                .duration
+                .filter(|duration| *duration > 0)
+                .ok_or(manifest_error(
+                    "the segment template has neither a duration nor a timeline",
+                ))?;
+            let period_duration =
+                period_duration.ok_or(manifest_error("the duration of the period is unknown"))?;
+
+            let count = period_duration * self.timescale as f64 / duration as f64;
+            let count = ternary!(live, count.floor(), count.ceil()) as u64;
+
+            for index in 0..count {
+                let time = self.presentation_time_offset + index * duration;
+                segments.push(segment(
+                    media,
+                    Some(number + index),
+                    Some(time),
+                    Some(duration),
+                )?);
+            }
+        }
+
+        Ok((init, segments))
+    }
+}
+
+impl Fetcher {
+    /// Downloads a representation of the DASH stream of the manifest at the URL, and writes it to the given destination.
+    ///
+    /// The representation with the given identifier is downloaded in each period, or the one with the highest bitrate.
+    /// Up to `parallelism` segments are fetched concurrently, each one being retried on transient failures,
+    /// and written in order after the initialization segment to a '.part' file, which is renamed once the download is complete.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The path to write the stream to.
+    /// * `representation` - The identifier of the representation to download, or none for the highest bitrate.
+    /// * `parallelism` - The maximum number of segments fetched at the same time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the manifest is invalid, if the representation is not found in every period,
+    /// if a segment could not be fetched, or if the stream could not be written to the destination,
+    /// in which case the partial file is removed.
+    /// It will return [`Error::Cancelled`] if the download was cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::fetcher::Fetcher;
+    /// # use std::path::PathBuf;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let url = "https://example.com/manifest.mpd";
+    /// let destination = PathBuf::from("video.mp4");
+    ///
+    /// let fetcher = Fetcher::new(url);
+    /// fetcher.fetch_dash(destination, Some("137"), 4).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, destination))
+    )]
+    pub async fn fetch_dash(
+        &self,
+        destination: impl AsRef<Path>,
+        representation: Option<&str>,
+        parallelism: usize,
+    ) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Fetching DASH stream from {} to {:?}",
+            self.url,
+            destination.as_ref()
+        );
+
+        let destination = destination.as_ref();
+        let part = file_system::try_part(destination)?;
+
+        let result = tokio::select! {
+            result = self.fetch_dash_uncancelled(destination, &part, representation, parallelism.max(1)) => result,
+            _ = self.cancellation.cancelled() => Err(Error::Cancelled),
+        };
+
+        if result.is_err() {
+            file_system::remove_file(&part).await?;
+        }
+
+        result
+    }
+
+    /// Fetches the DASH manifest at the given URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the manifest.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the manifest could not be fetched or parsed.
+    pub async fn fetch_dash_manifest(&self, url: impl AsRef<str>) -> Result<Mpd> {
+        let url = url.as_ref();
+        let content = self.fetch_text(url).await?;
+
+        Mpd::parse(content, url)
+    }
+
+    /// Downloads the DASH stream to the partial file, ignoring the cancellation token.
+    async fn fetch_dash_uncancelled(
+        &self,
+        destination: &Path,
+        part: &Path,
+        representation: Option<&str>,
+        parallelism: usize,
+    ) -> Result<()> {
+        file_system::create_parent_dir(destination)?;
+
+        let mpd = self.fetch_dash_manifest(&self.url).await?;
+        let selected = mpd.select(representation)?;
+
+        let mut segments = Vec::new();
+        let mut current_init = None;
+
+        for (period, representation) in selected {
+            let (init, media) = representation.segments(period.duration.or(mpd.duration))?;
+
+            if init.is_some() && init != current_init {
+                segments.extend(init.clone());
+                current_init = init;
+            }
+            segments.extend(media);
+        }
+
+        let mut dest = file_system::create_file(part).await?;
+        let mut tracker = ProgressTracker::new(self.progress.clone(), 0, self.size_hint);
+
+        let count = segments.len();
+        let fetched = futures_util::stream::iter(segments)
+            .map(
+                |segment| async move { self.fetch_segment(&segment.url, segment.byte_range).await },
+            )
+            .buffered(parallelism);
+
+        self.write_segments(fetched, Some(count), &mut dest, &mut tracker)
+            .await?;
+
+        dest.flush().await?;
+        drop(dest);
+
+        tokio::fs::rename(part, destination).await?;
+        tracker.finish();
+
+        Ok(())
+    }
+}
+
+/// Returns the first child element with the given name.
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &'a str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+/// Returns the child elements with the given name, ignoring their namespace.
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Resolves the 'BaseURL' of an element, if any, against the base URL of its parent.
+fn base_url(node: Node, parent_base: &str) -> Result<String> {
+    match child(node, "BaseURL").and_then(|base| base.text()) {
+        Some(url) => resolve(&parse_base(parent_base)?, url.trim()),
+        None => Ok(parent_base.to_string()),
+    }
+}
+
+/// Parses an attribute holding an ISO 8601 duration.
+fn duration_attribute(node: Node, name: &str) -> Result<Option<f64>> {
+    node.attribute(name)
+        .map(|value| {
+            parse_duration(value).ok_or(manifest_error(format!("invalid {} '{}'", name, value)))
+        })
+        .transpose()
+}
+
+/// Parses an ISO 8601 duration, e.g. 'PT1H2M3.5S', in seconds.
+/// The years and months are approximated to 365 and 30 days.
+fn parse_duration(value: &str) -> Option<f64> {
+    const DATE_UNITS: [(char, f64); 4] = [
+        ('Y', 365.0 * 86400.0),
+        ('M', 30.0 * 86400.0),
+        ('W', 7.0 * 86400.0),
+        ('D', 86400.0),
+    ];
+    const TIME_UNITS: [(char, f64); 3] = [('H', 3600.0), ('M', 60.0), ('S', 1.0)];
+
+    let value = value.trim().strip_prefix('P')?;
+    let (date, time) = value.split_once('T').unwrap_or((value, ""));
+
+    let mut seconds = 0.0;
+    for (part, units) in [(date, &DATE_UNITS[..]), (time, &TIME_UNITS[..])] {
+        let mut number = String::new();
+
+        for character in part.chars() {
+            if character.is_ascii_digit() || character == '.' {
+                number.push(character);
+                continue;
+            }
+
+            let (_, unit) = units.iter().find(|(unit, _)| *unit == character)?;
+            seconds += number.parse::<f64>().ok()? * unit;
+            number.clear();
+        }
+
+        if !number.is_empty() {
+            return None;
+        }
+    }
+
+    Some(seconds)
+}
+
+/// Parses an ISO 8601 date and time, e.g. '2024-01-01T00:00:00Z', as a UNIX timestamp in seconds.
+fn parse_date_time(value: &str) -> Option<f64> {
+    let (date, time) = value.trim().split_once('T')?;
+
+    let mut date = date.splitn(3, '-');
+    let year: i64 = date.next()?.parse().ok()?;
+    let month: i64 = date.next()?.parse().ok()?;
+    let day: i64 = date.next()?.parse().ok()?;
+
+    let (time, offset) = match time.strip_suffix('Z') {
+        Some(time) => (time, 0.0),
+        None => match time.rfind(['+', '-']) {
+            Some(index) => {
+                let (time, offset) = time.split_at(index);
+                let sign = ternary!(offset.starts_with('-'), -1.0, 1.0);
+                let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+
+                let offset =
+                    hours.parse::<f64>().ok()? * 3600.0 + minutes.parse::<f64>().ok()? * 60.0;
+                (time, sign * offset)
+            }
+            None => (time, 0.0),
+        },
+    };
+
+    let mut time = time.splitn(3, ':');
+    let hours: f64 = time.next()?.parse().ok()?;
+    let minutes: f64 = time.next()?.parse().ok()?;
+    let seconds: f64 = time.next().unwrap_or("0").parse().ok()?;
+
+    let days = date::days_from_civil(year, month, day);
+
+    Some(days as f64 * 86400.0 + hours * 3600.0 + minutes * 60.0 + seconds - offset)
+}
+
+/// Parses a frame rate, e.g. '30' or '30000/1001'.
+fn parse_frame_rate(value: &str) -> Option<f64> {
+    match value.split_once('/') {
+        Some((numerator, denominator)) => {
+            let denominator: f64 = denominator.parse().ok()?;
+            Some(numerator.parse::<f64>().ok()? / denominator)
+        }
+        None => value.parse().ok(),
+    }
+}
+
+/// Parses a byte range attribute, e.g. '0-999'.
+fn parse_range(value: &str) -> Result<ByteRange> {
+    let invalid = || manifest_error(format!("invalid range '{}'", value));
+
+    let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+    let start: u64 = start.trim().parse().map_err(|_| invalid())?;
+    let end: u64 = end.trim().parse().map_err(|_| invalid())?;
+
+    if end < start {
+        return Err(invalid());
+    }
+
+    Ok(ByteRange {
+        offset: start,
+        length: end - start + 1,
+    })
+}
+
+/// Fills a segment template, replacing the '$RepresentationID$', '$Bandwidth$', '$Number$' and '$Time$' identifiers,
+/// optionally formatted with a width, e.g. '$Number%05d$', and '$$' with '$'.
+fn fill_template(template: &str, id: &str, bandwidth: u64, number: u64, time: u64) -> String {
+    let mut filled = String::with_capacity(template.len());
+
+    for (index, part) in template.split('$').enumerate() {
+        if index % 2 == 0 {
+            filled.push_str(part);
+            continue;
+        }
+
+        if part.is_empty() {
+            filled.push('$');
+            continue;
+        }
+
+        let (name, width) = match part.split_once('%') {
+            Some((name, format)) => (
+                name,
+                format
+                    .trim_end_matches('d')
+                    .trim_start_matches('0')
+                    .parse()
+                    .unwrap_or(0),
+            ),
+            None => (part, 0),
+        };
+
+        let value = match name {
+            "RepresentationID" => id.to_string(),
+            "Bandwidth" => bandwidth.to_string(),
+            "Number" => number.to_string(),
+            "Time" => time.to_string(),
+            _ => {
+                filled.push('$');
+                filled.push_str(part);
+                filled.push('$');
+                continue;
+            }
+        };
+
+        let _ = write!(filled, "{:0>width$}", value, width = width);
+    }
+
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATIC_URL: &str = "https://example.com/dash/manifest.mpd";
+    const LIVE_URL: &str = "https://example.com/live/manifest.mpd";
+
+    /// Returns the URLs of the segments.
+    fn urls(segments: &[DashSegment]) -> Vec<&str> {
+        segments
+            .iter()
+            .map(|segment| segment.url.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parse_static_manifest() {
+        let content = include_str!("../../../tests/fixtures/manifests/static.mpd");
+        let mpd = Mpd::parse(content, STATIC_URL).unwrap();
+
+        assert_eq!(mpd.presentation_type, PresentationType::Static);
+        assert!(!mpd.is_live());
+        assert_eq!(mpd.duration, Some(60.5));
+        assert_eq!(mpd.minimum_update_period, None);
+
+        // The first period has an explicit duration, the last one ends with the presentation
+        let periods: Vec<_> = mpd
+            .periods
+            .iter()
+            .map(|period| (period.id.as_deref(), period.start, period.duration))
+            .collect();
+        assert_eq!(
+            periods,
+            vec![
+                (Some("intro"), Some(0.0), Some(10.0)),
+                (Some("main"), Some(10.0), Some(50.5)),
+            ]
+        );
+
+        let video = &mpd.periods[0].adaptation_sets[0];
+        assert_eq!(video.content_type.as_deref(), Some("video"));
+
+        // The attributes missing from the representations are inherited from their adaptation set
+        let low = &video.representations[0];
+        assert_eq!(low.id, "134");
+        assert_eq!(low.bandwidth, 600000);
+        assert_eq!(low.codecs.as_deref(), Some("avc1.4d401f"));
+        assert_eq!((low.width, low.height), (Some(640), Some(360)));
+        assert_eq!(low.frame_rate, Some(30000.0 / 1001.0));
+        assert_eq!(low.base_url, "https://example.com/dash/media/");
+        assert_eq!(
+            video.representations[1].codecs.as_deref(),
+            Some("avc1.4d401f.hi")
+        );
+
+        let audio = &mpd.periods[0].adaptation_sets[1];
+        assert_eq!(audio.content_type.as_deref(), Some("audio"));
+        assert_eq!(audio.language.as_deref(), Some("en"));
+        assert_eq!(audio.representations[0].audio_sampling_rate, Some(44100));
+        assert_eq!(audio.representations[0].audio_channels, Some(2));
+        assert_eq!(
+            audio.representations[0].base_url,
+            "https://cdn.example.com/audio.m4a"
+        );
+
+        // The representations are listed once, even if they are in several periods
+        let formats: Vec<_> = mpd
+            .formats(STATIC_URL)
+            .into_iter()
+            .map(|format| format.format_id)
+            .collect();
+        assert_eq!(formats, vec!["134", "136", "140"]);
+    }
+
+    #[test]
+    fn template_segments() {
+        let content = include_str!("../../../tests/fixtures/manifests/static.mpd");
+        let mpd = Mpd::parse(content, STATIC_URL).unwrap();
+
+        // 10 seconds of 4 seconds segments, the last one being partial
+        let (init, segments) = mpd.periods[0].adaptation_sets[0].representations[0]
+            .segments(Some(10.0))
+            .unwrap();
+        assert_eq!(
+            init.map(|init| init.url).as_deref(),
+            Some("https://example.com/dash/media/134/init.mp4")
+        );
+        assert_eq!(
+            urls(&segments),
+            vec![
+                "https://example.com/dash/media/134/00001.m4s",
+                "https://example.com/dash/media/134/00002.m4s",
+                "https://example.com/dash/media/134/00003.m4s",
+            ]
+        );
+        assert_eq!(segments[0].duration, Some(4.0));
+
+        // The last entry of the timeline is repeated until the end of the period
+        let period = &mpd.periods[1];
+        let (_, segments) = period.adaptation_sets[0].representations[0]
+            .segments(period.duration)
+            .unwrap();
+        let times: Vec<_> = segments.iter().filter_map(|segment| segment.time).collect();
+        assert_eq!(segments.len(), 48);
+        assert_eq!(times[..4], [0, 180000, 360000, 540000]);
+        assert_eq!(times.last(), Some(&4500000));
+        assert_eq!(
+            segments[3].url,
+            "https://example.com/dash/media/136/540000.m4s"
+        );
+        assert_eq!(segments[3].duration, Some(1.0));
+    }
+
+    #[test]
+    fn list_segments() {
+        let content = include_str!("../../../tests/fixtures/manifests/static.mpd");
+        let mpd = Mpd::parse(content, STATIC_URL).unwrap();
+
+        let (init, segments) = mpd.periods[0].adaptation_sets[1].representations[0]
+            .segments(None)
+            .unwrap();
+        let init = init.unwrap();
+        assert_eq!(init.url, "https://cdn.example.com/audio.m4a");
+        assert_eq!(
+            init.byte_range,
+            Some(ByteRange {
+                offset: 0,
+                length: 1000
+            })
+        );
+        assert_eq!(init.duration, None);
+
+        let ranges: Vec<_> = segments
+            .iter()
+            .map(|segment| (segment.byte_range, segment.duration))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (
+                    Some(ByteRange {
+                        offset: 1000,
+                        length: 4000
+                    }),
+                    Some(5.0)
+                ),
+                (
+                    Some(ByteRange {
+                        offset: 5000,
+                        length: 4000
+                    }),
+                    Some(5.0)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn select_representations() {
+        let content = include_str!("../../../tests/fixtures/manifests/static.mpd");
+        let mpd = Mpd::parse(content, STATIC_URL).unwrap();
+
+        let selected = |id| -> Result<Vec<(Option<&str>, &str)>> {
+            Ok(mpd
+                .select(id)?
+                .into_iter()
+                .map(|(period, representation)| (period.id.as_deref(), representation.id.as_str()))
+                .collect())
+        };
+
+        assert_eq!(
+            selected(None).unwrap(),
+            vec![(Some("intro"), "136"), (Some("main"), "136")]
+        );
+        assert_eq!(
+            selected(Some("136")).unwrap(),
+            vec![(Some("intro"), "136"), (Some("main"), "136")]
+        );
+        // The identifiers given by 'yt-dlp' are prefixed with the identifier of the manifest
+        assert_eq!(
+            selected(Some("dash-136")).unwrap(),
+            vec![(Some("intro"), "136"), (Some("main"), "136")]
+        );
+
+        // A representation missing from a period is an error, rather than a stream without that period
+        assert!(selected(Some("134")).is_err());
+        assert!(selected(Some("137")).is_err());
+
+        let empty = Mpd::parse("<MPD/>", STATIC_URL).unwrap();
+        assert!(empty.select(None).is_err());
+    }
+
+    #[test]
+    fn parse_live_manifest() {
+        let content = include_str!("../../../tests/fixtures/manifests/live.mpd");
+        let mpd = Mpd::parse(content, LIVE_URL).unwrap();
+
+        assert!(mpd.is_live());
+        assert_eq!(
+            mpd.availability_start_time.as_deref(),
+            Some("2024-01-01T00:00:00Z")
+        );
+        assert_eq!(mpd.minimum_update_period, Some(2.0));
+        assert_eq!(mpd.time_shift_buffer_depth, Some(60.0));
+
+        // Only the complete segments still in the window of the last 4 seconds are listed
+        let (init, segments) = mpd.periods[0].adaptation_sets[0].representations[0]
+            .live_segments(10.0, Some(4.0))
+            .unwrap();
+        assert_eq!(
+            init.map(|init| init.url).as_deref(),
+            Some("https://example.com/live/init-64000.mp4")
+        );
+        assert_eq!(
+            urls(&segments),
+            vec![
+                "https://example.com/live/chunk-2.m4s",
+                "https://example.com/live/chunk-3.m4s",
+                "https://example.com/live/chunk-4.m4s",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let invalid = [
+            "<MPD",
+            "<Manifest/>",
+            "<MPD><Period><AdaptationSet><Representation bandwidth='1'/></AdaptationSet></Period></MPD>",
+            "<MPD><Period><AdaptationSet><Representation id='1'/></AdaptationSet></Period></MPD>",
+            "<MPD mediaPresentationDuration='1 minute'/>",
+        ];
+
+        for content in invalid {
+            assert!(
+                matches!(Mpd::parse(content, STATIC_URL), Err(Error::Manifest(_))),
+                "{} should not be parsed",
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("PT1H2M3.5S"), Some(3723.5));
+        assert_eq!(parse_duration("PT0S"), Some(0.0));
+        assert_eq!(parse_duration("P1DT12H"), Some(129600.0));
+        assert_eq!(parse_duration("P1W"), Some(604800.0));
+        assert_eq!(parse_duration("P1Y2M"), Some(425.0 * 86400.0));
+        assert_eq!(parse_duration(" PT30S "), Some(30.0));
+
+        assert_eq!(parse_duration("T30S"), None);
+        assert_eq!(parse_duration("PT30"), None);
+        assert_eq!(parse_duration("P1H"), None);
+        assert_eq!(parse_duration("PT1X"), None);
+        assert_eq!(parse_duration("PT1.2.3S"), None);
+    }
+
+    #[test]
+    fn date_times() {
+        assert_eq!(parse_date_time("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(parse_date_time("2024-01-01T00:00:00Z"), Some(1704067200.0));
+        assert_eq!(
+            parse_date_time("2024-02-29T12:30:15.5Z"),
+            Some(1709209815.5)
+        );
+        assert_eq!(
+            parse_date_time("2024-03-01T01:00:00+01:00"),
+            Some(1709251200.0)
+        );
+        assert_eq!(
+            parse_date_time("2024-02-29T19:00:00-05:00"),
+            Some(1709251200.0)
+        );
+        assert_eq!(parse_date_time("2024-01-01T00:00"), Some(1704067200.0));
+        assert_eq!(parse_date_time("1969-12-31T23:59:59Z"), Some(-1.0));
+
+        assert_eq!(parse_date_time("2024-01-01"), None);
+        assert_eq!(parse_date_time("2024-01T00:00:00Z"), None);
+        assert_eq!(parse_date_time("yesterday"), None);
+    }
+}
@@ -0,0 +1,1108 @@
+//! Native downloader of HLS streams, described by 'm3u8' playlists.
+//!
+//! A master playlist lists the variants of a stream, each one described by a media playlist listing its segments.
+//! The segments are downloaded concurrently, decrypted if needed, and concatenated in order into a single file.
+
+use crate::error::{Error, Result};
+use crate::fetcher::manifest::{manifest_error, parse_base, resolve, ByteRange};
+use crate::fetcher::progress::ProgressTracker;
+use crate::fetcher::Fetcher;
+use crate::utils::file_system;
+use aes::Aes128;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+/// The tag starting every playlist.
+const HEADER: &str = "#EXTM3U";
+
+/// The size of the AES-128 keys and initialization vectors, in bytes.
+const AES_BLOCK_SIZE: usize = 16;
+
+/// A parsed HLS playlist.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::manifest::hls::Playlist;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let content = "#EXTM3U\n#EXT-X-TARGETDURATION:10\n#EXTINF:10,\nsegment0.ts\n#EXT-X-ENDLIST\n";
+///
+/// match Playlist::parse(content, "https://example.com/video.m3u8")? {
+///     Playlist::Master(master) => println!("{} variants", master.variants.len()),
+///     Playlist::Media(media) => println!("{} segments", media.segments.len()),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Playlist {
+    /// A master playlist, listing the variants of a stream.
+    Master(MasterPlaylist),
+    /// A media playlist, listing the segments of a variant.
+    Media(MediaPlaylist),
+}
+
+/// A master playlist, listing the variants of a stream and their alternative renditions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MasterPlaylist {
+    /// The variants of the stream, each one with its own media playlist.
+    pub variants: Vec<Variant>,
+    /// The alternative renditions, e.g. the audio tracks in other languages.
+    pub renditions: Vec<Rendition>,
+}
+
+/// A variant of a stream, described by the '#EXT-X-STREAM-INF' tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    /// The absolute URL of the media playlist of the variant.
+    pub url: String,
+    /// The peak bitrate of the variant, in bits per second.
+    pub bandwidth: u64,
+    /// The average bitrate of the variant, in bits per second.
+    pub average_bandwidth: Option<u64>,
+    /// The width of the video, in pixels.
+    pub width: Option<u32>,
+    /// The height of the video, in pixels.
+    pub height: Option<u32>,
+    /// The maximum frame rate of the video.
+    pub frame_rate: Option<f64>,
+    /// The codecs of the variant, e.g. 'avc1.4d401f,mp4a.40.2'.
+    pub codecs: Option<String>,
+    /// The group of the audio renditions played with the variant.
+    pub audio: Option<String>,
+}
+
+/// An alternative rendition, described by the '#EXT-X-MEDIA' tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendition {
+    /// The type of the rendition, e.g. 'AUDIO', 'VIDEO' or 'SUBTITLES'.
+    pub media_type: String,
+    /// The group the rendition belongs to.
+    pub group_id: String,
+    /// The human-readable name of the rendition.
+    pub name: Option<String>,
+    /// The language of the rendition, e.g. 'en'.
+    pub language: Option<String>,
+    /// The absolute URL of the media playlist of the rendition, if not included in the variant.
+    pub url: Option<String>,
+    /// Whether the rendition is played by default.
+    pub default: bool,
+}
+
+/// A media playlist, listing the segments of a variant.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaPlaylist {
+    /// The maximum duration of a segment, in seconds.
+    pub target_duration: f64,
+    /// The sequence number of the first segment.
+    pub media_sequence: u64,
+    /// The type of the playlist, 'VOD' or 'EVENT', if specified.
+    pub playlist_type: Option<String>,
+    /// Whether the playlist is complete, i.e. no segment will be added ('#EXT-X-ENDLIST').
+    pub ended: bool,
+    /// The segments of the playlist, in order.
+    pub segments: Vec<Segment>,
+}
+
+/// A segment of a media playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// The absolute URL of the segment.
+    pub url: String,
+    /// The duration of the segment, in seconds.
+    pub duration: f64,
+    /// The sequence number of the segment.
+    pub sequence: u64,
+    /// The range of the resource holding the segment, if not the whole resource.
+    pub byte_range: Option<ByteRange>,
+    /// The key the segment is encrypted with, if any.
+    pub key: Option<Key>,
+    /// The initialization section to prepend to the segment, e.g. for fragmented MP4 streams.
+    pub init: Option<InitSection>,
+}
+
+/// An initialization section, described by the '#EXT-X-MAP' tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitSection {
+    /// The absolute URL of the section.
+    pub url: String,
+    /// The range of the resource holding the section, if not the whole resource.
+    pub byte_range: Option<ByteRange>,
+}
+
+/// An encryption key, described by the '#EXT-X-KEY' tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    /// The encryption method.
+    pub method: KeyMethod,
+    /// The absolute URL of the key.
+    pub url: Option<String>,
+    /// The initialization vector. If none, the sequence number of the segment is used.
+    pub iv: Option<[u8; AES_BLOCK_SIZE]>,
+}
+
+/// The encryption methods of the segments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyMethod {
+    /// The whole segments are encrypted with AES-128 in CBC mode, with PKCS7 padding.
+    Aes128,
+    /// The media samples are encrypted individually, which is not supported.
+    SampleAes,
+    /// Another method, which is not supported.
+    Other(String),
+}
+
+/// How the variant of a master playlist is chosen, instead of the one with the highest bitrate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariantChoice {
+    /// The variant with the lowest bitrate.
+    Worst,
+    /// The variant with the given peak bitrate ('BANDWIDTH'), in bits per second.
+    Bandwidth(u64),
+    /// The variant with the highest bitrate whose height is at most the given one, in pixels.
+    /// The variants without resolution are ignored.
+    MaxHeight(u32),
+}
+
+/// The state of an HLS download, kept between the batches of segments of a live stream.
+#[derive(Debug, Default)]
+pub(crate) struct HlsState {
+    /// The keys already fetched, by URL.
+    keys: HashMap<String, Vec<u8>>,
+    /// The last initialization section written.
+    init: Option<InitSection>,
+}
+
+impl Playlist {
+    /// Parses a playlist, resolving its relative URLs against the URL it was fetched from.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the playlist.
+    /// * `url` - The URL the playlist was fetched from.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the content is not a valid playlist.
+    pub fn parse(content: impl AsRef<str>, url: impl AsRef<str>) -> Result<Self> {
+        let content = content.as_ref().trim_start_matches('\u{feff}');
+        let base = parse_base(url.as_ref())?;
+
+        if !content.trim_start().starts_with(HEADER) {
+            return Err(manifest_error(format!(
+                "the playlist does not start with {}",
+                HEADER
+            )));
+        }
+
+        let lines = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+
+        match content.contains("#EXT-X-STREAM-INF") {
+            true => MasterPlaylist::parse(lines, &base).map(Playlist::Master),
+            false => MediaPlaylist::parse(lines, &base).map(Playlist::Media),
+        }
+    }
+}
+
+impl MasterPlaylist {
+    /// Returns the variant with the highest bitrate.
+    /// Its audio may be in a separate rendition, see [`MasterPlaylist::audio_rendition`].
+    pub fn best_variant(&self) -> Option<&Variant> {
+        self.variants
+            .iter()
+            .max_by_key(|variant| (variant.bandwidth, variant.height))
+    }
+
+    /// Returns the variant with the lowest bitrate.
+    pub fn worst_variant(&self) -> Option<&Variant> {
+        self.variants
+            .iter()
+            .min_by_key(|variant| (variant.bandwidth, variant.height))
+    }
+
+    /// Returns the audio rendition played with the given variant, if it is not included in the variant itself.
+    /// The rendition played by default is preferred, and its playlist must be downloaded along with the variant,
+    /// which is otherwise silent.
+    ///
+    /// # Arguments
+    ///
+    /// * `variant` - The variant to find the audio of.
+    pub fn audio_rendition(&self, variant: &Variant) -> Option<&Rendition> {
+        let group = variant.audio.as_ref()?;
+        let mut renditions = self
+            .renditions
+            .iter()
+            .filter(|rendition| rendition.media_type == "AUDIO" && rendition.group_id == *group);
+
+        // A rendition without URI is included in the variant, as the other renditions of its group are alternatives
+        let rendition = renditions
+            .clone()
+            .find(|rendition| rendition.default)
+            .or(renditions.next())?;
+
+        rendition.url.as_ref().map(|_| rendition)
+    }
+
+    /// Returns the variant matching the given choice, or the one with the highest bitrate if none is given.
+    ///
+    /// # Arguments
+    ///
+    /// * `choice` - How the variant is chosen, or none for the highest bitrate.
+    pub fn select(&self, choice: Option<VariantChoice>) -> Option<&Variant> {
+        match choice {
+            None => self.best_variant(),
+            Some(VariantChoice::Worst) => self.worst_variant(),
+            Some(VariantChoice::Bandwidth(bandwidth)) => self
+                .variants
+                .iter()
+                .find(|variant| variant.bandwidth == bandwidth),
+            Some(VariantChoice::MaxHeight(height)) => self
+                .variants
+                .iter()
+                .filter(|variant| variant.height.is_some_and(|value| value <= height))
+                .max_by_key(|variant| (variant.bandwidth, variant.height)),
+        }
+    }
+
+    /// Returns the variant to download, matching the given choice, unless its audio is in a separate rendition.
+    /// The variant alone would be silent, so the playlists of both must be downloaded and merged instead.
+    pub(crate) fn downloadable_variant(&self, choice: Option<VariantChoice>) -> Result<&Variant> {
+        let variant = match (self.select(choice), choice) {
+            (Some(variant), _) => variant,
+            (None, None) => return Err(manifest_error("the master playlist has no variants")),
+            (None, Some(choice)) => {
+                return Err(manifest_error(format!("no variant matches {:?}", choice)));
+            }
+        };
+
+        match self.audio_rendition(variant) {
+            Some(Rendition { url: Some(url), .. }) => Err(manifest_error(format!(
+                "the audio of the variant {} is in the separate rendition {}, both must be downloaded and merged",
+                variant.url, url
+            ))),
+            _ => Ok(variant),
+        }
+    }
+
+    /// Parses the lines of a master playlist.
+    fn parse<'a>(lines: impl Iterator<Item = &'a str>, base: &reqwest::Url) -> Result<Self> {
+        let mut playlist = Self::default();
+        let mut pending: Option<HashMap<String, String>> = None;
+
+        for line in lines {
+            if let Some(list) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+                pending = Some(attributes(list));
+            } else if let Some(list) = line.strip_prefix("#EXT-X-MEDIA:") {
+                let attributes = attributes(list);
+
+                playlist.renditions.push(Rendition {
+                    media_type: required(&attributes, "TYPE")?.to_string(),
+                    group_id: required(&attributes, "GROUP-ID")?.to_string(),
+                    name: attributes.get("NAME").cloned(),
+                    language: attributes.get("LANGUAGE").cloned(),
+                    url: attributes
+                        .get("URI")
+                        .map(|url| resolve(base, url))
+                        .transpose()?,
+                    default: attributes
+                        .get("DEFAULT")
+                        .is_some_and(|value| value == "YES"),
+                });
+            } else if !line.starts_with('#') {
+                let Some(attributes) = pending.take() else {
+                    continue;
+                };
+
+                let resolution = attributes.get("RESOLUTION").and_then(|resolution| {
+                    let (width, height) = resolution.split_once('x')?;
+                    Some((width.parse().ok()?, height.parse().ok()?))
+                });
+
+                playlist.variants.push(Variant {
+                    url: resolve(base, line)?,
+                    bandwidth: parse_number(required(&attributes, "BANDWIDTH")?, "BANDWIDTH")?,
+                    average_bandwidth: attributes
+                        .get("AVERAGE-BANDWIDTH")
+                        .and_then(|value| value.parse().ok()),
+                    width: resolution.map(|(width, _)| width),
+                    height: resolution.map(|(_, height)| height),
+                    frame_rate: attributes
+                        .get("FRAME-RATE")
+                        .and_then(|value| value.parse().ok()),
+                    codecs: attributes.get("CODECS").cloned(),
+                    audio: attributes.get("AUDIO").cloned(),
+                });
+            }
+        }
+
+        Ok(playlist)
+    }
+}
+
+impl MediaPlaylist {
+    /// Returns the total duration of the segments, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.segments.iter().map(|segment| segment.duration).sum()
+    }
+
+    /// Parses the lines of a media playlist.
+    fn parse<'a>(lines: impl Iterator<Item = &'a str>, base: &reqwest::Url) -> Result<Self> {
+        let mut playlist = Self::default();
+
+        let mut duration = None;
+        let mut byte_range: Option<(u64, Option<u64>)> = None;
+        let mut key: Option<Key> = None;
+        let mut init: Option<InitSection> = None;
+        // The end of the previous sub-range, where a sub-range without offset starts.
+        let mut previous_end: Option<(String, u64)> = None;
+
+        for line in lines {
+            if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+                playlist.target_duration = parse_number(value, "EXT-X-TARGETDURATION")?;
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                playlist.media_sequence = parse_number(value, "EXT-X-MEDIA-SEQUENCE")?;
+            } else if let Some(value) = line.strip_prefix("#EXT-X-PLAYLIST-TYPE:") {
+                playlist.playlist_type = Some(value.to_string());
+            } else if line == "#EXT-X-ENDLIST" {
+                playlist.ended = true;
+            } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+                let value = value
+                    .split_once(',')
+                    .map_or(value, |(duration, _)| duration);
+                duration = Some(parse_number(value, "EXTINF")?);
+            } else if let Some(value) = line.strip_prefix("#EXT-X-BYTERANGE:") {
+                byte_range = Some(parse_byte_range(value)?);
+            } else if let Some(list) = line.strip_prefix("#EXT-X-KEY:") {
+                key = Key::parse(&attributes(list), base)?;
+            } else if let Some(list) = line.strip_prefix("#EXT-X-MAP:") {
+                let attributes = attributes(list);
+                let byte_range = attributes
+                    .get("BYTERANGE")
+                    .map(|value| parse_byte_range(value))
+                    .transpose()?
+                    .map(|(length, offset)| ByteRange {
+                        offset: offset.unwrap_or(0),
+                        length,
+                    });
+
+                init = Some(InitSection {
+                    url: resolve(base, required(&attributes, "URI")?)?,
+                    byte_range,
+                });
+            } else if !line.starts_with('#') {
+                let url = resolve(base, line)?;
+
+                let byte_range = byte_range.take().map(|(length, offset)| {
+                    let offset = offset.unwrap_or(match &previous_end {
+                        Some((previous, end)) if *previous == url => *end,
+                        _ => 0,
+                    });
+
+                    ByteRange { offset, length }
+                });
+                previous_end = byte_range.map(|range| (url.clone(), range.offset + range.length));
+
+                let sequence = playlist.media_sequence + playlist.segments.len() as u64;
+                playlist.segments.push(Segment {
+                    url,
+                    duration: duration.take().unwrap_or(playlist.target_duration),
+                    sequence,
+                    byte_range,
+                    key: key.clone(),
+                    init: init.clone(),
+                });
+            }
+        }
+
+        Ok(playlist)
+    }
+}
+
+impl Key {
+    /// Parses the attributes of a '#EXT-X-KEY' tag, returning none if the segments are not encrypted.
+    fn parse(attributes: &HashMap<String, String>, base: &reqwest::Url) -> Result<Option<Self>> {
+        let method = match required(attributes, "METHOD")? {
+            "NONE" => return Ok(None),
+            "AES-128" => KeyMethod::Aes128,
+            "SAMPLE-AES" => KeyMethod::SampleAes,
+            method => KeyMethod::Other(method.to_string()),
+        };
+
+        let iv = attributes
+            .get("IV")
+            .map(|iv| {
+                let hex = iv.trim_start_matches("0x").trim_start_matches("0X");
+                u128::from_str_radix(hex, 16)
+                    .map(u128::to_be_bytes)
+                    .map_err(|_| manifest_error(format!("invalid IV '{}'", iv)))
+            })
+            .transpose()?;
+
+        Ok(Some(Self {
+            method,
+            url: attributes
+                .get("URI")
+                .map(|url| resolve(base, url))
+                .transpose()?,
+            iv,
+        }))
+    }
+}
+
+impl Fetcher {
+    /// Downloads the HLS stream of the playlist at the URL, and writes it to the given destination.
+    ///
+    /// If the playlist is a master playlist, the variant matching the given choice is downloaded,
+    /// or the one with the highest bitrate.
+    /// When its audio is in a separate rendition (see [`MasterPlaylist::audio_rendition`]), the media playlists
+    /// of the variant and of the rendition must be downloaded with their own fetchers, and merged,
+    /// e.g. with [`Youtube::combine_audio_and_video`](crate::Youtube::combine_audio_and_video).
+    /// Up to `parallelism` segments are fetched concurrently, each one being retried on transient failures,
+    /// and written in order to a '.part' file, which is renamed once the download is complete.
+    /// The segments encrypted with AES-128 are decrypted.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The path to write the stream to.
+    /// * `variant` - How the variant of a master playlist is chosen, or none for the highest bitrate.
+    /// * `parallelism` - The maximum number of segments fetched at the same time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the playlist is invalid, if no variant matches the choice or its audio is separate,
+    /// if a segment could not be fetched or decrypted, or if the stream could not be written to the destination,
+    /// in which case the partial file is removed.
+    /// It will return [`Error::Cancelled`] if the download was cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::fetcher::Fetcher;
+    /// # use yt_dlp::fetcher::manifest::hls::VariantChoice;
+    /// # use std::path::PathBuf;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let url = "https://example.com/master.m3u8";
+    /// let destination = PathBuf::from("video.ts");
+    ///
+    /// let fetcher = Fetcher::new(url);
+    /// fetcher.fetch_hls(destination, Some(VariantChoice::MaxHeight(720)), 4).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, destination))
+    )]
+    pub async fn fetch_hls(
+        &self,
+        destination: impl AsRef<Path>,
+        variant: Option<VariantChoice>,
+        parallelism: usize,
+    ) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Fetching HLS stream from {} to {:?}",
+            self.url,
+            destination.as_ref()
+        );
+
+        let destination = destination.as_ref();
+        let part = file_system::try_part(destination)?;
+
+        let result = tokio::select! {
+            result = self.fetch_hls_uncancelled(destination, &part, variant, parallelism.max(1)) => result,
+            _ = self.cancellation.cancelled() => Err(Error::Cancelled),
+        };
+
+        if result.is_err() {
+            file_system::remove_file(&part).await?;
+        }
+
+        result
+    }
+
+    /// Fetches the playlist at the given URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the playlist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the playlist could not be fetched or parsed.
+    pub async fn fetch_hls_playlist(&self, url: impl AsRef<str>) -> Result<Playlist> {
+        let url = url.as_ref();
+        let content = self.fetch_text(url).await?;
+
+        Playlist::parse(content, url)
+    }
+
+    /// Downloads the HLS stream to the partial file, ignoring the cancellation token.
+    async fn fetch_hls_uncancelled(
+        &self,
+        destination: &Path,
+        part: &Path,
+        variant: Option<VariantChoice>,
+        parallelism: usize,
+    ) -> Result<()> {
+        file_system::create_parent_dir(destination)?;
+
+        let playlist = match self.fetch_hls_playlist(&self.url).await? {
+            Playlist::Media(playlist) => playlist,
+            Playlist::Master(master) => {
+                let variant = master.downloadable_variant(variant)?;
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Selected the HLS variant {:?}", variant);
+
+                match self.fetch_hls_playlist(&variant.url).await? {
+                    Playlist::Media(playlist) => playlist,
+                    Playlist::Master(_) => {
+                        return Err(manifest_error("the variant is a master playlist"));
+                    }
+                }
+            }
+        };
+
+        let mut dest = file_system::create_file(part).await?;
+        let mut tracker = ProgressTracker::new(self.progress.clone(), 0, self.size_hint);
+
+        self.write_hls_segments(
+            &playlist.segments,
+            &mut HlsState::default(),
+            parallelism,
+            &mut dest,
+            &mut tracker,
+            Some(playlist.segments.len()),
+        )
+        .await?;
+
+        dest.flush().await?;
+        drop(dest);
+
+        tokio::fs::rename(part, destination).await?;
+        tracker.finish();
+
+        Ok(())
+    }
+
+    /// Fetches the keys of the given segments which are not fetched yet.
+    async fn fetch_hls_keys(
+        &self,
+        segments: &[Segment],
+        keys: &mut HashMap<String, Vec<u8>>,
+    ) -> Result<()> {
+        for key in segments.iter().filter_map(|segment| segment.key.as_ref()) {
+            let url = match (&key.method, &key.url) {
+                (KeyMethod::Aes128, Some(url)) => url,
+                (KeyMethod::Aes128, None) => return Err(manifest_error("the key has no URI")),
+                (method, _) => {
+                    return Err(manifest_error(format!(
+                        "unsupported encryption method {:?}",
+                        method
+                    )));
+                }
+            };
+
+            if keys.contains_key(url) {
+                continue;
+            }
+
+            let value = self.fetch_segment(url, None).await?;
+            if value.len() != AES_BLOCK_SIZE {
+                return Err(manifest_error(format!(
+                    "the key is {} bytes long instead of {}",
+                    value.len(),
+                    AES_BLOCK_SIZE
+                )));
+            }
+
+            keys.insert(url.clone(), value);
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the given segments concurrently, and writes them in order to the destination.
+    /// The initialization section is written whenever it differs from the current one.
+    /// If the total number of segments is given, it is used to estimate the size of the stream.
+    pub(crate) async fn write_hls_segments(
+        &self,
+        segments: &[Segment],
+        state: &mut HlsState,
+        parallelism: usize,
+        dest: &mut File,
+        tracker: &mut ProgressTracker,
+        total: Option<usize>,
+    ) -> Result<()> {
+        self.fetch_hls_keys(segments, &mut state.keys).await?;
+
+        let mut inits = Vec::with_capacity(segments.len());
+        for segment in segments {
+            let changed = segment.init != state.init;
+            if changed {
+                state.init.clone_from(&segment.init);
+            }
+
+            inits.push(segment.init.clone().filter(|_| changed));
+        }
+
+        let fetched = futures_util::stream::iter(segments.iter().cloned().zip(inits))
+            .map(|(segment, init)| self.fetch_hls_segment(segment, init, &state.keys))
+            .buffered(parallelism);
+
+        self.write_segments(fetched, total, dest, tracker).await
+    }
+
+    /// Fetches a segment, decrypts it, and prepends the given initialization section.
+    async fn fetch_hls_segment(
+        &self,
+        segment: Segment,
+        init: Option<InitSection>,
+        keys: &HashMap<String, Vec<u8>>,
+    ) -> Result<Vec<u8>> {
+        let mut data = match init {
+            Some(init) => self.fetch_segment(&init.url, init.byte_range).await?,
+            None => Vec::new(),
+        };
+
+        let media = self.fetch_segment(&segment.url, segment.byte_range).await?;
+        let media = match &segment.key {
+            Some(key) => {
+                let value = key
+                    .url
+                    .as_ref()
+                    .and_then(|url| keys.get(url))
+                    .ok_or(manifest_error("the key of the segment was not fetched"))?;
+                let iv = key.iv.unwrap_or((segment.sequence as u128).to_be_bytes());
+
+                decrypt(media, value, &iv)?
+            }
+            None => media,
+        };
+
+        data.extend(media);
+        Ok(data)
+    }
+}
+
+/// Decrypts a segment encrypted with AES-128 in CBC mode, with PKCS7 padding.
+fn decrypt(mut data: Vec<u8>, key: &[u8], iv: &[u8; AES_BLOCK_SIZE]) -> Result<Vec<u8>> {
+    let decryptor = cbc::Decryptor::<Aes128>::new_from_slices(key, iv)
+        .map_err(|e| manifest_error(format!("invalid key: {}", e)))?;
+
+    let length = decryptor
+        .decrypt_padded_mut::<Pkcs7>(&mut data)
+        .map_err(|_| manifest_error("the segment could not be decrypted"))?
+        .len();
+
+    data.truncate(length);
+    Ok(data)
+}
+
+/// Parses an attribute list, e.g. 'BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2"'.
+/// The quotes of the quoted values are removed.
+fn attributes(list: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = list.trim();
+
+    while let Some((name, value)) = rest.split_once('=') {
+        let (value, remaining) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+
+        attributes.insert(name.trim().to_string(), value.to_string());
+        rest = remaining.trim_start_matches(',').trim_start();
+    }
+
+    attributes
+}
+
+/// Returns the value of a required attribute.
+fn required<'a>(attributes: &'a HashMap<String, String>, name: &str) -> Result<&'a str> {
+    attributes
+        .get(name)
+        .map(String::as_str)
+        .ok_or(manifest_error(format!("missing attribute {}", name)))
+}
+
+/// Parses a number of a tag or an attribute.
+fn parse_number<T: std::str::FromStr>(value: &str, name: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| manifest_error(format!("invalid {} '{}'", name, value)))
+}
+
+/// Parses a byte range, e.g. '1000@200', into its length and its optional offset.
+fn parse_byte_range(value: &str) -> Result<(u64, Option<u64>)> {
+    match value.split_once('@') {
+        Some((length, offset)) => Ok((
+            parse_number(length, "BYTERANGE")?,
+            Some(parse_number(offset, "BYTERANGE")?),
+        )),
+        None => Ok((parse_number(value, "BYTERANGE")?, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbc::cipher::BlockEncryptMut;
+
+    const MASTER_URL: &str = "https://example.com/hls/master.m3u8";
+    const MEDIA_URL: &str = "https://example.com/hls/720p/index.m3u8";
+
+    fn master() -> MasterPlaylist {
+        let content = include_str!("../../../tests/fixtures/manifests/master.m3u8");
+        match Playlist::parse(content, MASTER_URL).unwrap() {
+            Playlist::Master(master) => master,
+            Playlist::Media(_) => panic!("expected a master playlist"),
+        }
+    }
+
+    fn media() -> MediaPlaylist {
+        let content = include_str!("../../../tests/fixtures/manifests/media.m3u8");
+        match Playlist::parse(content, MEDIA_URL).unwrap() {
+            Playlist::Media(media) => media,
+            Playlist::Master(_) => panic!("expected a media playlist"),
+        }
+    }
+
+    /// Parses the attributes of a '#EXT-X-KEY' tag.
+    fn key(list: &str) -> Result<Option<Key>> {
+        Key::parse(&attributes(list), &parse_base(MEDIA_URL).unwrap())
+    }
+
+    #[test]
+    fn parse_master_playlist() {
+        let master = master();
+
+        let variants: Vec<_> = master
+            .variants
+            .iter()
+            .map(|variant| (variant.url.as_str(), variant.bandwidth, variant.height))
+            .collect();
+        assert_eq!(
+            variants,
+            vec![
+                ("https://example.com/hls/240p/index.m3u8", 300000, Some(240)),
+                (
+                    "https://cdn.example.com/480p/index.m3u8",
+                    1200000,
+                    Some(480)
+                ),
+                (
+                    "https://example.com/hls/720p/index.m3u8",
+                    2500000,
+                    Some(720)
+                ),
+                (
+                    "https://example.com/hls/1080p/index.m3u8",
+                    5000000,
+                    Some(1080)
+                ),
+            ]
+        );
+
+        assert_eq!(
+            master.variants[0],
+            Variant {
+                url: "https://example.com/hls/240p/index.m3u8".to_string(),
+                bandwidth: 300000,
+                average_bandwidth: Some(250000),
+                width: Some(426),
+                height: Some(240),
+                frame_rate: Some(25.0),
+                codecs: Some("avc1.4d400d,mp4a.40.2".to_string()),
+                audio: Some("muxed".to_string()),
+            }
+        );
+
+        assert_eq!(master.renditions.len(), 3);
+        assert_eq!(
+            master.renditions[1],
+            Rendition {
+                media_type: "AUDIO".to_string(),
+                group_id: "stereo".to_string(),
+                name: Some("Français".to_string()),
+                language: Some("fr".to_string()),
+                url: Some("https://example.com/hls/audio/fr.m3u8".to_string()),
+                default: false,
+            }
+        );
+    }
+
+    #[test]
+    fn select_variants() {
+        let master = master();
+        let height = |choice| master.select(choice).and_then(|variant| variant.height);
+
+        assert_eq!(height(None), Some(1080));
+        assert_eq!(master.best_variant().unwrap().height, Some(1080));
+        assert_eq!(height(Some(VariantChoice::Worst)), Some(240));
+        assert_eq!(height(Some(VariantChoice::Bandwidth(1200000))), Some(480));
+        assert_eq!(height(Some(VariantChoice::Bandwidth(1200001))), None);
+        assert_eq!(height(Some(VariantChoice::MaxHeight(720))), Some(720));
+        assert_eq!(height(Some(VariantChoice::MaxHeight(719))), Some(480));
+        assert_eq!(height(Some(VariantChoice::MaxHeight(100))), None);
+
+        assert!(MasterPlaylist::default().select(None).is_none());
+    }
+
+    #[test]
+    fn downloadable_variants() {
+        let master = master();
+
+        // The audio of the best variant is in a separate rendition, the default one of its group
+        let best = master.best_variant().unwrap();
+        assert_eq!(
+            master
+                .audio_rendition(best)
+                .and_then(|rendition| rendition.url.as_deref()),
+            Some("https://example.com/hls/audio/en.m3u8")
+        );
+        assert!(master.downloadable_variant(None).is_err());
+
+        // The rendition of the 240p variant has no URI, so its audio is included in the variant
+        let worst = master
+            .downloadable_variant(Some(VariantChoice::Worst))
+            .unwrap();
+        assert_eq!(worst.height, Some(240));
+        assert_eq!(master.audio_rendition(worst), None);
+
+        let variant = master
+            .downloadable_variant(Some(VariantChoice::MaxHeight(720)))
+            .unwrap();
+        assert_eq!(variant.url, MEDIA_URL);
+
+        assert!(master
+            .downloadable_variant(Some(VariantChoice::MaxHeight(100)))
+            .is_err());
+        assert!(MasterPlaylist::default()
+            .downloadable_variant(None)
+            .is_err());
+    }
+
+    #[test]
+    fn parse_media_playlist() {
+        let media = media();
+
+        assert_eq!(media.target_duration, 6.0);
+        assert_eq!(media.media_sequence, 100);
+        assert_eq!(media.playlist_type.as_deref(), Some("VOD"));
+        assert!(media.ended);
+
+        // The last segment has no '#EXTINF' tag, so it lasts the target duration
+        let segments: Vec<_> = media
+            .segments
+            .iter()
+            .map(|segment| (segment.url.as_str(), segment.sequence, segment.duration))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![
+                ("https://example.com/hls/720p/video.mp4", 100, 6.006),
+                ("https://example.com/hls/720p/video.mp4", 101, 6.006),
+                ("https://example.com/hls/720p/segment2.ts", 102, 5.5),
+                (
+                    "https://example.com/hls/720p/segment3.ts?token=abc",
+                    103,
+                    4.0
+                ),
+                ("https://example.com/hls/720p/segment4.ts", 104, 6.0),
+            ]
+        );
+        assert!((media.duration() - 27.512).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parse_byte_ranges() {
+        let media = media();
+
+        // A sub-range without offset starts at the end of the previous sub-range of the same resource
+        let ranges: Vec<_> = media
+            .segments
+            .iter()
+            .map(|segment| segment.byte_range)
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                Some(ByteRange {
+                    offset: 720,
+                    length: 1000
+                }),
+                Some(ByteRange {
+                    offset: 1720,
+                    length: 2000
+                }),
+                None,
+                None,
+                None,
+            ]
+        );
+
+        assert_eq!(parse_byte_range("1000@200").unwrap(), (1000, Some(200)));
+        assert_eq!(parse_byte_range("1000").unwrap(), (1000, None));
+        assert!(parse_byte_range("1000@").is_err());
+        assert!(parse_byte_range("abc").is_err());
+
+        // Without a previous sub-range of the same resource, it starts at the beginning
+        let content = "#EXTM3U\n#EXT-X-BYTERANGE:100@50\na.ts\n#EXT-X-BYTERANGE:200\nb.ts\n";
+        let Playlist::Media(media) = Playlist::parse(content, MEDIA_URL).unwrap() else {
+            panic!("expected a media playlist");
+        };
+        assert_eq!(
+            media.segments[1].byte_range,
+            Some(ByteRange {
+                offset: 0,
+                length: 200
+            })
+        );
+    }
+
+    #[test]
+    fn parse_init_sections() {
+        let media = media();
+
+        let first = InitSection {
+            url: "https://example.com/hls/720p/init.mp4".to_string(),
+            byte_range: Some(ByteRange {
+                offset: 0,
+                length: 720,
+            }),
+        };
+        let second = InitSection {
+            url: "https://example.com/hls/init2.mp4".to_string(),
+            byte_range: None,
+        };
+
+        let inits: Vec<_> = media
+            .segments
+            .iter()
+            .map(|segment| segment.init.clone())
+            .collect();
+        assert_eq!(
+            inits,
+            vec![
+                Some(first.clone()),
+                Some(first.clone()),
+                Some(first),
+                Some(second.clone()),
+                Some(second),
+            ]
+        );
+
+        let content = "#EXTM3U\n#EXT-X-MAP:BYTERANGE=\"100@0\"\n#EXTINF:4,\na.ts\n";
+        assert!(Playlist::parse(content, MEDIA_URL).is_err());
+    }
+
+    #[test]
+    fn parse_keys() {
+        let media = media();
+
+        let keys: Vec<_> = media
+            .segments
+            .iter()
+            .map(|segment| segment.key.clone())
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                None,
+                None,
+                Some(Key {
+                    method: KeyMethod::Aes128,
+                    url: Some("https://keys.example.com/key1".to_string()),
+                    iv: Some([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]),
+                }),
+                Some(Key {
+                    method: KeyMethod::Aes128,
+                    url: Some("https://example.com/hls/720p/key2".to_string()),
+                    iv: None,
+                }),
+                None,
+            ]
+        );
+
+        // The IV is padded on the left, and its prefix is case-insensitive
+        let parsed = key("METHOD=AES-128,URI=\"key\",IV=0XFF").unwrap().unwrap();
+        let mut iv = [0; AES_BLOCK_SIZE];
+        iv[AES_BLOCK_SIZE - 1] = 0xff;
+        assert_eq!(parsed.iv, Some(iv));
+
+        assert_eq!(key("METHOD=NONE").unwrap(), None);
+        assert_eq!(
+            key("METHOD=SAMPLE-AES,URI=\"key\"")
+                .unwrap()
+                .unwrap()
+                .method,
+            KeyMethod::SampleAes
+        );
+        assert_eq!(
+            key("METHOD=SAMPLE-AES-CTR").unwrap().unwrap().method,
+            KeyMethod::Other("SAMPLE-AES-CTR".to_string())
+        );
+
+        assert!(key("URI=\"key\"").is_err());
+        assert!(key("METHOD=AES-128,IV=0xZZ").is_err());
+        assert!(key("METHOD=AES-128,IV=0x1000102030405060708090A0B0C0D0E0F").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Playlist::parse("#EXTINF:4,\na.ts\n", MEDIA_URL).is_err());
+        assert!(Playlist::parse("#EXTM3U\n#EXT-X-TARGETDURATION:six\n", MEDIA_URL).is_err());
+        assert!(Playlist::parse("#EXTM3U\n#EXTINF:four,\na.ts\n", MEDIA_URL).is_err());
+        assert!(Playlist::parse(
+            "#EXTM3U\n#EXT-X-MEDIA:TYPE=AUDIO\n#EXT-X-STREAM-INF:BANDWIDTH=1\na.m3u8\n",
+            MASTER_URL
+        )
+        .is_err());
+        assert!(Playlist::parse(
+            "#EXTM3U\n#EXT-X-STREAM-INF:RESOLUTION=1x1\na.m3u8\n",
+            MASTER_URL
+        )
+        .is_err());
+
+        // The byte order mark is ignored
+        assert!(Playlist::parse("\u{feff}#EXTM3U\n#EXTINF:4,\na.ts\n", MEDIA_URL).is_ok());
+    }
+
+    #[test]
+    fn decrypt_segments() {
+        let key = *b"0123456789abcdef";
+        let iv = (102u128).to_be_bytes();
+        let plain = b"a segment of 29 bytes of data";
+
+        let mut buffer = [0u8; 32];
+        let encrypted = cbc::Encryptor::<Aes128>::new_from_slices(&key, &iv)
+            .unwrap()
+            .encrypt_padded_b2b_mut::<Pkcs7>(plain, &mut buffer)
+            .unwrap()
+            .to_vec();
+        assert_eq!(encrypted.len(), 32);
+
+        assert_eq!(decrypt(encrypted.clone(), &key, &iv).unwrap(), plain);
+
+        // A wrong IV only garbles the first block, while a wrong key breaks the padding
+        let wrong_iv = [0; AES_BLOCK_SIZE];
+        assert_ne!(
+            decrypt(encrypted.clone(), &key, &wrong_iv).ok().as_deref(),
+            Some(&plain[..])
+        );
+        assert!(decrypt(encrypted.clone(), b"too short", &iv).is_err());
+        assert!(decrypt(encrypted[..20].to_vec(), &key, &iv).is_err());
+    }
+}
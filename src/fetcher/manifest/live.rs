@@ -0,0 +1,537 @@
+//! Recording of live streams, following their HLS or DASH manifest as it grows.
+//!
+//! The manifest is fetched periodically, and the new segments are appended to a '.part' file,
+//! which is renamed once the recording stops: when the stream ends, after the requested duration, or on demand.
+
+use crate::error::{Error, Result};
+use crate::fetcher::manifest::dash::DashSegment;
+use crate::fetcher::manifest::hls::{HlsState, Playlist, Segment, VariantChoice};
+use crate::fetcher::manifest::manifest_error;
+use crate::fetcher::progress::ProgressTracker;
+use crate::fetcher::Fetcher;
+use crate::ternary;
+use crate::utils::file_system;
+use futures_util::StreamExt;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+/// The number of segments before the live edge a recording starts from, like most players.
+const LIVE_EDGE_SEGMENTS: usize = 3;
+
+/// The minimum delay between two fetches of the manifest.
+const MIN_REFRESH_DELAY: Duration = Duration::from_secs(1);
+
+/// The maximum delay between two fetches of the manifest.
+const MAX_REFRESH_DELAY: Duration = Duration::from_secs(30);
+
+/// The options of a live stream recording.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::manifest::live::RecordingOptions;
+/// # use std::time::Duration;
+/// let options = RecordingOptions {
+///     duration: Some(Duration::from_secs(3600)),
+///     from_start: true,
+///     ..RecordingOptions::default()
+/// };
+///
+/// // Later, to stop the recording and keep what was recorded
+/// options.stop.cancel();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RecordingOptions {
+    /// The token to stop the recording with.
+    /// Unlike with the cancellation token of the fetcher, the recorded part of the stream is kept.
+    pub stop: CancellationToken,
+    /// The maximum duration to record, or none to record until the stream ends.
+    pub duration: Option<Duration>,
+    /// Whether to start from the beginning of the DVR window, instead of the live edge.
+    pub from_start: bool,
+}
+
+/// Keeps track of the recorded duration, to know when to stop.
+struct Recorder<'a> {
+    /// The options of the recording.
+    options: &'a RecordingOptions,
+    /// The recorded duration, in seconds.
+    recorded: f64,
+}
+
+impl<'a> Recorder<'a> {
+    /// Starts tracking a recording.
+    fn new(options: &'a RecordingOptions) -> Self {
+        Self {
+            options,
+            recorded: 0.0,
+        }
+    }
+
+    /// Checks if the recording must stop, because it was stopped or reached its maximum duration.
+    fn is_done(&self) -> bool {
+        let limit_reached = self
+            .options
+            .duration
+            .is_some_and(|duration| self.recorded >= duration.as_secs_f64());
+
+        limit_reached || self.options.stop.is_cancelled()
+    }
+
+    /// Keeps the segments to record, until the recording must stop.
+    fn take<T>(&mut self, segments: Vec<T>, duration: impl Fn(&T) -> f64) -> Vec<T> {
+        let mut taken = Vec::with_capacity(segments.len());
+
+        for segment in segments {
+            if self.is_done() {
+                break;
+            }
+
+            self.recorded += duration(&segment);
+            taken.push(segment);
+        }
+
+        taken
+    }
+
+    /// Waits before fetching the manifest again.
+    /// Returns false if the recording was stopped in the meantime.
+    async fn wait(&self, delay: Duration) -> bool {
+        tokio::select! {
+            _ = tokio::time::sleep(delay.clamp(MIN_REFRESH_DELAY, MAX_REFRESH_DELAY)) => true,
+            _ = self.options.stop.cancelled() => false,
+        }
+    }
+}
+
+impl Fetcher {
+    /// Records the live HLS stream of the playlist at the URL, and writes it to the given destination.
+    ///
+    /// The playlist is fetched again every target duration, and its new segments are appended to a '.part' file.
+    /// If the playlist is a master playlist, the variant matching the given choice is recorded, or the one with the highest bitrate,
+    /// unless its audio is in a separate rendition (see [`MasterPlaylist::audio_rendition`](crate::fetcher::manifest::hls::MasterPlaylist::audio_rendition)).
+    /// The file is renamed to the destination once the recording stops, i.e. when the stream ends ('#EXT-X-ENDLIST'),
+    /// after the maximum duration, or when the stop token of the options is cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The path to write the recording to.
+    /// * `variant` - How the variant of a master playlist is chosen, or none for the highest bitrate.
+    /// * `parallelism` - The maximum number of segments fetched at the same time.
+    /// * `options` - The options of the recording.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the playlist is invalid, if no variant matches the choice or its audio is separate,
+    /// or if a segment could not be fetched or written,
+    /// in which case the recorded part of the stream is kept in the '.part' file, and recording again to the same destination appends to it.
+    /// It will return [`Error::Cancelled`] if the recording was cancelled, in which case the partial file is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::fetcher::Fetcher;
+    /// # use yt_dlp::fetcher::manifest::live::RecordingOptions;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let url = "https://example.com/live.m3u8";
+    /// let destination = PathBuf::from("live.ts");
+    ///
+    /// let options = RecordingOptions {
+    ///     duration: Some(Duration::from_secs(600)),
+    ///     ..RecordingOptions::default()
+    /// };
+    ///
+    /// let fetcher = Fetcher::new(url);
+    /// fetcher.record_hls(destination, None, 4, &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, destination, options))
+    )]
+    pub async fn record_hls(
+        &self,
+        destination: impl AsRef<Path>,
+        variant: Option<VariantChoice>,
+        parallelism: usize,
+        options: &RecordingOptions,
+    ) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Recording HLS stream from {} to {:?}",
+            self.url,
+            destination.as_ref()
+        );
+
+        let destination = destination.as_ref();
+        let part = file_system::try_part(destination)?;
+
+        let result = tokio::select! {
+            result = self.record_hls_uncancelled(destination, &part, variant, parallelism.max(1), options) => result,
+            _ = self.cancellation.cancelled() => Err(Error::Cancelled),
+        };
+
+        if let Err(Error::Cancelled) = result {
+            file_system::remove_file(&part).await?;
+        }
+
+        result
+    }
+
+    /// Records a representation of the live DASH stream of the manifest at the URL, and writes it to the given destination.
+    ///
+    /// The manifest is fetched again every minimum update period, and its new segments are appended to a '.part' file.
+    /// The representation with the given identifier is recorded, or the one with the highest bitrate.
+    /// The file is renamed to the destination once the recording stops, i.e. when the manifest becomes static,
+    /// after the maximum duration, or when the stop token of the options is cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The path to write the recording to.
+    /// * `representation` - The identifier of the representation to record, or none for the highest bitrate.
+    /// * `parallelism` - The maximum number of segments fetched at the same time.
+    /// * `options` - The options of the recording.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the manifest is invalid, if the representation is not found in every period,
+    /// or if a segment could not be fetched or written, in which case the recorded part of the stream is kept in the '.part' file,
+    /// and recording again to the same destination appends to it.
+    /// It will return [`Error::Cancelled`] if the recording was cancelled, in which case the partial file is removed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, destination, options))
+    )]
+    pub async fn record_dash(
+        &self,
+        destination: impl AsRef<Path>,
+        representation: Option<&str>,
+        parallelism: usize,
+        options: &RecordingOptions,
+    ) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Recording DASH stream from {} to {:?}",
+            self.url,
+            destination.as_ref()
+        );
+
+        let destination = destination.as_ref();
+        let part = file_system::try_part(destination)?;
+
+        let result = tokio::select! {
+            result = self.record_dash_uncancelled(destination, &part, representation, parallelism.max(1), options) => result,
+            _ = self.cancellation.cancelled() => Err(Error::Cancelled),
+        };
+
+        if let Err(Error::Cancelled) = result {
+            file_system::remove_file(&part).await?;
+        }
+
+        result
+    }
+
+    /// Records the live HLS stream to the partial file, ignoring the cancellation token.
+    async fn record_hls_uncancelled(
+        &self,
+        destination: &Path,
+        part: &Path,
+        variant: Option<VariantChoice>,
+        parallelism: usize,
+        options: &RecordingOptions,
+    ) -> Result<()> {
+        file_system::create_parent_dir(destination)?;
+
+        let url = match self.fetch_hls_playlist(&self.url).await? {
+            Playlist::Media(_) => self.url.clone(),
+            Playlist::Master(master) => master.downloadable_variant(variant)?.url.clone(),
+        };
+
+        // A recording which failed is kept, and retrying appends to it instead of truncating it.
+        let recorded = file_system::file_size(part).await?;
+        let mut dest = file_system::append_file(part).await?;
+        let mut tracker = ProgressTracker::new(self.progress.clone(), recorded, None);
+        let mut recorder = Recorder::new(options);
+        let mut state = HlsState::default();
+        let mut next_sequence = None;
+
+        loop {
+            let playlist = match self.fetch_hls_playlist(&url).await? {
+                Playlist::Media(playlist) => playlist,
+                Playlist::Master(_) => {
+                    return Err(manifest_error("the variant is a master playlist"));
+                }
+            };
+
+            let skipped = hls_skipped(&playlist.segments, next_sequence, options.from_start);
+
+            #[cfg(feature = "tracing")]
+            if let (Some(next), Some(first)) = (next_sequence, playlist.segments.first()) {
+                if first.sequence > next {
+                    tracing::warn!(
+                        "Missed {} segments of the live stream",
+                        first.sequence - next
+                    );
+                }
+            }
+
+            let segments = recorder.take(playlist.segments[skipped..].to_vec(), |segment| {
+                segment.duration
+            });
+            if let Some(last) = segments.last() {
+                next_sequence = Some(last.sequence + 1);
+            }
+
+            self.write_hls_segments(
+                &segments,
+                &mut state,
+                parallelism,
+                &mut dest,
+                &mut tracker,
+                None,
+            )
+            .await?;
+
+            if playlist.ended || recorder.is_done() {
+                break;
+            }
+
+            // Without new segments, the playlist is fetched again sooner, as advised by the HLS specification.
+            let target_duration = Duration::from_secs_f64(playlist.target_duration.max(0.0));
+            let delay = match segments.is_empty() {
+                true => target_duration / 2,
+                false => target_duration,
+            };
+
+            if !recorder.wait(delay).await {
+                break;
+            }
+        }
+
+        dest.flush().await?;
+        drop(dest);
+
+        tokio::fs::rename(part, destination).await?;
+        tracker.finish();
+
+        Ok(())
+    }
+
+    /// Records the live DASH stream to the partial file, ignoring the cancellation token.
+    async fn record_dash_uncancelled(
+        &self,
+        destination: &Path,
+        part: &Path,
+        representation: Option<&str>,
+        parallelism: usize,
+        options: &RecordingOptions,
+    ) -> Result<()> {
+        file_system::create_parent_dir(destination)?;
+
+        let recorded = file_system::file_size(part).await?;
+        let mut dest = file_system::append_file(part).await?;
+        let mut tracker = ProgressTracker::new(self.progress.clone(), recorded, None);
+        let mut recorder = Recorder::new(options);
+        let mut last_recorded: Option<String> = None;
+        let mut current_init: Option<DashSegment> = None;
+        let mut started = false;
+
+        loop {
+            let mpd = self.fetch_dash_manifest(&self.url).await?;
+            let selected = mpd.select(representation)?;
+
+            let mut candidates = Vec::new();
+            for (period, representation) in selected {
+                let (init, media) = match (mpd.is_live(), mpd.elapsed(period)) {
+                    (true, Some(elapsed)) => {
+                        representation.live_segments(elapsed, mpd.time_shift_buffer_depth)?
+                    }
+                    (true, None) => representation.segments(None)?,
+                    (false, _) => representation.segments(period.duration.or(mpd.duration))?,
+                };
+
+                candidates.extend(media.into_iter().map(|media| (init.clone(), media)));
+            }
+
+            // The segments up to the last recorded one are skipped, even if they were not recorded, to keep the order.
+            let last_position = candidates
+                .iter()
+                .rposition(|(_, media)| Some(&media.url) == last_recorded.as_ref());
+            if let Some(position) = last_position {
+                candidates.drain(..=position);
+            }
+
+            if !started && mpd.is_live() {
+                candidates.drain(..live_edge_skipped(candidates.len(), options.from_start));
+            }
+            started = true;
+
+            let candidates = recorder.take(candidates, |(_, media)| media.duration.unwrap_or(0.0));
+            let last_duration = candidates.last().and_then(|(_, media)| media.duration);
+
+            let mut segments = Vec::with_capacity(candidates.len());
+            for (init, media) in candidates {
+                if init.is_some() && init != current_init {
+                    segments.extend(init.clone());
+                    current_init = init;
+                }
+
+                last_recorded = Some(media.url.clone());
+                segments.push(media);
+            }
+
+            let fetched =
+                futures_util::stream::iter(segments)
+                    .map(|segment| async move {
+                        self.fetch_segment(&segment.url, segment.byte_range).await
+                    })
+                    .buffered(parallelism);
+
+            self.write_segments(fetched, None, &mut dest, &mut tracker)
+                .await?;
+
+            if !mpd.is_live() || recorder.is_done() {
+                break;
+            }
+
+            let delay = mpd
+                .minimum_update_period
+                .or(last_duration)
+                .map(Duration::from_secs_f64)
+                .unwrap_or(MIN_REFRESH_DELAY);
+
+            if !recorder.wait(delay).await {
+                break;
+            }
+        }
+
+        dest.flush().await?;
+        drop(dest);
+
+        tokio::fs::rename(part, destination).await?;
+        tracker.finish();
+
+        Ok(())
+    }
+}
+
+/// Returns the number of segments to skip to start a recording at the live edge, unless it starts from the beginning.
+fn live_edge_skipped(segments: usize, from_start: bool) -> usize {
+    ternary!(from_start, 0, segments.saturating_sub(LIVE_EDGE_SEGMENTS))
+}
+
+/// Returns the number of segments of an HLS playlist to skip, because they were already recorded,
+/// or because they are before the live edge when the recording starts.
+///
+/// # Arguments
+///
+/// * `segments` - The segments of the playlist.
+/// * `next_sequence` - The sequence number of the next segment to record, or none if the recording starts.
+/// * `from_start` - Whether the recording starts from the beginning of the playlist.
+fn hls_skipped(segments: &[Segment], next_sequence: Option<u64>, from_start: bool) -> usize {
+    match next_sequence {
+        Some(next) => segments
+            .iter()
+            .take_while(|segment| segment.sequence < next)
+            .count(),
+        None => live_edge_skipped(segments.len(), from_start),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the segments with the given sequence numbers.
+    fn segments(sequences: std::ops::Range<u64>) -> Vec<Segment> {
+        sequences
+            .map(|sequence| Segment {
+                url: format!("https://example.com/live/{}.ts", sequence),
+                duration: 2.0,
+                sequence,
+                byte_range: None,
+                key: None,
+                init: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recorder_takes_until_duration() {
+        let options = RecordingOptions {
+            duration: Some(Duration::from_secs(5)),
+            ..RecordingOptions::default()
+        };
+        let mut recorder = Recorder::new(&options);
+        assert!(!recorder.is_done());
+
+        // The segment reaching the duration is recorded entirely
+        let taken = recorder.take(vec![2.0, 2.0], |duration| *duration);
+        assert_eq!(taken, vec![2.0, 2.0]);
+        assert!(!recorder.is_done());
+
+        let taken = recorder.take(vec![2.0, 2.0, 2.0], |duration| *duration);
+        assert_eq!(taken, vec![2.0]);
+        assert!(recorder.is_done());
+
+        assert!(recorder.take(vec![2.0], |duration| *duration).is_empty());
+    }
+
+    #[test]
+    fn recorder_takes_until_stopped() {
+        let options = RecordingOptions::default();
+        let mut recorder = Recorder::new(&options);
+
+        let taken = recorder.take(vec![60.0; 100], |duration| *duration);
+        assert_eq!(taken.len(), 100);
+        assert!(!recorder.is_done());
+
+        options.stop.cancel();
+        assert!(recorder.is_done());
+        assert!(recorder.take(vec![1.0], |duration| *duration).is_empty());
+    }
+
+    #[tokio::test]
+    async fn recorder_waits_until_stopped() {
+        let options = RecordingOptions::default();
+        let recorder = Recorder::new(&options);
+
+        assert!(recorder.wait(Duration::ZERO).await);
+
+        options.stop.cancel();
+        assert!(!recorder.wait(MAX_REFRESH_DELAY).await);
+    }
+
+    #[test]
+    fn skip_to_live_edge() {
+        assert_eq!(live_edge_skipped(10, false), 10 - LIVE_EDGE_SEGMENTS);
+        assert_eq!(live_edge_skipped(10, true), 0);
+        assert_eq!(live_edge_skipped(LIVE_EDGE_SEGMENTS, false), 0);
+        assert_eq!(live_edge_skipped(1, false), 0);
+        assert_eq!(live_edge_skipped(0, false), 0);
+    }
+
+    #[test]
+    fn skip_recorded_hls_segments() {
+        let playlist = segments(100..110);
+
+        // At the start, the recording begins at the live edge, or at the first segment
+        assert_eq!(hls_skipped(&playlist, None, false), 7);
+        assert_eq!(hls_skipped(&playlist, None, true), 0);
+
+        // Then, the segments before the next one to record are skipped, whatever the start
+        assert_eq!(hls_skipped(&playlist, Some(105), false), 5);
+        assert_eq!(hls_skipped(&playlist, Some(105), true), 5);
+        assert_eq!(hls_skipped(&playlist, Some(110), false), 10);
+
+        // The segments missed while the playlist slid are not waited for
+        assert_eq!(hls_skipped(&playlist, Some(90), false), 0);
+        assert_eq!(hls_skipped(&[], Some(90), false), 0);
+    }
+}
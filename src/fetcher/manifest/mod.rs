@@ -0,0 +1,138 @@
+//! Native downloaders of streams described by manifests, instead of a single file.
+//!
+//! The streams are split into segments listed by the manifest, which are downloaded concurrently
+//! and concatenated in order into a single file.
+
+use crate::error::{Error, Result};
+use crate::fetcher::progress::ProgressTracker;
+use crate::fetcher::Fetcher;
+use futures_util::{Stream, StreamExt};
+use reqwest::header::RANGE;
+use reqwest::Url;
+use std::fmt::Display;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+pub mod dash;
+pub mod hls;
+pub mod live;
+
+/// A range of bytes of a resource, e.g. a segment stored in a larger file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    /// The first byte of the range.
+    pub offset: u64,
+    /// The number of bytes of the range.
+    pub length: u64,
+}
+
+impl ByteRange {
+    /// Returns the last byte of the range (inclusive).
+    pub fn end(&self) -> u64 {
+        (self.offset + self.length).saturating_sub(1)
+    }
+}
+
+impl Fetcher {
+    /// Fetches the text at the given URL, e.g. a manifest, retrying transient failures.
+    pub(crate) async fn fetch_text(&self, url: &str) -> Result<String> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching text from {}", url);
+
+        self.retry
+            .run(|| async {
+                let response = self
+                    .client
+                    .get(url)
+                    .headers(self.headers.clone())
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                let text = response.text().await?;
+                Ok(text)
+            })
+            .await
+    }
+
+    /// Fetches a segment at the given URL, or a range of it, retrying transient failures.
+    pub(crate) async fn fetch_segment(
+        &self,
+        url: &str,
+        range: Option<ByteRange>,
+    ) -> Result<Vec<u8>> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Fetching segment {} ({:?})", url, range);
+
+        self.retry
+            .run(|| async {
+                let mut request = self.client.get(url).headers(self.headers.clone());
+                if let Some(range) = range {
+                    request =
+                        request.header(RANGE, format!("bytes={}-{}", range.offset, range.end()));
+                }
+
+                let response = request.send().await?.error_for_status()?;
+                let expected = response.content_length();
+                let segment = response.bytes().await?;
+
+                if let Some(expected) = expected {
+                    if segment.len() as u64 != expected {
+                        return Err(Error::Incomplete(segment.len() as u64, expected));
+                    }
+                }
+
+                Ok(segment.to_vec())
+            })
+            .await
+    }
+
+    /// Writes the fetched segments to the destination, in the order of the stream.
+    /// If the size of the stream is unknown, it is estimated from the average size of the segments and their total number.
+    pub(crate) async fn write_segments(
+        &self,
+        segments: impl Stream<Item = Result<Vec<u8>>>,
+        total: Option<usize>,
+        dest: &mut File,
+        tracker: &mut ProgressTracker,
+    ) -> Result<()> {
+        let mut segments = std::pin::pin!(segments);
+
+        let mut received = 0;
+        let mut written = 0;
+
+        while let Some(data) = segments.next().await {
+            let data = data?;
+
+            dest.write_all(&data).await?;
+            received += data.len() as u64;
+            written += 1;
+
+            if let (None, Some(total)) = (self.size_hint, total) {
+                tracker.set_total(Some(received * total as u64 / written));
+            }
+            tracker.advance(data.len() as u64);
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolves a URL found in a manifest, relative to the URL of the manifest.
+fn resolve(base: &Url, url: &str) -> Result<String> {
+    let resolved = base
+        .join(url)
+        .map_err(|e| manifest_error(format!("invalid URL '{}': {}", url, e)))?;
+
+    Ok(resolved.to_string())
+}
+
+/// Parses the URL of a manifest, against which its relative URLs are resolved.
+fn parse_base(url: &str) -> Result<Url> {
+    Url::parse(url).map_err(|e| manifest_error(format!("invalid URL '{}': {}", url, e)))
+}
+
+/// Creates an error for an invalid manifest.
+fn manifest_error(reason: impl Display) -> Error {
+    Error::Manifest(reason.to_string())
+}
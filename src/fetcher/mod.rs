@@ -6,14 +6,33 @@
 //! The `blocking` module contains blocking functions for fetching data from YouTube.
 
 use crate::error::{Error, Result};
+use crate::fetcher::client::{default_client, DEFAULT_USER_AGENT};
+use crate::fetcher::progress::{ProgressObserver, ProgressTracker};
 use crate::utils::file_system;
+use crate::utils::retry::RetryPolicy;
 use derive_more::Display;
 use futures_util::StreamExt;
-use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
-use std::path::{Path};
+use reqwest::header::{
+    HeaderMap, HeaderValue, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE, USER_AGENT,
+};
+use reqwest::{Client, Response, StatusCode};
+use std::path::{Path, PathBuf};
 use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
 
+pub mod captions;
+pub mod channel;
+pub mod client;
+pub mod comments;
+pub mod cookies;
 pub mod deps;
+pub mod entries;
+pub mod manifest;
+pub mod playlist;
+pub mod postprocess;
+pub mod progress;
+pub mod search;
+pub mod storyboard;
 pub mod streams;
 pub mod thumbnail;
 
@@ -38,6 +57,18 @@ pub mod thumbnail;
 pub struct Fetcher {
     /// The URL to fetch data from.
     url: String,
+    /// The HTTP client to send the requests with.
+    client: Client,
+    /// The headers to send with the requests.
+    headers: HeaderMap,
+    /// The token to cancel the requests with.
+    cancellation: CancellationToken,
+    /// The observer notified with the download progress.
+    progress: Option<ProgressObserver>,
+    /// The expected size of the asset, used when the server does not announce it.
+    size_hint: Option<u64>,
+    /// The policy to retry failed requests with.
+    retry: RetryPolicy,
 }
 
 impl Fetcher {
@@ -49,10 +80,81 @@ impl Fetcher {
     pub fn new(url: impl AsRef<str>) -> Self {
         Self {
             url: url.as_ref().to_string(),
+            client: default_client(),
+            headers: HeaderMap::new(),
+            cancellation: CancellationToken::new(),
+            progress: None,
+            size_hint: None,
+            retry: RetryPolicy::default(),
         }
     }
 
+    /// Sets the HTTP client to send the requests with, e.g. built from [`client::ClientOptions`].
+    /// By default, a client shared by all the fetchers is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The HTTP client to send the requests with.
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sets the policy to retry failed requests with. By default, transient failures are retried twice.
+    /// Interrupted downloads are resumed from where they stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The policy to retry failed requests with.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the observer notified with the download progress of assets.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - The observer notified with the download progress.
+    pub fn with_progress(mut self, progress: Option<ProgressObserver>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Sets the expected size of the asset, used to report the progress when the server does not send a 'Content-Length'.
+    ///
+    /// # Arguments
+    ///
+    /// * `size_hint` - The expected size of the asset, in bytes.
+    pub fn with_size_hint(mut self, size_hint: Option<u64>) -> Self {
+        self.size_hint = size_hint;
+        self
+    }
+
+    /// Sets the headers to send with the requests, e.g. the user agent or the cookies.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The headers to send with the requests.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Sets the token to cancel the requests with.
+    /// When cancelled, the HTTP stream is aborted, and the partially written asset is removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation` - The token to cancel the requests with.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
     /// Fetch the data from the URL and return it as Serde value.
+    /// Unless the headers hold one, a default 'User-Agent' is sent, as required by APIs like GitHub's,
+    /// since the HTTP client may not have one.
     ///
     /// # Arguments
     ///
@@ -60,14 +162,28 @@ impl Fetcher {
     ///
     /// # Errors
     ///
-    /// This function will return an error if the data could not be fetched or parsed.
+    /// This function will return an error if the data could not be fetched or parsed,
+    /// or [`Error::Cancelled`] if the request was cancelled.
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
     pub async fn fetch_json(&self, auth_token: Option<String>) -> Result<serde_json::Value> {
+        tokio::select! {
+            result = self.retry.run(|| self.fetch_json_uncancelled(auth_token.clone())) => result,
+            _ = self.cancellation.cancelled() => Err(Error::Cancelled),
+        }
+    }
+
+    /// Fetch the data from the URL and return it as Serde value, ignoring the cancellation token.
+    async fn fetch_json_uncancelled(
+        &self,
+        auth_token: Option<String>,
+    ) -> Result<serde_json::Value> {
         #[cfg(feature = "tracing")]
         tracing::debug!("Fetching JSON from {}", self.url);
 
-        let mut headers = HeaderMap::new();
-        headers.insert(USER_AGENT, HeaderValue::from_static("rust-reqwest"));
+        let mut headers = self.headers.clone();
+        if !headers.contains_key(USER_AGENT) {
+            headers.insert(USER_AGENT, HeaderValue::from_static(DEFAULT_USER_AGENT));
+        }
 
         if let Some(auth_token) = auth_token {
             let value = HeaderValue::from_str(&format!("Bearer {}", auth_token))
@@ -76,8 +192,8 @@ impl Fetcher {
             headers.insert(reqwest::header::AUTHORIZATION, value);
         }
 
-        let client = reqwest::Client::new();
-        let response = client
+        let response = self
+            .client
             .get(&self.url)
             .headers(headers)
             .send()
@@ -90,30 +206,400 @@ impl Fetcher {
 
     /// Downloads the asset at the given URL and writes it to the given destination.
     ///
+    /// The asset is first written to a '.part' file next to the destination, which is renamed once the download is complete.
+    /// If a partial file already exists, for example after a network failure, the download is resumed with an HTTP Range request.
+    /// The validator (ETag or Last-Modified) of the first response is stored in a '.part.validator' file,
+    /// so the download restarts from scratch if the asset changed in the meantime.
+    ///
     /// # Arguments
     ///
     /// * `destination` - The path to write the asset to.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the asset could not be fetched or written to the destination.
+    /// This function will return an error if the asset could not be fetched or written to the destination,
+    /// or if fewer bytes than announced by the server were received.
+    /// It will return [`Error::Cancelled`] if the download was cancelled, in which case the partial file is removed.
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
     pub async fn fetch_asset(&self, destination: impl AsRef<Path>) -> Result<()> {
         #[cfg(feature = "tracing")]
         tracing::debug!("Fetching asset from {} to {:?}", self.url, destination);
 
-        let response = reqwest::get(&self.url).await?.error_for_status()?;
-        file_system::create_parent_dir(&destination)?;
+        let destination = destination.as_ref();
+        let part = file_system::try_part(destination)?;
+        let validator = Self::validator_path(&part)?;
+
+        let result = tokio::select! {
+            result = self.retry.run(|| self.fetch_asset_uncancelled(destination, &part, &validator)) => result,
+            _ = self.cancellation.cancelled() => Err(Error::Cancelled),
+        };
 
-        let mut dest = file_system::create_file(destination).await?;
+        if let Err(Error::Cancelled) = result {
+            file_system::remove_file(&part).await?;
+            file_system::remove_file(&validator).await?;
+        }
+
+        result
+    }
+
+    /// Downloads the asset at the given URL to the partial file, resuming it if possible, ignoring the cancellation token.
+    async fn fetch_asset_uncancelled(
+        &self,
+        destination: &Path,
+        part: &Path,
+        validator: &Path,
+    ) -> Result<()> {
+        file_system::create_parent_dir(destination)?;
+
+        let mut offset = file_system::file_size(part).await?;
+        let stored_validator = match offset {
+            0 => None,
+            _ => tokio::fs::read_to_string(validator).await.ok(),
+        };
+
+        let mut response = self.request_range(offset, None, stored_validator).await?;
+
+        if offset > 0 {
+            let content_range = Self::content_range(&response);
+
+            if response.status() == StatusCode::RANGE_NOT_SATISFIABLE
+                && content_range.and_then(|(_, total)| total) == Some(offset)
+            {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Partial file {:?} is already complete", part);
+
+                Self::complete(destination, part, validator).await?;
+                ProgressTracker::new(self.progress.clone(), offset, Some(offset)).finish();
+
+                return Ok(());
+            }
+
+            let resumed = response.status() == StatusCode::PARTIAL_CONTENT
+                && content_range.and_then(|(start, _)| start) == Some(offset);
+
+            if !resumed {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Could not resume {:?}, restarting the download", part);
+
+                if response.status() != StatusCode::OK {
+                    response = self.request_range(0, None, None).await?;
+                }
+
+                offset = 0;
+            }
+        }
+
+        let response = response.error_for_status()?;
+        let expected = match offset {
+            0 => response.content_length(),
+            _ => Self::content_range(&response)
+                .and_then(|(_, total)| total)
+                .or(response.content_length().map(|length| offset + length)),
+        };
+
+        let mut dest = match offset {
+            0 => {
+                Self::store_validator(&response, validator).await?;
+                file_system::create_file(part).await?
+            }
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Resuming download of {:?} from byte {}", part, offset);
+
+                file_system::append_file(part).await?
+            }
+        };
+
+        let mut received = offset;
         let mut stream = response.bytes_stream();
+        let mut tracker =
+            ProgressTracker::new(self.progress.clone(), offset, expected.or(self.size_hint));
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk?;
 
             dest.write_all(&chunk).await?;
+            received += chunk.len() as u64;
+            tracker.advance(chunk.len() as u64);
+        }
+
+        dest.flush().await?;
+        drop(dest);
+
+        if let Some(expected) = expected {
+            if received != expected {
+                return Err(Error::Incomplete(received, expected));
+            }
+        }
+
+        Self::complete(destination, part, validator).await?;
+        tracker.finish();
+
+        Ok(())
+    }
+
+    /// Downloads the asset at the given URL in chunks of the given size, and writes it to the given destination.
+    ///
+    /// Up to `parallelism` chunks are fetched concurrently with HTTP Range requests, and written in order to a '.part' file,
+    /// so an interrupted download can be resumed the same way as with [`Fetcher::fetch_asset`].
+    /// If the server does not support Range requests, the asset is downloaded in a single request.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The path to write the asset to.
+    /// * `chunk_size` - The size of each chunk, in bytes.
+    /// * `parallelism` - The maximum number of chunks fetched at the same time.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a chunk could not be fetched or written to the destination,
+    /// or if the asset changed during the download.
+    /// It will return [`Error::Cancelled`] if the download was cancelled, in which case the partial file is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::fetcher::Fetcher;
+    /// # use std::path::PathBuf;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let url = "https://example.com/video.mp4";
+    /// let destination = PathBuf::from("video.mp4");
+    ///
+    /// let fetcher = Fetcher::new(url);
+    /// fetcher.fetch_asset_in_chunks(destination, 10 * 1024 * 1024, 4).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, destination))
+    )]
+    pub async fn fetch_asset_in_chunks(
+        &self,
+        destination: impl AsRef<Path>,
+        chunk_size: u64,
+        parallelism: usize,
+    ) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Fetching asset from {} to {:?}, in chunks of {} bytes",
+            self.url,
+            destination.as_ref(),
+            chunk_size
+        );
+
+        let destination = destination.as_ref();
+        let part = file_system::try_part(destination)?;
+        let validator = Self::validator_path(&part)?;
+
+        let chunk_size = chunk_size.max(1);
+        let parallelism = parallelism.max(1);
+
+        let result = tokio::select! {
+            result = self.retry.run(|| self.fetch_chunks_uncancelled(destination, &part, &validator, chunk_size, parallelism)) => result,
+            _ = self.cancellation.cancelled() => Err(Error::Cancelled),
+        };
+
+        if let Err(Error::Cancelled) = result {
+            file_system::remove_file(&part).await?;
+            file_system::remove_file(&validator).await?;
+        }
+
+        result
+    }
+
+    /// Downloads the asset at the given URL in chunks to the partial file, resuming it if possible, ignoring the cancellation token.
+    async fn fetch_chunks_uncancelled(
+        &self,
+        destination: &Path,
+        part: &Path,
+        validator: &Path,
+        chunk_size: u64,
+        parallelism: usize,
+    ) -> Result<()> {
+        file_system::create_parent_dir(destination)?;
+
+        // A single byte request tells if the server supports ranges, and gives the total size and the validator of the asset.
+        let probe = self
+            .request_range(0, Some(0), None)
+            .await?
+            .error_for_status()?;
+        let total = match Self::content_range(&probe) {
+            Some((_, Some(total))) if probe.status() == StatusCode::PARTIAL_CONTENT => total,
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Range requests are not supported, fetching the whole asset");
+
+                drop(probe);
+                return self
+                    .fetch_asset_uncancelled(destination, part, validator)
+                    .await;
+            }
+        };
+
+        let current_validator = Self::validator(&probe);
+        let stored_validator = tokio::fs::read_to_string(validator).await.ok();
+
+        let mut offset = file_system::file_size(part).await?;
+        if offset > total || current_validator.is_none() || stored_validator != current_validator {
+            offset = 0;
+        }
+
+        let mut dest = match offset {
+            0 => {
+                Self::store_validator(&probe, validator).await?;
+                file_system::create_file(part).await?
+            }
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Resuming download of {:?} from byte {}", part, offset);
+
+                file_system::append_file(part).await?
+            }
+        };
+
+        let ranges = (offset..total)
+            .step_by(chunk_size as usize)
+            .map(|start| (start, (start + chunk_size).min(total) - 1));
+
+        let mut chunks = futures_util::stream::iter(ranges)
+            .map(|(start, end)| self.fetch_chunk(start, end, current_validator.clone()))
+            .buffered(parallelism);
+
+        let mut received = offset;
+        let mut tracker = ProgressTracker::new(self.progress.clone(), offset, Some(total));
+
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk?;
+
+            dest.write_all(&chunk).await?;
+            received += chunk.len() as u64;
+            tracker.advance(chunk.len() as u64);
+        }
+
+        dest.flush().await?;
+        drop(dest);
+
+        if received != total {
+            return Err(Error::Incomplete(received, total));
         }
 
+        Self::complete(destination, part, validator).await?;
+        tracker.finish();
+
         Ok(())
     }
+
+    /// Fetches the bytes of the asset from the given start byte to the given end byte (inclusive).
+    async fn fetch_chunk(
+        &self,
+        start: u64,
+        end: u64,
+        validator: Option<String>,
+    ) -> Result<Vec<u8>> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Fetching chunk {}-{} from {}", start, end, self.url);
+
+        let response = self
+            .request_range(start, Some(end), validator)
+            .await?
+            .error_for_status()?;
+
+        let range_start = Self::content_range(&response).and_then(|(start, _)| start);
+        if response.status() != StatusCode::PARTIAL_CONTENT || range_start != Some(start) {
+            return Err(Error::Unknown(format!(
+                "The server did not honor the range {}-{}, the asset may have changed",
+                start, end
+            )));
+        }
+
+        let chunk = response.bytes().await?;
+
+        let expected = end - start + 1;
+        if chunk.len() as u64 != expected {
+            return Err(Error::Incomplete(chunk.len() as u64, expected));
+        }
+
+        Ok(chunk.into())
+    }
+
+    /// Sends a GET request for the asset, from the given start byte to the given end byte (inclusive), or to the end of the asset.
+    /// If a validator is given, the server only honors the range if the asset did not change.
+    async fn request_range(
+        &self,
+        start: u64,
+        end: Option<u64>,
+        validator: Option<String>,
+    ) -> Result<Response> {
+        let mut request = self.client.get(&self.url).headers(self.headers.clone());
+
+        if start > 0 || end.is_some() {
+            let end = end.map(|end| end.to_string()).unwrap_or_default();
+            request = request.header(RANGE, format!("bytes={}-{}", start, end));
+
+            if let Some(validator) = validator {
+                request = request.header(IF_RANGE, validator);
+            }
+        }
+
+        let response = request.send().await?;
+        Ok(response)
+    }
+
+    /// Parses the 'Content-Range' header of the response, e.g. 'bytes 100-199/1000' or 'bytes */1000'.
+    /// Returns the first byte position and the total size of the asset, if known.
+    fn content_range(response: &Response) -> Option<(Option<u64>, Option<u64>)> {
+        let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+        let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+
+        let start = range
+            .split_once('-')
+            .and_then(|(start, _)| start.parse().ok());
+        let total = total.parse().ok();
+
+        Some((start, total))
+    }
+
+    /// Returns the validator of the response, preferring a strong ETag over the Last-Modified date.
+    /// Weak ETags can't be used in 'If-Range' requests, so they are ignored.
+    fn validator(response: &Response) -> Option<String> {
+        let headers = response.headers();
+
+        let etag = headers
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .filter(|etag| !etag.starts_with("W/"));
+        let last_modified = headers
+            .get(LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok());
+
+        etag.or(last_modified).map(String::from)
+    }
+
+    /// Stores the validator of the response next to the partial file, or removes the stored one if there is none.
+    async fn store_validator(response: &Response, validator: &Path) -> Result<()> {
+        match Self::validator(response) {
+            Some(value) => tokio::fs::write(validator, value).await?,
+            None => file_system::remove_file(validator).await?,
+        }
+
+        Ok(())
+    }
+
+    /// Moves the complete partial file to its destination, and removes its validator.
+    async fn complete(destination: &Path, part: &Path, validator: &Path) -> Result<()> {
+        tokio::fs::rename(part, destination).await?;
+        file_system::remove_file(validator).await?;
+
+        Ok(())
+    }
+
+    /// Returns the path of the file storing the validator of the given partial file.
+    fn validator_path(part: &Path) -> Result<PathBuf> {
+        let name = file_system::try_name(part)?;
+        let validator = part.with_file_name(format!("{}.validator", name));
+
+        Ok(validator)
+    }
 }
@@ -0,0 +1,463 @@
+//! Tools for fetching playlists from YouTube, and downloading their videos.
+//!
+//! The playlists are fetched flat with 'yt-dlp', in a single JSON document listing their entries.
+//! The entries are resolved into complete videos lazily, or all at once when the playlist is fetched.
+
+use crate::error::{Error, Result};
+use crate::executor::Executor;
+use crate::model::format::Extension;
+use crate::model::playlist::{Playlist, PlaylistEntry};
+use crate::model::sorting::FormatComparator;
+use crate::model::Video;
+use crate::utils::file_system;
+use crate::{ternary, utils, Youtube};
+use futures_util::StreamExt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The timeout of the flat fetching of a playlist, a channel tab or a search.
+/// Their entries are listed a page of about a hundred at a time, so listing tens of thousands of them takes minutes.
+const FLAT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// The options of the playlist fetching.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::playlist::PlaylistOptions;
+/// let options = PlaylistOptions {
+///     items: Some(String::from("1:10,-1")),
+///     reverse: false,
+///     lazy: true,
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistOptions {
+    /// The items to fetch, with the syntax of the '--playlist-items' option of 'yt-dlp', e.g. '1:10,15,-3:'.
+    /// All the items are fetched if `None`.
+    pub items: Option<String>,
+    /// Whether to fetch the entries in reverse order.
+    pub reverse: bool,
+    /// Whether to resolve the entries into complete videos only when they are needed, which is much faster.
+    /// Otherwise, all the entries are resolved when the playlist is fetched.
+    pub lazy: bool,
+}
+
+impl Default for PlaylistOptions {
+    fn default() -> Self {
+        Self {
+            items: None,
+            reverse: false,
+            lazy: true,
+        }
+    }
+}
+
+impl Youtube {
+    /// Fetch the playlist information from the given URL, with its entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the playlist to fetch.
+    /// * `options` - The items, order and resolution of the entries to fetch.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the URL is not a playlist,
+    /// or if the playlist information or its entries could not be fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::playlist::PlaylistOptions;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/playlist?list=PLlaN88a7y2_plecYoJxvRFTLHVbIVAOoc");
+    /// let options = PlaylistOptions {
+    ///     items: Some(String::from("1:5")),
+    ///     ..Default::default()
+    /// };
+    /// let playlist = fetcher.fetch_playlist_infos(url, &options).await?;
+    /// for entry in &playlist.entries {
+    ///     println!("{:?}: {:?}", entry.playlist_index, entry.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub async fn fetch_playlist_infos(
+        &self,
+        url: String,
+        options: &PlaylistOptions,
+    ) -> Result<Playlist> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching playlist information for {}", url);
+
+        let mut playlist_args = Vec::new();
+        if let Some(items) = &options.items {
+            playlist_args.extend([String::from("--playlist-items"), items.clone()]);
+        }
+        if options.reverse {
+            playlist_args.push(String::from("--playlist-reverse"));
+        }
+
+        let output = self.fetch_flat_json(url, playlist_args).await?;
+
+        let mut playlist: Playlist = serde_json::from_str(&output).map_err(Error::Serde)?;
+        number_entries(&mut playlist, options);
+
+        if !options.lazy {
+            self.resolve_entries(&mut playlist).await?;
+        }
+
+        Ok(playlist)
+    }
+
+    /// Resolves the entry of a playlist into a complete video, fetching it if it was not resolved yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The entry to resolve.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video information could not be fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::playlist::PlaylistOptions;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/playlist?list=PLlaN88a7y2_plecYoJxvRFTLHVbIVAOoc");
+    /// let playlist = fetcher.fetch_playlist_infos(url, &PlaylistOptions::default()).await?;
+    ///
+    /// let video = fetcher.resolve_entry(&playlist.entries[0]).await?;
+    /// let video_path = fetcher.download_video(&video, "my-video.mp4").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(entry)))]
+    pub async fn resolve_entry(&self, entry: &PlaylistEntry) -> Result<Video> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Resolving playlist entry {}", entry.id);
+
+        match &entry.video {
+            Some(video) => Ok(video.as_ref().clone()),
+            None => self.fetch_video_infos(entry.url.clone()).await,
+        }
+    }
+
+    /// Downloads the videos of the playlist entries into the given directory, in the output directory,
+    /// and returns their paths in the order of the entries.
+    /// The videos are named after their index and ID, e.g. '001-dQw4w9WgXcQ.mp4', in MP4 when their video format is,
+    /// or in Matroska otherwise, which holds any video codec along with the AAC audio of the combined file.
+    /// Up to [`Youtube::concurrent_downloads`] of them are downloaded at the same time.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `playlist` - The playlist to download.
+    /// * `output` - The name of the directory to save the videos to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error as soon as an entry could not be resolved or downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::playlist::PlaylistOptions;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    /// fetcher.with_concurrent_downloads(3);
+    ///
+    /// let url = String::from("https://www.youtube.com/playlist?list=PLlaN88a7y2_plecYoJxvRFTLHVbIVAOoc");
+    /// let playlist = fetcher.fetch_playlist_infos(url, &PlaylistOptions::default()).await?;
+    ///
+    /// let video_paths = fetcher.download_playlist(&playlist, "my-playlist").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(playlist))
+    )]
+    pub async fn download_playlist(
+        &self,
+        playlist: &Playlist,
+        output: impl AsRef<str> + std::fmt::Debug,
+    ) -> Result<Vec<PathBuf>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Downloading {} entries of playlist {}",
+            playlist.entries.len(),
+            playlist.id
+        );
+
+        let directory = output.as_ref();
+        file_system::create_dir(self.output_dir.join(directory))?;
+
+        let last_index = playlist
+            .entries
+            .iter()
+            .zip(1..)
+            .map(|(entry, position)| entry.playlist_index.unwrap_or(position))
+            .max()
+            .unwrap_or_default();
+        let width = last_index.to_string().len().max(3);
+
+        let downloads = playlist.entries.iter().zip(1..).map(|(entry, position)| {
+            let index = entry.playlist_index.unwrap_or(position);
+
+            async move {
+                let video = self.resolve_entry(entry).await?;
+                let extension = merge_extension(&video, self.video_comparator.as_ref());
+                let name = format!("{}/{:0width$}-{}.{}", directory, index, entry.id, extension);
+
+                self.download_video(&video, name).await
+            }
+        });
+
+        let mut downloads =
+            futures_util::stream::iter(downloads).buffered(self.concurrent_downloads.max(1));
+        let mut paths = Vec::with_capacity(playlist.entries.len());
+
+        loop {
+            let path = tokio::select! {
+                path = downloads.next() => path,
+                _ = self.cancellation.cancelled() => return Err(Error::Cancelled),
+            };
+
+            match path {
+                Some(path) => paths.push(path?),
+                None => break,
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Fetches the information of a playlist-like URL flat, with the given arguments, and returns its JSON document.
+    pub(crate) async fn fetch_flat_json(
+        &self,
+        url: String,
+        mut args: Vec<String>,
+    ) -> Result<String> {
+        let mut final_args = self.args.clone();
+        final_args.append(&mut self.network_args());
+        final_args.append(&mut utils::to_owned(vec![
+            "--no-progress",
+            "--flat-playlist",
+            "--dump-single-json",
+        ]));
+        final_args.append(&mut args);
+        final_args.push(url);
+
+        let executor = Executor::new(self.libraries.youtube.clone(), FLAT_TIMEOUT, final_args)
+            .with_env(self.network_env()?);
+
+        let output = self
+            .retry
+            .run_with_cancellation(&self.cancellation, || {
+                executor.execute_with_cancellation(&self.cancellation)
+            })
+            .await?;
+
+        Ok(output.stdout)
+    }
+
+    /// Resolves all the entries of the playlist into complete videos, up to [`Youtube::concurrent_downloads`] at a time.
+    async fn resolve_entries(&self, playlist: &mut Playlist) -> Result<()> {
+        let urls: Vec<String> = playlist
+            .entries
+            .iter()
+            .map(|entry| entry.url.clone())
+            .collect();
+        let videos = urls.into_iter().map(|url| self.fetch_video_infos(url));
+
+        let mut videos =
+            futures_util::stream::iter(videos).buffered(self.concurrent_downloads.max(1));
+        let mut index = 0;
+
+        loop {
+            let video = tokio::select! {
+                video = videos.next() => video,
+                _ = self.cancellation.cancelled() => return Err(Error::Cancelled),
+            };
+
+            match video {
+                Some(video) => {
+                    playlist.entries[index].video = Some(Box::new(video?));
+                    index += 1;
+                }
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Numbers the entries of the playlist with their index, as listed by 'yt-dlp' when the items are filtered,
+/// or from their position otherwise.
+fn number_entries(playlist: &mut Playlist, options: &PlaylistOptions) {
+    let count = playlist.entry_count.unwrap_or(playlist.entries.len());
+    let requested = &playlist.requested_entries;
+
+    for (position, entry) in playlist.entries.iter_mut().enumerate() {
+        if entry.playlist_index.is_some() {
+            continue;
+        }
+
+        entry.playlist_index = match (requested, &options.items) {
+            (Some(requested), _) => requested.get(position).copied(),
+            (None, Some(_)) => None,
+            (None, None) if options.reverse => count.checked_sub(position),
+            (None, None) => Some(position + 1),
+        };
+    }
+}
+
+/// Returns the extension of the file the audio and video of the video are combined into:
+/// 'mp4' if its best video format is in MP4, or 'mkv' otherwise.
+fn merge_extension(video: &Video, comparator: &dyn FormatComparator) -> &'static str {
+    let is_mp4 = video
+        .best_video_format_by(comparator)
+        .is_some_and(|format| format.codec_info.video_ext == Extension::Mp4);
+
+    ternary!(is_mp4, "mp4", "mkv")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Returns a playlist of 10 entries, of which the given ones were fetched.
+    fn fetched_playlist(ids: &[&str], requested_entries: Option<Vec<usize>>) -> Playlist {
+        let entries: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                json!({
+                    "id": id,
+                    "url": format!("https://www.youtube.com/watch?v={}", id),
+                })
+            })
+            .collect();
+
+        let playlist = json!({
+            "id": "PLlaN88a7y2_plecYoJxvRFTLHVbIVAOoc",
+            "playlist_count": 10,
+            "entries": entries,
+            "requested_entries": requested_entries,
+        });
+
+        serde_json::from_value(playlist).unwrap()
+    }
+
+    /// Numbers the entries of the playlist, and returns their indexes.
+    fn indexes(mut playlist: Playlist, items: Option<&str>, reverse: bool) -> Vec<Option<usize>> {
+        let options = PlaylistOptions {
+            items: items.map(str::to_string),
+            reverse,
+            ..PlaylistOptions::default()
+        };
+        number_entries(&mut playlist, &options);
+
+        playlist
+            .entries
+            .iter()
+            .map(|entry| entry.playlist_index)
+            .collect()
+    }
+
+    #[test]
+    fn parse_requested_entries() {
+        let content = r#"{"id": "PL", "entries": [], "requested_entries": [2, 5]}"#;
+        let playlist: Playlist = serde_json::from_str(content).unwrap();
+        assert_eq!(playlist.requested_entries, Some(vec![2, 5]));
+
+        let playlist: Playlist = serde_json::from_str(r#"{"id": "PL", "entries": []}"#).unwrap();
+        assert_eq!(playlist.requested_entries, None);
+    }
+
+    #[test]
+    fn number_entries_from_position() {
+        let playlist = fetched_playlist(&["a", "b", "c"], None);
+
+        assert_eq!(
+            indexes(playlist.clone(), None, false),
+            vec![Some(1), Some(2), Some(3)]
+        );
+
+        // In reverse, the entries are numbered from the end of the playlist
+        assert_eq!(
+            indexes(playlist, None, true),
+            vec![Some(10), Some(9), Some(8)]
+        );
+    }
+
+    #[test]
+    fn number_entries_from_requested_entries() {
+        let playlist = fetched_playlist(&["b", "e", "j"], Some(vec![2, 5, 10]));
+        assert_eq!(
+            indexes(playlist, Some("2,5,-1"), false),
+            vec![Some(2), Some(5), Some(10)]
+        );
+
+        let playlist = fetched_playlist(&["j", "e", "b"], Some(vec![10, 5, 2]));
+        assert_eq!(
+            indexes(playlist, Some("2,5,-1"), true),
+            vec![Some(10), Some(5), Some(2)]
+        );
+
+        // The entries beyond the requested ones are left unnumbered
+        let playlist = fetched_playlist(&["b", "e"], Some(vec![2]));
+        assert_eq!(indexes(playlist, Some("2"), false), vec![Some(2), None]);
+    }
+
+    #[test]
+    fn number_entries_with_items() {
+        // Without the requested entries, the positions are not the indexes of the filtered items
+        let playlist = fetched_playlist(&["b", "e"], None);
+        assert_eq!(indexes(playlist, Some("2,5"), false), vec![None, None]);
+    }
+
+    #[test]
+    fn keep_entries_index() {
+        let mut playlist = fetched_playlist(&["a", "b"], None);
+        playlist.entries[1].playlist_index = Some(7);
+
+        assert_eq!(indexes(playlist, None, false), vec![Some(1), Some(7)]);
+    }
+}
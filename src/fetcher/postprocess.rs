@@ -0,0 +1,1152 @@
+//! Tools for post-processing the downloaded files with 'ffmpeg'.
+//!
+//! The subtitles, the thumbnail and the metadata of a video can be embedded while its audio and video are combined,
+//! in a single 'ffmpeg' pass.
+//! The audio of a video can also be extracted to a chosen codec, remuxed without re-encoding when it is compatible.
+
+use crate::error::{Error, Result};
+use crate::executor::Executor;
+use crate::fetcher::Fetcher;
+use crate::model::caption::{CaptionFormat, CaptionTrack};
+use crate::model::format::{Extension, Format};
+use crate::model::Video;
+use crate::utils::{date, file_system};
+use crate::{utils, Youtube};
+use image::ImageFormat;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The timeout of the combination of the audio and video files, long enough to copy the streams of hours of video.
+const COMBINE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The timeout of the audio extraction, long enough to re-encode hours of audio.
+const EXTRACTION_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The options of what to embed into the combined file, besides its audio and video.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::postprocess::EmbedOptions;
+/// let options = EmbedOptions {
+///     subtitles: vec![String::from("en"), String::from("fr")],
+///     thumbnail: true,
+///     metadata: true,
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbedOptions {
+    /// The languages of the subtitles to embed, the best track of each language being picked.
+    /// The languages without captions are skipped.
+    pub subtitles: Vec<String>,
+    /// Whether to embed the thumbnail of the video as cover art.
+    pub thumbnail: bool,
+    /// Whether to embed the metadata of the video: title, channel, date, description and URL.
+    pub metadata: bool,
+}
+
+impl EmbedOptions {
+    /// Checks if nothing has to be embedded.
+    pub fn is_empty(&self) -> bool {
+        self.subtitles.is_empty() && !self.thumbnail && !self.metadata
+    }
+}
+
+/// The codecs the audio can be extracted to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AudioCodec {
+    /// The MP3 codec, encoded with 'libmp3lame' in a '.mp3' file.
+    #[default]
+    Mp3,
+    /// The AAC codec, in a '.m4a' file.
+    Aac,
+    /// The Opus codec, encoded with 'libopus' in a '.opus' file.
+    Opus,
+    /// The lossless FLAC codec, in a '.flac' file.
+    Flac,
+    /// The uncompressed 16-bit PCM codec, in a '.wav' file.
+    Wav,
+}
+
+impl AudioCodec {
+    /// Returns the extension of the files holding this codec, e.g. 'mp3'.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "mp3",
+            AudioCodec::Aac => "m4a",
+            AudioCodec::Opus => "opus",
+            AudioCodec::Flac => "flac",
+            AudioCodec::Wav => "wav",
+        }
+    }
+
+    /// Returns the 'ffmpeg' encoder of this codec.
+    fn encoder(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "libmp3lame",
+            AudioCodec::Aac => "aac",
+            AudioCodec::Opus => "libopus",
+            AudioCodec::Flac => "flac",
+            AudioCodec::Wav => "pcm_s16le",
+        }
+    }
+
+    /// Detects the codec of an audio stream from its name, e.g. 'opus' or 'mp4a.40.2'.
+    fn detect(codec: &str) -> Option<Self> {
+        let codec = codec.to_lowercase();
+        let (name, _) = codec.split_once('.').unwrap_or((&codec, ""));
+
+        match name {
+            "mp3" => Some(AudioCodec::Mp3),
+            "mp4a" | "aac" => Some(AudioCodec::Aac),
+            "opus" => Some(AudioCodec::Opus),
+            "flac" => Some(AudioCodec::Flac),
+            _ => None,
+        }
+    }
+}
+
+/// The quality of the re-encoded audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioQuality {
+    /// A variable bitrate quality, from 0 (best) to 10 (worst), like the '--audio-quality' option of 'yt-dlp'.
+    /// It is ignored by the Opus codec, whose encoder has no such scale and uses its default bitrate:
+    /// use [`AudioQuality::Bitrate`] to choose the quality of Opus audio.
+    Vbr(u8),
+    /// A constant bitrate, in kbit/s, e.g. 192.
+    Bitrate(u32),
+}
+
+impl Default for AudioQuality {
+    fn default() -> Self {
+        AudioQuality::Vbr(5)
+    }
+}
+
+/// The options of the audio extraction.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::postprocess::{AudioCodec, AudioOptions, AudioQuality};
+/// let options = AudioOptions {
+///     codec: AudioCodec::Mp3,
+///     quality: AudioQuality::Bitrate(192),
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioOptions {
+    /// The codec to extract the audio to.
+    pub codec: AudioCodec,
+    /// The quality of the audio, if it has to be re-encoded.
+    /// It is ignored by the lossless codecs, by Opus if it is a variable bitrate quality,
+    /// and when the audio is already in the requested codec.
+    pub quality: AudioQuality,
+}
+
+/// The containers, which support different subtitles codecs and cover arts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    /// The MP4 container and its variants, with 'mov_text' subtitles and an attached picture.
+    Mp4,
+    /// The Matroska container, with 'ass' subtitles and a cover attachment.
+    Matroska,
+    /// The WebM container, with 'webvtt' subtitles and no cover art.
+    WebM,
+    /// Any other container, without subtitles nor cover art.
+    Other,
+}
+
+impl Container {
+    /// Detects the container of a file from its extension.
+    fn detect(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "mp4" | "m4v" | "mov" => Container::Mp4,
+            "mkv" | "mka" => Container::Matroska,
+            "webm" => Container::WebM,
+            _ => Container::Other,
+        }
+    }
+
+    /// Returns the codec of the subtitles in this container, if it supports them.
+    fn subtitles_codec(&self) -> Option<&'static str> {
+        match self {
+            Container::Mp4 => Some("mov_text"),
+            Container::Matroska => Some("ass"),
+            Container::WebM => Some("webvtt"),
+            Container::Other => None,
+        }
+    }
+}
+
+/// The files to embed into the combined file, fetched beforehand.
+#[derive(Debug, Default)]
+struct Embedded {
+    /// The subtitles files, with their language and name.
+    subtitles: Vec<(PathBuf, String, Option<String>)>,
+    /// The cover art, as a JPEG file.
+    cover: Option<PathBuf>,
+}
+
+impl Embedded {
+    /// Removes the fetched files.
+    async fn remove(&self) -> Result<()> {
+        let subtitles = self.subtitles.iter().map(|(path, _, _)| path);
+
+        for path in subtitles.chain(self.cover.as_ref()) {
+            file_system::remove_file(path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Youtube {
+    /// Combines the audio and video files into a single file, embedding the subtitles, the thumbnail and the metadata
+    /// of the video according to the options, in a single 'ffmpeg' pass.
+    ///
+    /// The subtitles are embedded as 'mov_text' in MP4 files, 'ass' in Matroska files and 'webvtt' in WebM files.
+    /// The thumbnail is embedded as cover art in MP4 and Matroska files.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video the files were downloaded from.
+    /// * `audio_file` - The name of the audio file to combine.
+    /// * `video_file` - The name of the video file to combine.
+    /// * `output_file` - The name of the output file.
+    /// * `options` - The options of what to embed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the subtitles or the thumbnail could not be fetched,
+    /// or if the files could not be combined.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::postprocess::EmbedOptions;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let audio_format = video.best_audio_format().unwrap();
+    /// let audio_path = fetcher.download_format(&audio_format, "audio-stream.mp3").await?;
+    ///
+    /// let video_format = video.best_video_format().unwrap();
+    /// let format_path = fetcher.download_format(&video_format, "video-stream.mp4").await?;
+    ///
+    /// let options = EmbedOptions {
+    ///     subtitles: vec![String::from("en")],
+    ///     thumbnail: true,
+    ///     metadata: true,
+    /// };
+    /// let output_path = fetcher
+    ///     .combine_and_embed(&video, "audio-stream.mp3", "video-stream.mp4", "my-output.mp4", &options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video, audio_file, video_file, output_file))
+    )]
+    pub async fn combine_and_embed(
+        &self,
+        video: &Video,
+        audio_file: impl AsRef<str>,
+        video_file: impl AsRef<str>,
+        output_file: impl AsRef<str>,
+        options: &EmbedOptions,
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Combining and embedding {:?} into {}",
+            options,
+            output_file.as_ref()
+        );
+
+        let output_path = self.output_dir.join(output_file.as_ref());
+        let container = Container::detect(&output_path);
+        let file_name = file_system::try_stem(&output_path)?;
+
+        let mut embedded = Embedded::default();
+        let result = async {
+            self.fetch_embedded(video, &file_name, container, options, &mut embedded)
+                .await?;
+
+            let args = embed_args(
+                &self.output_dir.join(audio_file.as_ref()),
+                &self.output_dir.join(video_file.as_ref()),
+                &output_path,
+                container,
+                &embedded,
+                options.metadata.then_some(video),
+            )?;
+
+            let executor = Executor::new(self.libraries.ffmpeg.clone(), COMBINE_TIMEOUT, args);
+
+            executor.execute_with_cancellation(&self.cancellation).await
+        }
+        .await;
+
+        // The fetched files are removed whether the files could be combined or not
+        embedded.remove().await?;
+        if let Err(Error::Cancelled) = result {
+            file_system::remove_file(&output_path).await?;
+        }
+
+        result?;
+        Ok(output_path)
+    }
+
+    /// Fetches the subtitles and the thumbnail to embed, as supported by the container.
+    async fn fetch_embedded(
+        &self,
+        video: &Video,
+        file_name: &str,
+        container: Container,
+        options: &EmbedOptions,
+        embedded: &mut Embedded,
+    ) -> Result<()> {
+        if container.subtitles_codec().is_some() {
+            for language in &options.subtitles {
+                let Some(track) = video.best_caption_track(language) else {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("No {} subtitles to embed, skipping", language);
+
+                    continue;
+                };
+
+                let output = format!("{}.{}.vtt", file_name, track.language);
+                let path = self
+                    .download_caption_track(&track, output, CaptionFormat::WebVtt)
+                    .await?;
+
+                embedded
+                    .subtitles
+                    .push((path, base_language(&track), caption_name(&track)));
+            }
+        }
+
+        if options.thumbnail && matches!(container, Container::Mp4 | Container::Matroska) {
+            let path = self.output_dir.join(format!("{}.cover.jpg", file_name));
+            self.fetch_cover(video, &path).await?;
+
+            embedded.cover = Some(path);
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the thumbnail of the video, and converts it to JPEG, the format supported by all the containers.
+    async fn fetch_cover(&self, video: &Video, path: &Path) -> Result<()> {
+        let fetcher = Fetcher::new(&video.thumbnail)
+            .with_cancellation(self.cancellation.clone())
+            .with_retry(self.retry.clone())
+            .with_client(self.client.clone());
+
+        let thumbnail = tokio::select! {
+            thumbnail = fetcher.fetch_segment(&video.thumbnail, None) => thumbnail?,
+            _ = self.cancellation.cancelled() => return Err(Error::Cancelled),
+        };
+
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let cover = image::load_from_memory(&thumbnail)?;
+            image::DynamicImage::ImageRgb8(cover.to_rgb8())
+                .save_with_format(path, ImageFormat::Jpeg)?;
+
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Downloads the best audio format of the video, and extracts it to the codec of the options.
+    /// The extension of the output file is replaced by the one of the codec, e.g. 'my-audio.mp3'.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to extract the audio from.
+    /// * `output` - The name of the file to save the audio to.
+    /// * `options` - The codec and quality of the audio.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video has no audio format,
+    /// or if the audio could not be downloaded or extracted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::postprocess::{AudioCodec, AudioOptions, AudioQuality};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let options = AudioOptions {
+    ///     codec: AudioCodec::Mp3,
+    ///     quality: AudioQuality::Bitrate(192),
+    /// };
+    /// let audio_path = fetcher.extract_audio(&video, "my-audio.mp3", &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video, output))
+    )]
+    pub async fn extract_audio(
+        &self,
+        video: &Video,
+        output: impl AsRef<str>,
+        options: &AudioOptions,
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Extracting audio of {}", video.id);
+
+        let best_audio = video
+            .best_audio_format_by(self.audio_comparator.as_ref())
+            .ok_or(Error::Video("No audio format available".to_string()))?;
+
+        self.extract_audio_format(best_audio, output, options).await
+    }
+
+    /// Downloads an audio format, and extracts it to the codec of the options.
+    /// The audio is remuxed without re-encoding if the format is already in the requested codec,
+    /// and re-encoded with the quality of the options otherwise.
+    /// The extension of the output file is replaced by the one of the codec, e.g. 'my-audio.mp3'.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format to extract the audio from.
+    /// * `output` - The name of the file to save the audio to.
+    /// * `options` - The codec and quality of the audio.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the format has no audio,
+    /// or if the audio could not be downloaded or extracted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::postprocess::{AudioCodec, AudioOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let audio_format = video.worst_audio_format().unwrap();
+    /// let options = AudioOptions {
+    ///     codec: AudioCodec::Opus,
+    ///     ..Default::default()
+    /// };
+    /// let audio_path = fetcher.extract_audio_format(&audio_format, "my-audio", &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(format, output))
+    )]
+    pub async fn extract_audio_format(
+        &self,
+        format: &Format,
+        output: impl AsRef<str>,
+        options: &AudioOptions,
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Extracting audio of format {}", format.format_id);
+
+        let source_codec = format
+            .codec_info
+            .audio_codec
+            .as_deref()
+            .filter(|codec| *codec != "none")
+            .ok_or(Error::Video(format!(
+                "The format {} has no audio",
+                format.format_id
+            )))?;
+
+        let output_path = self
+            .output_dir
+            .join(output.as_ref())
+            .with_extension(options.codec.extension());
+        let file_name = file_system::try_stem(&output_path)?;
+
+        let source_name = format!("audio-{}.{}", file_name, source_extension(format));
+        let source_path = self.download_format(format, &source_name).await?;
+
+        let copy = AudioCodec::detect(source_codec) == Some(options.codec);
+        let result = async {
+            let args = extract_args(&source_path, &output_path, options, copy)?;
+
+            let executor = Executor::new(self.libraries.ffmpeg.clone(), EXTRACTION_TIMEOUT, args);
+
+            executor.execute_with_cancellation(&self.cancellation).await
+        }
+        .await;
+
+        // The downloaded stream is removed whether the audio could be extracted or not
+        file_system::remove_file(&source_path).await?;
+        if let Err(Error::Cancelled) = result {
+            file_system::remove_file(&output_path).await?;
+        }
+
+        result?;
+        Ok(output_path)
+    }
+}
+
+/// Builds the arguments of 'ffmpeg' to combine the audio and video files, with the embedded files and metadata.
+fn embed_args(
+    audio: &Path,
+    video: &Path,
+    output: &Path,
+    container: Container,
+    embedded: &Embedded,
+    metadata: Option<&Video>,
+) -> Result<Vec<String>> {
+    let path = |path: &Path| {
+        path.to_str()
+            .map(str::to_string)
+            .ok_or(Error::Path(format!("Invalid path {:?}", path)))
+    };
+
+    let mut inputs = vec![
+        String::from("-i"),
+        path(audio)?,
+        String::from("-i"),
+        path(video)?,
+    ];
+    let mut maps = utils::to_owned(vec!["-map", "1:v:0", "-map", "0:a:0"]);
+    let mut codecs = utils::to_owned(vec!["-c:v", "copy", "-c:a", "aac"]);
+
+    if let Some(subtitles_codec) = container.subtitles_codec() {
+        for (index, (subtitles, language, name)) in embedded.subtitles.iter().enumerate() {
+            inputs.extend([String::from("-i"), path(subtitles)?]);
+            maps.extend([String::from("-map"), format!("{}:0", index + 2)]);
+
+            codecs.extend([
+                format!("-metadata:s:s:{}", index),
+                format!("language={}", language),
+            ]);
+            if let Some(name) = name {
+                codecs.extend([
+                    format!("-metadata:s:s:{}", index),
+                    format!("title={}", name),
+                ]);
+            }
+        }
+
+        if !embedded.subtitles.is_empty() {
+            codecs.extend(utils::to_owned(vec!["-c:s", subtitles_codec]));
+        }
+    }
+
+    match (&embedded.cover, container) {
+        (Some(cover), Container::Mp4) => {
+            inputs.extend([String::from("-i"), path(cover)?]);
+            maps.extend([
+                String::from("-map"),
+                format!("{}:0", embedded.subtitles.len() + 2),
+            ]);
+            codecs.extend(utils::to_owned(vec!["-disposition:v:1", "attached_pic"]));
+        }
+        (Some(cover), Container::Matroska) => {
+            codecs.extend([
+                String::from("-attach"),
+                path(cover)?,
+                String::from("-metadata:s:t"),
+                String::from("mimetype=image/jpeg"),
+                String::from("-metadata:s:t"),
+                String::from("filename=cover.jpg"),
+            ]);
+        }
+        _ => {}
+    }
+
+    if let Some(video) = metadata {
+        for (key, value) in metadata_tags(video) {
+            codecs.extend([String::from("-metadata"), format!("{}={}", key, value)]);
+        }
+    }
+
+    let mut args = inputs;
+    args.extend(maps);
+    args.extend(codecs);
+    args.push(path(output)?);
+
+    Ok(args)
+}
+
+/// Returns the metadata tags of the video, as written by 'yt-dlp'.
+fn metadata_tags(video: &Video) -> Vec<(&'static str, String)> {
+    let url = video
+        .webpage_url
+        .clone()
+        .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}", video.id));
+
+    let mut tags = vec![
+        ("title", video.title.clone()),
+        ("artist", video.channel.clone()),
+        ("date", upload_date(video.upload_date)),
+        ("comment", url.clone()),
+        ("purl", url),
+    ];
+
+    if !video.description.is_empty() {
+        tags.push(("description", video.description.clone()));
+        tags.push(("synopsis", video.description.clone()));
+    }
+
+    tags
+}
+
+/// Formats a Unix timestamp as a date, e.g. '2009-10-25'.
+fn upload_date(timestamp: i64) -> String {
+    let (year, month, day) = date::civil_from_days(timestamp.div_euclid(86_400));
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Returns the language of a caption track, without its region or source language, e.g. 'en' for 'en-US'.
+fn base_language(track: &CaptionTrack) -> String {
+    let (language, _) = track
+        .language
+        .split_once('-')
+        .unwrap_or((track.language, ""));
+
+    language.to_string()
+}
+
+/// Returns the display name of a caption track, e.g. 'English'.
+fn caption_name(track: &CaptionTrack) -> Option<String> {
+    track
+        .best_caption()
+        .and_then(|caption| caption.name.clone())
+}
+
+/// Builds the arguments of 'ffmpeg' to extract the audio, copying the stream or re-encoding it.
+/// The video streams, such as cover arts, are dropped.
+fn extract_args(
+    source: &Path,
+    output: &Path,
+    options: &AudioOptions,
+    copy: bool,
+) -> Result<Vec<String>> {
+    let path = |path: &Path| {
+        path.to_str()
+            .map(str::to_string)
+            .ok_or(Error::Path(format!("Invalid path {:?}", path)))
+    };
+
+    let mut args = vec![String::from("-i"), path(source)?, String::from("-vn")];
+
+    if copy {
+        args.extend(utils::to_owned(vec!["-c:a", "copy"]));
+
+        // The AAC streams of HLS formats are in ADTS, which the M4A container does not support
+        if options.codec == AudioCodec::Aac {
+            args.extend(utils::to_owned(vec!["-bsf:a", "aac_adtstoasc"]));
+        }
+    } else {
+        args.extend([String::from("-c:a"), options.codec.encoder().to_string()]);
+        args.extend(quality_args(options));
+    }
+
+    args.push(path(output)?);
+
+    Ok(args)
+}
+
+/// Builds the arguments of 'ffmpeg' setting the quality of the re-encoded audio, like 'yt-dlp' does.
+fn quality_args(options: &AudioOptions) -> Vec<String> {
+    if matches!(options.codec, AudioCodec::Flac | AudioCodec::Wav) {
+        return Vec::new();
+    }
+
+    match options.quality {
+        AudioQuality::Bitrate(bitrate) => vec![String::from("-b:a"), format!("{}k", bitrate)],
+        AudioQuality::Vbr(quality) => {
+            // The quality is mapped from 0 (best) to 10 (worst) onto the VBR scale of the encoder
+            let quality = f64::from(quality.min(10)) / 10.0;
+            let scale = match options.codec {
+                AudioCodec::Mp3 => quality * 10.0,
+                AudioCodec::Aac => 4.0 - quality * 3.9,
+                _ => return Vec::new(),
+            };
+
+            vec![
+                String::from("-q:a"),
+                format!("{}", (scale * 100.0).round() / 100.0),
+            ]
+        }
+    }
+}
+
+/// Returns the extension of the file holding an audio format, e.g. 'webm' or 'm4a'.
+fn source_extension(format: &Format) -> &'static str {
+    match format.codec_info.audio_ext {
+        Extension::M4A => "m4a",
+        Extension::Mp4 => "mp4",
+        Extension::Webm => "webm",
+        _ => "audio",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn video() -> Video {
+        let video = json!({
+            "id": "dQw4w9WgXcQ",
+            "title": "Rick Astley - Never Gonna Give You Up",
+            "thumbnail": "https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg",
+            "description": "",
+            "availability": "public",
+            "timestamp": 1256453853,
+            "view_count": 0,
+            "channel": "Rick Astley",
+            "channel_id": "UCuAXFkgsw1L7xaCfnd5JJOw",
+            "channel_url": "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw",
+            "channel_follower_count": 0,
+            "formats": [],
+            "thumbnails": [],
+            "automatic_captions": {},
+            "tags": [],
+            "categories": [],
+            "age_limit": 0,
+            "playable_in_embed": true,
+            "extractor": "youtube",
+            "extractor_key": "Youtube",
+            "_version": {
+                "version": "2024.10.22",
+                "release_git_head": "",
+                "repository": "yt-dlp/yt-dlp",
+            },
+        });
+
+        serde_json::from_value(video).unwrap()
+    }
+
+    /// Returns the files to embed: the subtitles in English and French, the latter without name, and the cover.
+    fn embedded() -> Embedded {
+        Embedded {
+            subtitles: vec![
+                (
+                    PathBuf::from("out.en.vtt"),
+                    String::from("en"),
+                    Some(String::from("English")),
+                ),
+                (PathBuf::from("out.fr.vtt"), String::from("fr"), None),
+            ],
+            cover: Some(PathBuf::from("out.jpg")),
+        }
+    }
+
+    /// Builds the arguments to combine 'audio.m4a' and 'video.mp4' into the given output.
+    fn args(output: &str, embedded: &Embedded, metadata: Option<&Video>) -> Vec<String> {
+        let output = Path::new(output);
+        let container = Container::detect(output);
+
+        embed_args(
+            Path::new("audio.m4a"),
+            Path::new("video.mp4"),
+            output,
+            container,
+            embedded,
+            metadata,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn detect_containers() {
+        assert_eq!(Container::detect(Path::new("out.mp4")), Container::Mp4);
+        assert_eq!(Container::detect(Path::new("out.M4V")), Container::Mp4);
+        assert_eq!(Container::detect(Path::new("out.mkv")), Container::Matroska);
+        assert_eq!(Container::detect(Path::new("out.webm")), Container::WebM);
+        assert_eq!(Container::detect(Path::new("out.avi")), Container::Other);
+        assert_eq!(Container::detect(Path::new("out")), Container::Other);
+    }
+
+    #[test]
+    fn embed_args_without_embedded() {
+        assert_eq!(
+            args("out.mp4", &Embedded::default(), None),
+            vec![
+                "-i",
+                "audio.m4a",
+                "-i",
+                "video.mp4",
+                "-map",
+                "1:v:0",
+                "-map",
+                "0:a:0",
+                "-c:v",
+                "copy",
+                "-c:a",
+                "aac",
+                "out.mp4",
+            ]
+        );
+    }
+
+    #[test]
+    fn embed_args_mp4() {
+        assert_eq!(
+            args("out.mp4", &embedded(), None),
+            vec![
+                "-i",
+                "audio.m4a",
+                "-i",
+                "video.mp4",
+                "-i",
+                "out.en.vtt",
+                "-i",
+                "out.fr.vtt",
+                "-i",
+                "out.jpg",
+                "-map",
+                "1:v:0",
+                "-map",
+                "0:a:0",
+                "-map",
+                "2:0",
+                "-map",
+                "3:0",
+                "-map",
+                "4:0",
+                "-c:v",
+                "copy",
+                "-c:a",
+                "aac",
+                "-metadata:s:s:0",
+                "language=en",
+                "-metadata:s:s:0",
+                "title=English",
+                "-metadata:s:s:1",
+                "language=fr",
+                "-c:s",
+                "mov_text",
+                "-disposition:v:1",
+                "attached_pic",
+                "out.mp4",
+            ]
+        );
+    }
+
+    #[test]
+    fn embed_args_mkv() {
+        assert_eq!(
+            args("out.mkv", &embedded(), None),
+            vec![
+                "-i",
+                "audio.m4a",
+                "-i",
+                "video.mp4",
+                "-i",
+                "out.en.vtt",
+                "-i",
+                "out.fr.vtt",
+                "-map",
+                "1:v:0",
+                "-map",
+                "0:a:0",
+                "-map",
+                "2:0",
+                "-map",
+                "3:0",
+                "-c:v",
+                "copy",
+                "-c:a",
+                "aac",
+                "-metadata:s:s:0",
+                "language=en",
+                "-metadata:s:s:0",
+                "title=English",
+                "-metadata:s:s:1",
+                "language=fr",
+                "-c:s",
+                "ass",
+                "-attach",
+                "out.jpg",
+                "-metadata:s:t",
+                "mimetype=image/jpeg",
+                "-metadata:s:t",
+                "filename=cover.jpg",
+                "out.mkv",
+            ]
+        );
+    }
+
+    #[test]
+    fn embed_args_webm() {
+        // The cover art is not supported, and the metadata is written as tags
+        let video = video();
+
+        assert_eq!(
+            args("out.webm", &embedded(), Some(&video)),
+            vec![
+                "-i",
+                "audio.m4a",
+                "-i",
+                "video.mp4",
+                "-i",
+                "out.en.vtt",
+                "-i",
+                "out.fr.vtt",
+                "-map",
+                "1:v:0",
+                "-map",
+                "0:a:0",
+                "-map",
+                "2:0",
+                "-map",
+                "3:0",
+                "-c:v",
+                "copy",
+                "-c:a",
+                "aac",
+                "-metadata:s:s:0",
+                "language=en",
+                "-metadata:s:s:0",
+                "title=English",
+                "-metadata:s:s:1",
+                "language=fr",
+                "-c:s",
+                "webvtt",
+                "-metadata",
+                "title=Rick Astley - Never Gonna Give You Up",
+                "-metadata",
+                "artist=Rick Astley",
+                "-metadata",
+                "date=2009-10-25",
+                "-metadata",
+                "comment=https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "-metadata",
+                "purl=https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "out.webm",
+            ]
+        );
+    }
+
+    #[test]
+    fn embed_args_other_container() {
+        // The subtitles and the cover art are dropped
+        assert_eq!(
+            args("out.avi", &embedded(), None),
+            vec![
+                "-i",
+                "audio.m4a",
+                "-i",
+                "video.mp4",
+                "-map",
+                "1:v:0",
+                "-map",
+                "0:a:0",
+                "-c:v",
+                "copy",
+                "-c:a",
+                "aac",
+                "out.avi",
+            ]
+        );
+    }
+
+    #[test]
+    fn metadata_tags_of_video() {
+        let mut video = video();
+        video.webpage_url = Some(String::from("https://youtu.be/dQw4w9WgXcQ"));
+        video.description = String::from("The official video");
+
+        assert_eq!(
+            metadata_tags(&video),
+            vec![
+                (
+                    "title",
+                    String::from("Rick Astley - Never Gonna Give You Up")
+                ),
+                ("artist", String::from("Rick Astley")),
+                ("date", String::from("2009-10-25")),
+                ("comment", String::from("https://youtu.be/dQw4w9WgXcQ")),
+                ("purl", String::from("https://youtu.be/dQw4w9WgXcQ")),
+                ("description", String::from("The official video")),
+                ("synopsis", String::from("The official video")),
+            ]
+        );
+    }
+
+    #[test]
+    fn format_upload_dates() {
+        assert_eq!(upload_date(0), "1970-01-01");
+        assert_eq!(upload_date(1256453853), "2009-10-25");
+        assert_eq!(upload_date(951782400), "2000-02-29");
+        assert_eq!(upload_date(1704067199), "2023-12-31");
+        assert_eq!(upload_date(1704067200), "2024-01-01");
+        assert_eq!(upload_date(-1), "1969-12-31");
+    }
+
+    /// Builds the arguments to extract 'audio.webm' to 'out.<extension>' with the given codec and quality.
+    fn extraction(codec: AudioCodec, quality: AudioQuality, copy: bool) -> Vec<String> {
+        let output = format!("out.{}", codec.extension());
+        let options = AudioOptions { codec, quality };
+
+        extract_args(Path::new("audio.webm"), Path::new(&output), &options, copy).unwrap()
+    }
+
+    #[test]
+    fn detect_audio_codecs() {
+        assert_eq!(AudioCodec::detect("opus"), Some(AudioCodec::Opus));
+        assert_eq!(AudioCodec::detect("mp4a.40.2"), Some(AudioCodec::Aac));
+        assert_eq!(AudioCodec::detect("MP4A.40.5"), Some(AudioCodec::Aac));
+        assert_eq!(AudioCodec::detect("aac"), Some(AudioCodec::Aac));
+        assert_eq!(AudioCodec::detect("mp3"), Some(AudioCodec::Mp3));
+        assert_eq!(AudioCodec::detect("flac"), Some(AudioCodec::Flac));
+        assert_eq!(AudioCodec::detect("vorbis"), None);
+        assert_eq!(AudioCodec::detect("ac-3"), None);
+        assert_eq!(AudioCodec::detect("none"), None);
+    }
+
+    #[test]
+    fn extract_args_copy() {
+        assert_eq!(
+            extraction(AudioCodec::Opus, AudioQuality::Bitrate(128), true),
+            vec!["-i", "audio.webm", "-vn", "-c:a", "copy", "out.opus"]
+        );
+
+        // The ADTS streams of AAC are converted for the M4A container
+        assert_eq!(
+            extraction(AudioCodec::Aac, AudioQuality::default(), true),
+            vec![
+                "-i",
+                "audio.webm",
+                "-vn",
+                "-c:a",
+                "copy",
+                "-bsf:a",
+                "aac_adtstoasc",
+                "out.m4a",
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_args_encode() {
+        assert_eq!(
+            extraction(AudioCodec::Mp3, AudioQuality::Bitrate(192), false),
+            vec![
+                "-i",
+                "audio.webm",
+                "-vn",
+                "-c:a",
+                "libmp3lame",
+                "-b:a",
+                "192k",
+                "out.mp3",
+            ]
+        );
+        assert_eq!(
+            extraction(AudioCodec::Mp3, AudioQuality::Vbr(2), false),
+            vec![
+                "-i",
+                "audio.webm",
+                "-vn",
+                "-c:a",
+                "libmp3lame",
+                "-q:a",
+                "2",
+                "out.mp3",
+            ]
+        );
+        assert_eq!(
+            extraction(AudioCodec::Wav, AudioQuality::Bitrate(192), false),
+            vec!["-i", "audio.webm", "-vn", "-c:a", "pcm_s16le", "out.wav"]
+        );
+    }
+
+    #[test]
+    fn quality_args_of_codecs() {
+        let quality = |codec, quality| quality_args(&AudioOptions { codec, quality });
+
+        // The VBR quality is mapped onto the scale of each encoder
+        assert_eq!(
+            quality(AudioCodec::Mp3, AudioQuality::Vbr(0)),
+            vec!["-q:a", "0"]
+        );
+        assert_eq!(
+            quality(AudioCodec::Mp3, AudioQuality::Vbr(5)),
+            vec!["-q:a", "5"]
+        );
+        assert_eq!(
+            quality(AudioCodec::Mp3, AudioQuality::Vbr(42)),
+            vec!["-q:a", "10"]
+        );
+        assert_eq!(
+            quality(AudioCodec::Aac, AudioQuality::Vbr(0)),
+            vec!["-q:a", "4"]
+        );
+        assert_eq!(
+            quality(AudioCodec::Aac, AudioQuality::Vbr(5)),
+            vec!["-q:a", "2.05"]
+        );
+        assert_eq!(
+            quality(AudioCodec::Aac, AudioQuality::Vbr(10)),
+            vec!["-q:a", "0.1"]
+        );
+
+        // Opus has no VBR scale, only a bitrate
+        assert!(quality(AudioCodec::Opus, AudioQuality::Vbr(0)).is_empty());
+        assert_eq!(
+            quality(AudioCodec::Opus, AudioQuality::Bitrate(96)),
+            vec!["-b:a", "96k"]
+        );
+
+        // The lossless codecs have no quality
+        assert!(quality(AudioCodec::Flac, AudioQuality::Bitrate(320)).is_empty());
+        assert!(quality(AudioCodec::Wav, AudioQuality::Vbr(0)).is_empty());
+    }
+}
@@ -0,0 +1,415 @@
+//! Tools for reporting the progress of downloads.
+
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// The minimum interval between two notifications of the same download, to avoid flooding the observer.
+const NOTIFY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Represents the progress of a download.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Progress {
+    /// The current stage of the download.
+    pub stage: Stage,
+    /// The number of bytes downloaded so far, including the bytes of a resumed download.
+    pub downloaded_bytes: u64,
+    /// The total number of bytes to download, if known.
+    pub total_bytes: Option<u64>,
+    /// The download speed, in bytes per second.
+    pub speed: f64,
+    /// The estimated remaining time, if the total size is known.
+    pub eta: Option<Duration>,
+}
+
+/// The stages of a download.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Stage {
+    /// The files are being downloaded.
+    #[default]
+    Downloading,
+    /// The audio and video files are being combined with 'ffmpeg'.
+    Merging,
+    /// The download is complete.
+    Finished,
+}
+
+impl Progress {
+    /// Returns the downloaded fraction, between 0 and 1, if the total size is known.
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.total_bytes.filter(|total| *total > 0)?;
+        let fraction = self.downloaded_bytes as f64 / total as f64;
+
+        Some(fraction.min(1.0))
+    }
+}
+
+/// An observer notified with the progress of downloads.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::fetcher::progress::ProgressObserver;
+/// let observer = ProgressObserver::new(|progress| {
+///     println!(
+///         "{} / {:?} bytes, at {:.0} B/s, ETA {:?}",
+///         progress.downloaded_bytes, progress.total_bytes, progress.speed, progress.eta
+///     );
+/// });
+///
+/// // Or, with a watch channel
+/// let (observer, receiver) = ProgressObserver::channel();
+/// ```
+#[derive(Clone)]
+pub struct ProgressObserver {
+    /// The callback called with each progress update.
+    callback: Arc<dyn Fn(&Progress) + Send + Sync>,
+}
+
+impl ProgressObserver {
+    /// Creates a new observer, calling the given callback with each progress update.
+    /// The callback is called from the downloading task, so it should not block.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The callback called with each progress update.
+    pub fn new(callback: impl Fn(&Progress) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+
+    /// Creates a new observer, publishing each progress update to a watch channel.
+    /// The receiver always holds the latest progress.
+    pub fn channel() -> (Self, watch::Receiver<Progress>) {
+        let (sender, receiver) = watch::channel(Progress::default());
+        let observer = Self::new(move |progress| {
+            sender.send_replace(progress.clone());
+        });
+
+        (observer, receiver)
+    }
+
+    /// Notifies the observer with the given progress.
+    pub fn notify(&self, progress: &Progress) {
+        (self.callback)(progress);
+    }
+}
+
+impl Debug for ProgressObserver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressObserver")
+    }
+}
+
+/// Tracks the progress of a single download, computing its speed and remaining time.
+pub(crate) struct ProgressTracker {
+    /// The observer to notify, if any.
+    observer: Option<ProgressObserver>,
+    /// The number of bytes already downloaded when the tracking started.
+    initial_bytes: u64,
+    /// When the tracking started.
+    started: Instant,
+    /// When the observer was last notified.
+    notified: Option<Instant>,
+    /// The current progress.
+    progress: Progress,
+}
+
+impl ProgressTracker {
+    /// Starts tracking a download.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer to notify, if any.
+    /// * `downloaded_bytes` - The number of bytes already downloaded, e.g. when resuming.
+    /// * `total_bytes` - The total number of bytes to download, if known.
+    pub(crate) fn new(
+        observer: Option<ProgressObserver>,
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    ) -> Self {
+        let mut tracker = Self {
+            observer,
+            initial_bytes: downloaded_bytes,
+            started: Instant::now(),
+            notified: None,
+            progress: Progress {
+                downloaded_bytes,
+                total_bytes,
+                ..Progress::default()
+            },
+        };
+
+        tracker.notify(true);
+        tracker
+    }
+
+    /// Records newly downloaded bytes.
+    pub(crate) fn advance(&mut self, bytes: u64) {
+        self.progress.downloaded_bytes += bytes;
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            let downloaded = self.progress.downloaded_bytes - self.initial_bytes;
+            self.progress.speed = downloaded as f64 / elapsed;
+        }
+
+        self.progress.eta = eta(&self.progress);
+        self.notify(false);
+    }
+
+    /// Updates the total number of bytes to download, e.g. when it is estimated during the download.
+    pub(crate) fn set_total(&mut self, total_bytes: Option<u64>) {
+        self.progress.total_bytes = total_bytes;
+        self.progress.eta = eta(&self.progress);
+    }
+
+    /// Marks the download as finished.
+    pub(crate) fn finish(&mut self) {
+        self.progress.stage = Stage::Finished;
+        self.progress.eta = Some(Duration::ZERO);
+
+        if self.progress.total_bytes.is_none() {
+            self.progress.total_bytes = Some(self.progress.downloaded_bytes);
+        }
+
+        self.notify(true);
+    }
+
+    /// Notifies the observer, at most once per interval unless forced.
+    fn notify(&mut self, force: bool) {
+        let Some(observer) = &self.observer else {
+            return;
+        };
+
+        let now = Instant::now();
+        let due = self
+            .notified
+            .is_none_or(|notified| now.duration_since(notified) >= NOTIFY_INTERVAL);
+
+        if force || due {
+            observer.notify(&self.progress);
+            self.notified = Some(now);
+        }
+    }
+}
+
+/// Combines the progress of several downloads into a single view, e.g. the audio and video streams of a video.
+pub(crate) struct AggregatedProgress {
+    /// The observer to notify with the combined progress.
+    observer: ProgressObserver,
+    /// The latest progress of each download.
+    parts: Mutex<Vec<Progress>>,
+}
+
+impl AggregatedProgress {
+    /// Creates a new aggregated view, for the given expected sizes of each download.
+    ///
+    /// # Arguments
+    ///
+    /// * `observer` - The observer to notify with the combined progress.
+    /// * `sizes` - The expected size of each download, if known.
+    pub(crate) fn new(observer: ProgressObserver, sizes: &[Option<u64>]) -> Arc<Self> {
+        let parts = sizes
+            .iter()
+            .map(|size| Progress {
+                total_bytes: *size,
+                ..Progress::default()
+            })
+            .collect();
+
+        Arc::new(Self {
+            observer,
+            parts: Mutex::new(parts),
+        })
+    }
+
+    /// Returns an observer for the download at the given index.
+    pub(crate) fn part(self: &Arc<Self>, index: usize) -> ProgressObserver {
+        let aggregated = Arc::clone(self);
+
+        ProgressObserver::new(move |progress| {
+            let combined = {
+                let mut parts = aggregated
+                    .parts
+                    .lock()
+                    .unwrap_or_else(|error| error.into_inner());
+
+                if let Some(part) = parts.get_mut(index) {
+                    let size = part.total_bytes;
+
+                    *part = progress.clone();
+                    part.total_bytes = part.total_bytes.or(size);
+                }
+
+                combine(&parts)
+            };
+
+            aggregated.observer.notify(&combined);
+        })
+    }
+
+    /// Notifies the observer that all downloads reached the given stage.
+    pub(crate) fn stage(&self, stage: Stage) {
+        let mut combined = {
+            let parts = self.parts.lock().unwrap_or_else(|error| error.into_inner());
+
+            combine(&parts)
+        };
+
+        combined.stage = stage;
+        if stage != Stage::Downloading {
+            combined.speed = 0.0;
+            combined.eta = None;
+        }
+
+        self.observer.notify(&combined);
+    }
+}
+
+/// Sums the progress of several downloads.
+fn combine(parts: &[Progress]) -> Progress {
+    let total_bytes = parts
+        .iter()
+        .map(|part| part.total_bytes)
+        .sum::<Option<u64>>();
+
+    let mut progress = Progress {
+        stage: Stage::Downloading,
+        downloaded_bytes: parts.iter().map(|part| part.downloaded_bytes).sum(),
+        total_bytes,
+        speed: parts
+            .iter()
+            .filter(|part| part.stage == Stage::Downloading)
+            .map(|part| part.speed)
+            .sum(),
+        eta: None,
+    };
+
+    progress.eta = eta(&progress);
+    progress
+}
+
+/// Estimates the remaining time of a download, from its speed and total size.
+fn eta(progress: &Progress) -> Option<Duration> {
+    let total = progress.total_bytes?;
+    if progress.speed <= 0.0 {
+        return None;
+    }
+
+    let remaining = total.saturating_sub(progress.downloaded_bytes);
+    Some(Duration::from_secs_f64(remaining as f64 / progress.speed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(
+        stage: Stage,
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+        speed: f64,
+    ) -> Progress {
+        Progress {
+            stage,
+            downloaded_bytes,
+            total_bytes,
+            speed,
+            eta: None,
+        }
+    }
+
+    #[test]
+    fn tracker_throttles_notifications() {
+        let (observer, mut receiver) = ProgressObserver::channel();
+
+        // The start of the tracking is always notified, with the resumed bytes
+        let mut tracker = ProgressTracker::new(Some(observer), 100, Some(1000));
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(receiver.borrow_and_update().downloaded_bytes, 100);
+
+        // The updates in the following interval are only recorded
+        tracker.advance(200);
+        tracker.advance(200);
+        assert!(!receiver.has_changed().unwrap());
+
+        std::thread::sleep(NOTIFY_INTERVAL);
+        tracker.advance(100);
+        assert!(receiver.has_changed().unwrap());
+
+        let progress = receiver.borrow_and_update().clone();
+        assert_eq!(progress.stage, Stage::Downloading);
+        assert_eq!(progress.downloaded_bytes, 600);
+        assert_eq!(progress.fraction(), Some(0.6));
+        assert!(progress.speed > 0.0);
+        assert!(progress.eta.is_some());
+
+        // The end is notified right away, with the downloaded size as total if it was unknown
+        tracker.set_total(None);
+        tracker.advance(50);
+        tracker.finish();
+        assert!(receiver.has_changed().unwrap());
+
+        let progress = receiver.borrow_and_update().clone();
+        assert_eq!(progress.stage, Stage::Finished);
+        assert_eq!(progress.total_bytes, Some(650));
+        assert_eq!(progress.eta, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn aggregated_progress_sums_parts() {
+        let (observer, receiver) = ProgressObserver::channel();
+        let aggregated = AggregatedProgress::new(observer, &[Some(100), None]);
+        let (audio, video) = (aggregated.part(0), aggregated.part(1));
+
+        // The total is unknown as long as the size of a part is
+        audio.notify(&progress(Stage::Downloading, 50, None, 10.0));
+        assert_eq!(
+            *receiver.borrow(),
+            progress(Stage::Downloading, 50, None, 10.0)
+        );
+
+        // The expected size of a part is kept if its progress has none
+        video.notify(&progress(Stage::Downloading, 100, Some(300), 20.0));
+        let combined = receiver.borrow().clone();
+        assert_eq!(combined.downloaded_bytes, 150);
+        assert_eq!(combined.total_bytes, Some(400));
+        assert_eq!(combined.speed, 30.0);
+        assert_eq!(combined.eta, Some(Duration::from_secs_f64(250.0 / 30.0)));
+
+        // The finished parts don't count in the speed
+        audio.notify(&progress(Stage::Finished, 100, Some(100), 10.0));
+        let combined = receiver.borrow().clone();
+        assert_eq!(combined.stage, Stage::Downloading);
+        assert_eq!(combined.downloaded_bytes, 200);
+        assert_eq!(combined.speed, 20.0);
+        assert_eq!(combined.eta, Some(Duration::from_secs_f64(10.0)));
+    }
+
+    #[test]
+    fn aggregated_progress_stages() {
+        let (observer, receiver) = ProgressObserver::channel();
+        let aggregated = AggregatedProgress::new(observer, &[Some(100), Some(300)]);
+
+        aggregated
+            .part(0)
+            .notify(&progress(Stage::Finished, 100, Some(100), 10.0));
+        aggregated
+            .part(1)
+            .notify(&progress(Stage::Downloading, 300, Some(300), 20.0));
+
+        // The following stages have no speed nor remaining time
+        aggregated.stage(Stage::Merging);
+        assert_eq!(
+            *receiver.borrow(),
+            progress(Stage::Merging, 400, Some(400), 0.0)
+        );
+
+        aggregated.stage(Stage::Finished);
+        assert_eq!(receiver.borrow().stage, Stage::Finished);
+        assert_eq!(receiver.borrow().fraction(), Some(1.0));
+    }
+}
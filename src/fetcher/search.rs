@@ -0,0 +1,133 @@
+//! Tools for searching videos on YouTube.
+//!
+//! The searches are run flat with the 'ytsearch' prefixes of 'yt-dlp', returning the basic information of the videos,
+//! which can be resolved into complete videos with [`Youtube::resolve_entry`] or [`Youtube::fetch_video_infos`].
+
+use crate::error::{Error, Result};
+use crate::model::playlist::PlaylistEntry;
+use crate::Youtube;
+use serde::Deserialize;
+
+/// The order of the search results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SearchOrder {
+    /// The most relevant videos first, with the 'ytsearch' prefix.
+    #[default]
+    Relevance,
+    /// The newest videos first, with the 'ytsearchdate' prefix.
+    Date,
+}
+
+impl SearchOrder {
+    /// Returns the search prefix of 'yt-dlp' for this order.
+    fn prefix(&self) -> &'static str {
+        match self {
+            SearchOrder::Relevance => "ytsearch",
+            SearchOrder::Date => "ytsearchdate",
+        }
+    }
+}
+
+/// The results of a search.
+#[derive(Debug, Deserialize)]
+struct SearchResults {
+    entries: Vec<PlaylistEntry>,
+}
+
+impl Youtube {
+    /// Searches videos on YouTube, and returns the most relevant ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The text to search for.
+    /// * `limit` - The maximum number of results.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the search could not be run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let results = fetcher.search("never gonna give you up", 5).await?;
+    /// for result in &results {
+    ///     println!("{}: {:?} by {:?}", result.id, result.title, result.channel);
+    /// }
+    ///
+    /// let video = fetcher.resolve_entry(&results[0]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(query)))]
+    pub async fn search(&self, query: impl AsRef<str>, limit: usize) -> Result<Vec<PlaylistEntry>> {
+        self.search_with_order(query, limit, SearchOrder::Relevance)
+            .await
+    }
+
+    /// Searches videos on YouTube, and returns them in the given order.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The text to search for.
+    /// * `limit` - The maximum number of results.
+    /// * `order` - The order of the results.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the search could not be run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::search::SearchOrder;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let results = fetcher
+    ///     .search_with_order("rust programming", 10, SearchOrder::Date)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(query)))]
+    pub async fn search_with_order(
+        &self,
+        query: impl AsRef<str>,
+        limit: usize,
+        order: SearchOrder,
+    ) -> Result<Vec<PlaylistEntry>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Searching {} videos for {}", limit, query.as_ref());
+
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let url = format!("{}{}:{}", order.prefix(), limit, query.as_ref());
+        let output = self.fetch_flat_json(url, Vec::new()).await?;
+        let results: SearchResults = serde_json::from_str(&output).map_err(Error::Serde)?;
+
+        Ok(results.entries)
+    }
+}
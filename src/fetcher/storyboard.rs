@@ -0,0 +1,606 @@
+//! Tools for fetching storyboards from YouTube, and slicing them into preview frames.
+//!
+//! A storyboard is split into fragments, each of them being a sprite sheet: a grid of frames taken from the video.
+//! The frames are extracted from the sheets, and listed in a WebVTT thumbnails track, which players use for scrubbing previews.
+
+use crate::error::{Error, Result};
+use crate::fetcher::captions::write::format_timestamp;
+use crate::fetcher::Fetcher;
+use crate::model::format::Format;
+use crate::model::Video;
+use crate::utils::file_system;
+use crate::Youtube;
+use futures_util::StreamExt;
+use image::{DynamicImage, ImageFormat};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// The name of the WebVTT thumbnails track, written next to the frames.
+const TRACK_NAME: &str = "thumbnails.vtt";
+
+/// Represents a downloaded storyboard, sliced into frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Storyboard {
+    /// The frames of the storyboard, in chronological order.
+    pub frames: Vec<StoryboardFrame>,
+    /// The path of the WebVTT thumbnails track, referencing the frames.
+    pub track: PathBuf,
+}
+
+/// Represents a preview frame of a storyboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoryboardFrame {
+    /// The path of the frame image.
+    pub path: PathBuf,
+    /// The time the frame starts being displayed at, in seconds.
+    pub start: f64,
+    /// The time the frame stops being displayed at, in seconds.
+    pub end: f64,
+    /// The width of the frame, in pixels.
+    pub width: u32,
+    /// The height of the frame, in pixels.
+    pub height: u32,
+}
+
+/// The layout of the frames of a sprite sheet.
+#[derive(Debug, Clone)]
+struct SheetLayout {
+    /// The number of columns of the grid.
+    columns: u32,
+    /// The number of rows of the grid.
+    rows: u32,
+    /// The size of a frame, if known from the format.
+    frame_size: Option<(u32, u32)>,
+    /// The times of the frames of the sheet, in seconds.
+    timings: Vec<(f64, f64)>,
+    /// The index of the first frame of the sheet, among all the frames.
+    first_index: usize,
+}
+
+impl Youtube {
+    /// Downloads the storyboard of the video with the largest frames, and slices it into preview frames.
+    /// The frames and their WebVTT thumbnails track are written to the given directory, in the output directory.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to download the storyboard from.
+    /// * `output` - The name of the directory to save the frames to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video has no storyboard,
+    /// or if a sprite sheet could not be fetched, decoded, or written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let storyboard = fetcher.download_storyboard(&video, "storyboard").await?;
+    /// for frame in &storyboard.frames {
+    ///     println!("{:?} from {}s to {}s", frame.path, frame.start, frame.end);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video, output))
+    )]
+    pub async fn download_storyboard(
+        &self,
+        video: &Video,
+        output: impl AsRef<str>,
+    ) -> Result<Storyboard> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Downloading storyboard of {}", video.id);
+
+        let format = video.best_storyboard_format().ok_or(Error::Video(format!(
+            "The video {} has no storyboard",
+            video.id
+        )))?;
+
+        self.download_storyboard_format(format, output).await
+    }
+
+    /// Downloads the fragments of a storyboard format, and slices them into preview frames.
+    /// The frames are written as '00000.jpg', '00001.jpg', etc. to the given directory, in the output directory,
+    /// along with a 'thumbnails.vtt' track displaying each frame during its part of the video.
+    ///
+    /// The frames of a sheet are spread evenly over the duration of its fragment,
+    /// and the last sheet may contain fewer frames than its grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The storyboard format to download.
+    /// * `output` - The name of the directory to save the frames to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the format is not a storyboard,
+    /// or if a sprite sheet could not be fetched, decoded, or written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let format = video
+    ///     .formats
+    ///     .iter()
+    ///     .find(|format| format.format_id == "sb1")
+    ///     .unwrap();
+    /// let storyboard = fetcher.download_storyboard_format(format, "storyboard").await?;
+    /// println!("Thumbnails track: {:?}", storyboard.track);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(format, output))
+    )]
+    pub async fn download_storyboard_format(
+        &self,
+        format: &Format,
+        output: impl AsRef<str>,
+    ) -> Result<Storyboard> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Downloading storyboard format {}", format.format_id);
+
+        let info = &format.storyboard_info;
+        let fragments = info.fragments.as_deref().unwrap_or_default();
+        if fragments.is_empty() {
+            return Err(Error::Video(format!(
+                "The format {} is not a storyboard",
+                format.format_id
+            )));
+        }
+
+        let directory = self.output_dir.join(output.as_ref());
+        file_system::create_dir(&directory)?;
+
+        let layouts = sheet_layouts(format);
+        let fetcher = Fetcher::new(&fragments[0].url)
+            .with_headers(format.download_info.headers())
+            .with_cancellation(self.cancellation.clone())
+            .with_retry(self.retry.clone())
+            .with_client(self.client.clone());
+
+        let sheets = fragments
+            .iter()
+            .map(|fragment| fragment.url.clone())
+            .zip(layouts)
+            .map(|(url, layout)| {
+                let fetcher = &fetcher;
+                let directory = directory.clone();
+
+                async move {
+                    let sheet = fetcher.fetch_segment(&url, None).await?;
+
+                    tokio::task::spawn_blocking(move || slice_sheet(&sheet, &layout, directory))
+                        .await?
+                }
+            });
+
+        let mut sheets = futures_util::stream::iter(sheets).buffered(self.parallel_chunks.max(1));
+        let mut frames = Vec::new();
+
+        loop {
+            let sheet = tokio::select! {
+                sheet = sheets.next() => sheet,
+                _ = self.cancellation.cancelled() => return Err(Error::Cancelled),
+            };
+
+            match sheet {
+                Some(sheet) => frames.extend(sheet?),
+                None => break,
+            }
+        }
+
+        let track = directory.join(TRACK_NAME);
+        tokio::fs::write(&track, webvtt_track(&frames)).await?;
+
+        Ok(Storyboard { frames, track })
+    }
+}
+
+/// Computes the layout of each sheet of a storyboard format, spreading the frames over the fragments durations.
+///
+/// The interval between two frames is given by the frame rate of the format if known,
+/// or by the duration of the first fragment, which is full.
+fn sheet_layouts(format: &Format) -> Vec<SheetLayout> {
+    let info = &format.storyboard_info;
+    let fragments = info.fragments.as_deref().unwrap_or_default();
+
+    let dimension = |value: Option<i64>| value.and_then(|value| u32::try_from(value).ok());
+    let columns = dimension(info.columns).unwrap_or(1).max(1);
+    let rows = dimension(info.rows).unwrap_or(1).max(1);
+    let per_sheet = (columns * rows) as usize;
+
+    let frame_size = dimension(format.video_resolution.width)
+        .zip(dimension(format.video_resolution.height))
+        .filter(|(width, height)| *width > 0 && *height > 0);
+
+    let interval = format
+        .video_resolution
+        .fps
+        .filter(|fps| *fps > 0.0)
+        .map(|fps| 1.0 / fps)
+        .or_else(|| {
+            fragments
+                .first()
+                .map(|first| first.duration / per_sheet as f64)
+        })
+        .filter(|interval| *interval > 0.0);
+
+    let mut layouts = Vec::with_capacity(fragments.len());
+    let mut start = 0.0;
+    let mut first_index = 0;
+
+    for fragment in fragments {
+        let count = match interval {
+            // Rounding errors are tolerated, so a full fragment does not get an extra frame
+            Some(interval) => ((fragment.duration / interval) - 0.01).ceil() as usize,
+            None => per_sheet,
+        }
+        .clamp(1, per_sheet);
+
+        let frame_duration = fragment.duration / count as f64;
+        let timings = (0..count)
+            .map(|index| {
+                let frame_start = start + index as f64 * frame_duration;
+                (frame_start, frame_start + frame_duration)
+            })
+            .collect();
+
+        layouts.push(SheetLayout {
+            columns,
+            rows,
+            frame_size,
+            timings,
+            first_index,
+        });
+
+        start += fragment.duration;
+        first_index += count;
+    }
+
+    layouts
+}
+
+/// Decodes a sprite sheet, and writes its frames to the directory, from left to right and top to bottom.
+/// The frames outside of the sheet are skipped.
+fn slice_sheet(
+    sheet: &[u8],
+    layout: &SheetLayout,
+    directory: PathBuf,
+) -> Result<Vec<StoryboardFrame>> {
+    let sheet = image::load_from_memory(sheet)?;
+
+    let (width, height) = layout
+        .frame_size
+        .unwrap_or((sheet.width() / layout.columns, sheet.height() / layout.rows));
+    if width == 0 || height == 0 {
+        return Err(Error::Video(String::from("The storyboard sheet is empty")));
+    }
+
+    let mut frames = Vec::with_capacity(layout.timings.len());
+    for (index, (start, end)) in layout.timings.iter().enumerate() {
+        let x = (index as u32 % layout.columns) * width;
+        let y = (index as u32 / layout.columns) * height;
+        if x + width > sheet.width() || y + height > sheet.height() {
+            break;
+        }
+
+        let frame = sheet.crop_imm(x, y, width, height);
+        let path = directory.join(format!("{:05}.jpg", layout.first_index + index));
+        DynamicImage::ImageRgb8(frame.to_rgb8()).save_with_format(&path, ImageFormat::Jpeg)?;
+
+        frames.push(StoryboardFrame {
+            path,
+            start: *start,
+            end: *end,
+            width,
+            height,
+        });
+    }
+
+    Ok(frames)
+}
+
+/// Creates a WebVTT thumbnails track, with a cue per frame referencing its image by its name.
+fn webvtt_track(frames: &[StoryboardFrame]) -> String {
+    let mut track = String::from("WEBVTT\n");
+
+    for frame in frames {
+        let name = frame
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+
+        let _ = write!(
+            track,
+            "\n{} --> {}\n{}\n",
+            format_timestamp(frame.start, '.'),
+            format_timestamp(frame.end, '.'),
+            name
+        );
+    }
+
+    track
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+    use std::io::Cursor;
+
+    /// The colors of the frames of the test sprite sheets, from left to right and top to bottom.
+    const COLORS: [[u8; 3]; 6] = [
+        [255, 0, 0],
+        [0, 255, 0],
+        [0, 0, 255],
+        [255, 255, 0],
+        [0, 255, 255],
+        [255, 0, 255],
+    ];
+
+    /// Returns a storyboard format of 3 x 2 frames of 16 x 9 pixels, with the given frame rate and fragments durations.
+    fn format(fps: Option<f64>, durations: &[f64]) -> Format {
+        let fragments: Vec<_> = durations
+            .iter()
+            .enumerate()
+            .map(|(index, duration)| {
+                serde_json::json!({
+                    "url": format!("https://i.ytimg.com/sb/M{}.jpg", index),
+                    "duration": duration,
+                })
+            })
+            .collect();
+
+        let format = serde_json::json!({
+            "format": "sb0 - 16x9 (storyboard)",
+            "format_id": "sb0",
+            "format_note": "storyboard",
+            "ext": "mhtml",
+            "protocol": "mhtml",
+            "acodec": "none",
+            "vcodec": "none",
+            "url": "https://i.ytimg.com/sb/M$M.jpg",
+            "width": 16,
+            "height": 9,
+            "fps": fps,
+            "rows": 2,
+            "columns": 3,
+            "fragments": fragments,
+            "resolution": "16x9",
+            "http_headers": {
+                "User-Agent": "",
+                "Accept": "",
+                "Accept-Language": "",
+                "Sec-Fetch-Mode": "",
+            },
+        });
+
+        serde_json::from_value(format).unwrap()
+    }
+
+    /// Returns a sprite sheet of the given number of frames of 16 x 9 pixels on a grid of 3 columns, encoded in WebP.
+    fn sheet(frames: usize) -> Vec<u8> {
+        let rows = frames.div_ceil(3) as u32;
+        let sheet = RgbImage::from_fn(48, 9 * rows, |x, y| {
+            let index = (y / 9 * 3 + x / 16) as usize;
+            Rgb(COLORS.get(index).copied().unwrap_or([0, 0, 0]))
+        });
+
+        let mut encoded = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(sheet)
+            .write_to(&mut encoded, ImageFormat::WebP)
+            .unwrap();
+        encoded.into_inner()
+    }
+
+    /// Returns the timings of the frames of the layouts.
+    fn timings(layouts: &[SheetLayout]) -> Vec<Vec<(f64, f64)>> {
+        layouts
+            .iter()
+            .map(|layout| {
+                let round = |time: f64| (time * 1000.0).round() / 1000.0;
+                let timings = layout.timings.iter();
+                timings
+                    .map(|(start, end)| (round(*start), round(*end)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sheet_layouts_from_frame_rate() {
+        // A frame every 2 seconds: the full sheets last 12 seconds, and the last one holds 3 frames
+        let layouts = sheet_layouts(&format(Some(0.5), &[12.0, 12.0, 5.0]));
+
+        assert_eq!(layouts.len(), 3);
+        for layout in &layouts {
+            assert_eq!((layout.columns, layout.rows), (3, 2));
+            assert_eq!(layout.frame_size, Some((16, 9)));
+        }
+
+        let first_indexes: Vec<_> = layouts.iter().map(|layout| layout.first_index).collect();
+        assert_eq!(first_indexes, vec![0, 6, 12]);
+
+        let timings = timings(&layouts);
+        assert_eq!(
+            timings[0],
+            vec![
+                (0.0, 2.0),
+                (2.0, 4.0),
+                (4.0, 6.0),
+                (6.0, 8.0),
+                (8.0, 10.0),
+                (10.0, 12.0)
+            ]
+        );
+        assert_eq!(timings[1].first(), Some(&(12.0, 14.0)));
+        assert_eq!(timings[1].last(), Some(&(22.0, 24.0)));
+        assert_eq!(
+            timings[2],
+            vec![(24.0, 25.667), (25.667, 27.333), (27.333, 29.0)]
+        );
+    }
+
+    #[test]
+    fn sheet_layouts_from_fragment_duration() {
+        // Without frame rate, the interval is given by the first fragment, which is full
+        let layouts = sheet_layouts(&format(None, &[30.0, 30.0, 9.5]));
+
+        let counts: Vec<_> = layouts.iter().map(|layout| layout.timings.len()).collect();
+        assert_eq!(counts, vec![6, 6, 2]);
+
+        let timings = timings(&layouts);
+        assert_eq!(timings[0][1], (5.0, 10.0));
+        assert_eq!(timings[2], vec![(60.0, 64.75), (64.75, 69.5)]);
+
+        // A fragment slightly longer than a full sheet, because of rounding, does not get an extra frame
+        let layouts = sheet_layouts(&format(Some(0.5), &[12.001, 0.1]));
+        let counts: Vec<_> = layouts.iter().map(|layout| layout.timings.len()).collect();
+        assert_eq!(counts, vec![6, 1]);
+    }
+
+    #[test]
+    fn sheet_layouts_without_grid() {
+        let mut format = format(None, &[10.0]);
+        format.storyboard_info.rows = None;
+        format.storyboard_info.columns = Some(0);
+        format.video_resolution.width = Some(0);
+
+        let layouts = sheet_layouts(&format);
+        assert_eq!((layouts[0].columns, layouts[0].rows), (1, 1));
+        assert_eq!(layouts[0].frame_size, None);
+        assert_eq!(timings(&layouts), vec![vec![(0.0, 10.0)]]);
+
+        format.storyboard_info.fragments = None;
+        assert!(sheet_layouts(&format).is_empty());
+    }
+
+    #[test]
+    fn slice_partial_sheet() {
+        let directory = tempfile::tempdir().unwrap();
+        let layouts = sheet_layouts(&format(Some(0.5), &[12.0, 12.0, 9.0]));
+        let layout = &layouts[2];
+        assert_eq!(layout.timings.len(), 5);
+
+        let frames = slice_sheet(&sheet(5), layout, directory.path().to_path_buf()).unwrap();
+
+        let names: Vec<_> = frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "00012.jpg",
+                "00013.jpg",
+                "00014.jpg",
+                "00015.jpg",
+                "00016.jpg"
+            ]
+        );
+        assert_eq!((frames[0].start, frames[0].end), (24.0, 25.8));
+
+        for (frame, color) in frames.iter().zip(COLORS) {
+            assert_eq!((frame.width, frame.height), (16, 9));
+
+            let image = image::open(&frame.path).unwrap().to_rgb8();
+            assert_eq!(image.dimensions(), (16, 9));
+
+            // The frames are written in JPEG, so their colors are approximate
+            let center = image.get_pixel(8, 4).0;
+            for (channel, expected) in center.iter().zip(color) {
+                assert!(
+                    channel.abs_diff(expected) < 32,
+                    "{:?} != {:?}",
+                    center,
+                    color
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn slice_sheet_smaller_than_grid() {
+        let directory = tempfile::tempdir().unwrap();
+        let layouts = sheet_layouts(&format(Some(0.5), &[12.0]));
+
+        // The frames of the missing row are skipped
+        let frames = slice_sheet(&sheet(3), &layouts[0], directory.path().to_path_buf()).unwrap();
+        assert_eq!(frames.len(), 3);
+
+        // Without frame size, it is given by the grid
+        let mut layout = layouts[0].clone();
+        layout.frame_size = None;
+        let frames = slice_sheet(&sheet(6), &layout, directory.path().to_path_buf()).unwrap();
+        assert_eq!(frames.len(), 6);
+        assert_eq!((frames[5].width, frames[5].height), (16, 9));
+
+        layout.columns = 100;
+        assert!(slice_sheet(&sheet(6), &layout, directory.path().to_path_buf()).is_err());
+        assert!(slice_sheet(b"not an image", &layout, directory.path().to_path_buf()).is_err());
+    }
+
+    #[test]
+    fn write_webvtt_track() {
+        let frame = |name: &str, start, end| StoryboardFrame {
+            path: PathBuf::from("storyboard").join(name),
+            start,
+            end,
+            width: 16,
+            height: 9,
+        };
+
+        assert_eq!(
+            webvtt_track(&[
+                frame("00000.jpg", 0.0, 2.0),
+                frame("00001.jpg", 2.0, 3666.5)
+            ]),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.000\n00000.jpg\n\n\
+             00:00:02.000 --> 01:01:06.500\n00001.jpg\n"
+        );
+        assert_eq!(webvtt_track(&[]), "WEBVTT\n");
+    }
+}
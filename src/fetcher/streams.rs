@@ -2,16 +2,24 @@
 
 use crate::error::Error;
 use crate::executor::Executor;
+use crate::fetcher::manifest::live::RecordingOptions;
+use crate::fetcher::progress::{AggregatedProgress, ProgressObserver, Stage};
 use crate::fetcher::Fetcher;
-use crate::model::format::Format;
+use crate::model::format::{Format, Protocol};
+use crate::model::selector::{FormatSelector, Selection};
+use crate::model::sorting::FormatComparator;
 use crate::model::Video;
 use crate::utils::file_system;
 use crate::{utils, Youtube};
 use std::path::PathBuf;
 use std::time::Duration;
 
+/// The chunk size used when the format does not specify one, 10 MiB like 'yt-dlp'.
+const DEFAULT_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
 impl Youtube {
     /// Fetch the video information from the given URL.
+    /// The URL must point to a single video, see [`Youtube::stream_video_infos`] for playlists and channels.
     ///
     /// # Arguments
     ///
@@ -49,15 +57,22 @@ impl Youtube {
         let download_args = vec!["--no-progress", "--dump-json", &url];
 
         let mut final_args = self.args.clone();
+        final_args.append(&mut self.network_args());
         final_args.append(&mut utils::to_owned(download_args));
 
-        let executor = Executor {
-            executable_path: self.libraries.youtube.clone(),
-            timeout: Duration::from_secs(30),
-            args: final_args,
-        };
+        let executor = Executor::new(
+            self.libraries.youtube.clone(),
+            Duration::from_secs(30),
+            final_args,
+        )
+        .with_env(self.network_env()?);
 
-        let output = executor.execute().await?;
+        let output = self
+            .retry
+            .run_with_cancellation(&self.cancellation, || {
+                executor.execute_with_cancellation(&self.cancellation)
+            })
+            .await?;
         let video: Video = serde_json::from_str(&output.stdout).map_err(Error::Serde)?;
 
         Ok(video)
@@ -109,7 +124,65 @@ impl Youtube {
         self.download_video(&video, output).await
     }
 
+    /// Fetch the video from the given URL, download it (video with audio) picking its formats with the given comparators
+    /// instead of the ones of this instance, and returns its path.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download.
+    /// * `video_comparator` - The comparator ranking the video formats.
+    /// * `audio_comparator` - The comparator ranking the audio formats.
+    /// * `output` - The name of the file to save the video to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video could not be fetched or downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sorting::FormatSorter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video_sorter = FormatSorter::parse("res:1080,fps")?;
+    /// let audio_sorter = FormatSorter::audio();
+    /// let video_path = fetcher
+    ///     .download_video_from_url_by(url, &video_sorter, &audio_sorter, "my-video.mp4")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video_comparator, audio_comparator, output))
+    )]
+    pub async fn download_video_from_url_by(
+        &self,
+        url: String,
+        video_comparator: &dyn FormatComparator,
+        audio_comparator: &dyn FormatComparator,
+        output: impl AsRef<str>,
+    ) -> crate::error::Result<PathBuf> {
+        let video = self.fetch_video_infos(url).await?;
+
+        self.download_video_by(&video, video_comparator, audio_comparator, output)
+            .await
+    }
+
     /// Downloads the video (with its audio), and returns its path.
+    /// If a progress observer is set, it receives the combined progress of the audio and video streams, then the merging stage.
     /// Be careful, this function may take a while to execute.
     ///
     /// # Arguments
@@ -152,17 +225,202 @@ impl Youtube {
         #[cfg(feature = "tracing")]
         tracing::debug!("Downloading video {}", video.title);
 
+        self.download_video_by(
+            video,
+            self.video_comparator.as_ref(),
+            self.audio_comparator.as_ref(),
+            output,
+        )
+        .await
+    }
+
+    /// Downloads the video (with its audio), picking its formats with the given comparators instead of
+    /// the ones of this instance, and returns its path.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to download.
+    /// * `video_comparator` - The comparator ranking the video formats.
+    /// * `audio_comparator` - The comparator ranking the audio formats.
+    /// * `output` - The name of the file to save the video to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video could not be fetched or downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sorting::FormatSorter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let video_sorter = FormatSorter::parse("fps,res")?;
+    /// let audio_sorter = FormatSorter::audio();
+    /// let video_path = fetcher
+    ///     .download_video_by(&video, &video_sorter, &audio_sorter, "my-video.mp4")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video, output))
+    )]
+    pub async fn download_video_by(
+        &self,
+        video: &Video,
+        video_comparator: &dyn FormatComparator,
+        audio_comparator: &dyn FormatComparator,
+        output: impl AsRef<str>,
+    ) -> crate::error::Result<PathBuf> {
+        let best_audio = video
+            .best_audio_format_by(audio_comparator)
+            .ok_or(Error::Video("No audio format available".to_string()))?;
+        let best_video = video
+            .best_video_format_by(video_comparator)
+            .ok_or(Error::Video("No video format available".to_string()))?;
+
+        self.download_and_combine(video, best_video, best_audio, output)
+            .await
+    }
+
+    /// Downloads the formats picked by the given selector, and returns the path of the downloaded file.
+    /// A single format is downloaded as is, while a video format and an audio format are combined into a single file.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to download.
+    /// * `selector` - The selector picking the formats, e.g. parsed from 'bv[height<=1080]+ba/b'.
+    /// * `output` - The name of the file to save the video to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no format matches the selector, or if the formats could not be downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::selector::FormatSelector;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let selector = FormatSelector::parse("bv[height<=1080][ext=mp4]+ba/b")?;
+    /// let video_path = fetcher.download_selected(&video, &selector, "my-video.mp4").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video, output))
+    )]
+    pub async fn download_selected(
+        &self,
+        video: &Video,
+        selector: &FormatSelector,
+        output: impl AsRef<str>,
+    ) -> crate::error::Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Downloading video {} with selector {}",
+            video.title,
+            selector
+        );
+
+        let selection = video
+            .select_formats_by(
+                selector,
+                self.video_comparator.as_ref(),
+                self.audio_comparator.as_ref(),
+            )
+            .ok_or(Error::Video(format!(
+                "No format matches the selector '{}'",
+                selector
+            )))?;
+
+        match selection {
+            Selection::Single(format) => self.download_format(format, output).await,
+            Selection::Merge {
+                video: video_format,
+                audio: audio_format,
+            } => {
+                self.download_and_combine(video, video_format, audio_format, output)
+                    .await
+            }
+        }
+    }
+
+    /// Downloads the video format and the audio format, and combines them into a single file.
+    /// The subtitles, thumbnail and metadata of the video are embedded according to the embed options.
+    async fn download_and_combine(
+        &self,
+        video: &Video,
+        best_video: &Format,
+        best_audio: &Format,
+        output: impl AsRef<str>,
+    ) -> crate::error::Result<PathBuf> {
         let output_path = self.output_dir.join(output.as_ref());
         let file_name = file_system::try_without_extension(output_path.clone())?;
 
+        let sizes = [best_audio.file_info.size(), best_video.file_info.size()];
+        let progress = self
+            .progress
+            .clone()
+            .map(|observer| AggregatedProgress::new(observer, &sizes));
+
         let audio_name = format!("audio-{}.mp3", file_name.clone());
-        self.download_audio_stream(video, &audio_name).await?;
+        let audio_progress = progress.as_ref().map(|progress| progress.part(0));
+        self.download_format_with_progress(best_audio, &audio_name, audio_progress)
+            .await?;
 
         let video_name = format!("video-{}.mp4", file_name.clone());
-        self.download_video_stream(video, &video_name).await?;
+        let video_progress = progress.as_ref().map(|progress| progress.part(1));
+        self.download_format_with_progress(best_video, &video_name, video_progress)
+            .await?;
 
-        self.combine_audio_and_video(&audio_name, &video_name, output)
-            .await
+        if let Some(progress) = &progress {
+            progress.stage(Stage::Merging);
+        }
+
+        let path = if self.embed.is_empty() {
+            self.combine_audio_and_video(&audio_name, &video_name, output)
+                .await?
+        } else {
+            self.combine_and_embed(video, &audio_name, &video_name, output, &self.embed)
+                .await?
+        };
+
+        if let Some(progress) = &progress {
+            progress.stage(Stage::Finished);
+        }
+
+        Ok(path)
     }
 
     /// Fetch the video from the given URL, download it and returns its path.
@@ -211,6 +469,60 @@ impl Youtube {
         self.download_video_stream(&video, output).await
     }
 
+    /// Fetch the video from the given URL, download the video only picking its format with the given comparator
+    /// instead of the one of this instance, and returns its path.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download.
+    /// * `comparator` - The comparator ranking the video formats.
+    /// * `output` - The name of the file to save the video to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video could not be fetched or downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sorting::FormatSorter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let sorter = FormatSorter::parse("+size,res")?;
+    /// let video_path = fetcher
+    ///     .download_video_stream_from_url_by(url, &sorter, "my-video-stream.mp4")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(comparator, output))
+    )]
+    pub async fn download_video_stream_from_url_by(
+        &self,
+        url: String,
+        comparator: &dyn FormatComparator,
+        output: impl AsRef<str>,
+    ) -> crate::error::Result<PathBuf> {
+        let video = self.fetch_video_infos(url).await?;
+
+        self.download_video_stream_by(&video, comparator, output)
+            .await
+    }
+
     /// Download the video only, and returns its path.
     /// Be careful, this function may take a while to execute.
     ///
@@ -254,8 +566,62 @@ impl Youtube {
         #[cfg(feature = "tracing")]
         tracing::debug!("Downloading video stream {}", video.title);
 
+        self.download_video_stream_by(video, self.video_comparator.as_ref(), output)
+            .await
+    }
+
+    /// Download the video only, picking its format with the given comparator instead of the one of this instance,
+    /// and returns its path.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to download.
+    /// * `comparator` - The comparator ranking the video formats.
+    /// * `output` - The name of the file to save the video to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video could not be fetched or downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sorting::FormatSorter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let sorter = FormatSorter::parse("+size,res")?;
+    /// let video_path = fetcher
+    ///     .download_video_stream_by(&video, &sorter, "my-video-stream.mp4")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video, output))
+    )]
+    pub async fn download_video_stream_by(
+        &self,
+        video: &Video,
+        comparator: &dyn FormatComparator,
+        output: impl AsRef<str>,
+    ) -> crate::error::Result<PathBuf> {
         let best_video = video
-            .best_video_format()
+            .best_video_format_by(comparator)
             .ok_or(Error::Video("No video format available".to_string()))?;
 
         self.download_format(best_video, output).await
@@ -307,7 +673,62 @@ impl Youtube {
         self.download_audio_stream(&video, output).await
     }
 
+    /// Fetch the audio from the given URL, download it picking its format with the given comparator
+    /// instead of the one of this instance, and returns its path.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download.
+    /// * `comparator` - The comparator ranking the audio formats.
+    /// * `output` - The name of the file to save the audio to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video could not be fetched or downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sorting::FormatSorter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let sorter = FormatSorter::parse("acodec:opus,abr")?;
+    /// let audio_path = fetcher
+    ///     .download_audio_stream_from_url_by(url, &sorter, "my-audio-stream.webm")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(comparator, output))
+    )]
+    pub async fn download_audio_stream_from_url_by(
+        &self,
+        url: String,
+        comparator: &dyn FormatComparator,
+        output: impl AsRef<str>,
+    ) -> crate::error::Result<PathBuf> {
+        let video = self.fetch_video_infos(url).await?;
+
+        self.download_audio_stream_by(&video, comparator, output)
+            .await
+    }
+
     /// Downloads the audio, and returns its path.
+    /// The audio is written as is, whatever the extension of the output; see [`Youtube::extract_audio`] to convert it.
     /// Be careful, this function may take a while to execute.
     ///
     /// # Arguments
@@ -350,14 +771,71 @@ impl Youtube {
         #[cfg(feature = "tracing")]
         tracing::debug!("Downloading audio stream {}", video.title);
 
+        self.download_audio_stream_by(video, self.audio_comparator.as_ref(), output)
+            .await
+    }
+
+    /// Downloads the audio, picking its format with the given comparator instead of the one of this instance,
+    /// and returns its path.
+    /// The audio is written as is, whatever the extension of the output; see [`Youtube::extract_audio`] to convert it.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to download.
+    /// * `comparator` - The comparator ranking the audio formats.
+    /// * `output` - The name of the file to save the audio to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video could not be fetched or downloaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sorting::FormatSorter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let sorter = FormatSorter::parse("acodec:opus,abr")?;
+    /// let audio_path = fetcher
+    ///     .download_audio_stream_by(&video, &sorter, "my-audio-stream.opus")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video, output))
+    )]
+    pub async fn download_audio_stream_by(
+        &self,
+        video: &Video,
+        comparator: &dyn FormatComparator,
+        output: impl AsRef<str>,
+    ) -> crate::error::Result<PathBuf> {
         let best_audio = video
-            .best_audio_format()
+            .best_audio_format_by(comparator)
             .ok_or(Error::Video("No audio format available".to_string()))?;
 
         self.download_format(best_audio, output).await
     }
 
     /// Downloads a specific format, and returns its path.
+    /// The format HTTP headers and cookies are sent with the requests, like 'yt-dlp' does.
+    /// Formats using the HTTPS protocol are downloaded in parallel chunks, see [`Youtube::with_parallel_chunks`].
     /// Be careful, this function may take a while to execute.
     ///
     /// # Arguments
@@ -404,12 +882,180 @@ impl Youtube {
         #[cfg(feature = "tracing")]
         tracing::debug!("Downloading format {}", format.download_info.url);
 
+        self.download_format_with_progress(format, output, self.progress.clone())
+            .await
+    }
+
+    /// Downloads a specific format, reporting its progress to the given observer, and returns its path.
+    async fn download_format_with_progress(
+        &self,
+        format: &Format,
+        output: impl AsRef<str>,
+        progress: Option<ProgressObserver>,
+    ) -> crate::error::Result<PathBuf> {
         let path = self.output_dir.join(output.as_ref());
-        let url = format.download_info.url.clone();
+        let fetcher = self.format_fetcher(format, progress);
+
+        match format.protocol {
+            Protocol::Https => {
+                let chunk_size = format
+                    .download_info
+                    .downloader_options
+                    .as_ref()
+                    .and_then(|options| u64::try_from(options.http_chunk_size).ok())
+                    .filter(|chunk_size| *chunk_size > 0)
+                    .unwrap_or(DEFAULT_CHUNK_SIZE);
 
-        let fetcher = Fetcher::new(&url);
-        fetcher.fetch_asset(path.clone()).await?;
+                fetcher
+                    .fetch_asset_in_chunks(path.clone(), chunk_size, self.parallel_chunks)
+                    .await?;
+            }
+            Protocol::M3U8 | Protocol::M3U8Native => {
+                fetcher
+                    .fetch_hls(path.clone(), None, self.parallel_chunks)
+                    .await?;
+            }
+            Protocol::HttpDashSegments => {
+                fetcher
+                    .fetch_dash(path.clone(), Some(&format.format_id), self.parallel_chunks)
+                    .await?;
+            }
+            _ => fetcher.fetch_asset(path.clone()).await?,
+        }
 
         Ok(path)
     }
+
+    /// Records a live stream, following its HLS or DASH manifest as it grows, and returns the path of the recording.
+    ///
+    /// The format with the best video among the formats of the manifests is recorded, preferring the ones with audio.
+    /// The recording stops when the stream ends, after the maximum duration of the options,
+    /// or when their stop token is cancelled, and the recorded part of the stream is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The live stream to record.
+    /// * `output` - The name of the file to save the recording to.
+    /// * `options` - The options of the recording.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video is not live, if it has no manifest format,
+    /// or if the recording failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::manifest::live::RecordingOptions;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=jfKfPfyJRdk");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let options = RecordingOptions {
+    ///     duration: Some(Duration::from_secs(60)),
+    ///     from_start: false,
+    ///     ..RecordingOptions::default()
+    /// };
+    ///
+    /// let recording_path = fetcher.record_live_stream(&video, "my-live.ts", options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(video, output, options))
+    )]
+    pub async fn record_live_stream(
+        &self,
+        video: &Video,
+        output: impl AsRef<str>,
+        options: RecordingOptions,
+    ) -> crate::error::Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Recording live stream {}", video.id);
+
+        if !video.live_status.is_recordable() {
+            return Err(Error::Video(format!(
+                "The video {} is not a live stream",
+                video.id
+            )));
+        }
+
+        let format = video
+            .formats
+            .iter()
+            .filter(|format| {
+                matches!(
+                    format.protocol,
+                    Protocol::M3U8 | Protocol::M3U8Native | Protocol::HttpDashSegments
+                )
+            })
+            .max_by(|a, b| {
+                let muxed = |format: &Format| format.codec_info.audio_codec.is_some();
+
+                muxed(a)
+                    .cmp(&muxed(b))
+                    .then(self.video_comparator.compare(a, b))
+            })
+            .ok_or(Error::Video(format!(
+                "The live stream {} has no manifest format",
+                video.id
+            )))?;
+
+        let path = self.output_dir.join(output.as_ref());
+        let fetcher = self.format_fetcher(format, self.progress.clone());
+
+        match format.protocol {
+            Protocol::HttpDashSegments => {
+                fetcher
+                    .record_dash(
+                        path.clone(),
+                        Some(&format.format_id),
+                        self.parallel_chunks,
+                        &options,
+                    )
+                    .await?;
+            }
+            _ => {
+                fetcher
+                    .record_hls(path.clone(), None, self.parallel_chunks, &options)
+                    .await?
+            }
+        }
+
+        Ok(path)
+    }
+
+    /// Creates the fetcher of a format, with the headers, client, retry policy and cancellation token of this instance.
+    /// The URL of the DASH formats is the URL of their manifest.
+    fn format_fetcher(&self, format: &Format, progress: Option<ProgressObserver>) -> Fetcher {
+        let url = match format.protocol {
+            Protocol::HttpDashSegments => format
+                .download_info
+                .manifest_url
+                .as_ref()
+                .unwrap_or(&format.download_info.url),
+            _ => &format.download_info.url,
+        };
+
+        Fetcher::new(url)
+            .with_headers(format.download_info.headers())
+            .with_cancellation(self.cancellation.clone())
+            .with_progress(progress)
+            .with_size_hint(format.file_info.size())
+            .with_retry(self.retry.clone())
+            .with_client(self.client.clone())
+    }
 }
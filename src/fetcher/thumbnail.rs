@@ -97,7 +97,11 @@ impl Youtube {
 
         let path = self.output_dir.join(file_name.as_ref());
 
-        let fetcher = Fetcher::new(&video.thumbnail);
+        let fetcher = Fetcher::new(&video.thumbnail)
+            .with_cancellation(self.cancellation.clone())
+            .with_progress(self.progress.clone())
+            .with_retry(self.retry.clone())
+            .with_client(self.client.clone());
         fetcher.fetch_asset(path.clone()).await?;
 
         Ok(path)
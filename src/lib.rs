@@ -2,11 +2,19 @@
 
 use crate::error::{Error, Result};
 use crate::executor::Executor;
+use crate::fetcher::client::{ClientOptions, ProxyConfig};
+use crate::fetcher::cookies::{CookieFile, CookieJar, CookieSource};
 use crate::fetcher::deps::{Libraries, LibraryInstaller};
+use crate::fetcher::postprocess::EmbedOptions;
+use crate::fetcher::progress::ProgressObserver;
+use crate::model::sorting::{FormatComparator, FormatSorter};
 use crate::utils::file_system;
+use crate::utils::retry::RetryPolicy;
 use derive_more::Display;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 pub mod error;
 pub mod executor;
@@ -58,6 +66,30 @@ pub struct Youtube {
     pub output_dir: PathBuf,
     /// The arguments to pass to 'yt-dlp'.
     pub args: Vec<String>,
+    /// The token to cancel the running operations with.
+    /// On Unix, the processes run in their own process group, so they do not receive the Ctrl+C of the terminal,
+    /// which must be handled by cancelling this token.
+    pub cancellation: CancellationToken,
+    /// The maximum number of chunks downloaded at the same time, for each format.
+    pub parallel_chunks: usize,
+    /// The maximum number of videos downloaded or resolved at the same time, for playlists.
+    pub concurrent_downloads: usize,
+    /// The observer notified with the progress of the downloads.
+    pub progress: Option<ProgressObserver>,
+    /// The policy to retry failed 'yt-dlp' invocations and downloads with.
+    pub retry: RetryPolicy,
+    /// The private copy of the cookies passed to 'yt-dlp', if any.
+    pub cookies_file: Option<CookieFile>,
+    /// The options the HTTP client was built with.
+    pub client_options: ClientOptions,
+    /// The HTTP client used to download the formats and thumbnails.
+    pub client: reqwest::Client,
+    /// The comparator ranking the video formats, when picking the best one to download.
+    pub video_comparator: Arc<dyn FormatComparator>,
+    /// The comparator ranking the audio formats, when picking the best one to download.
+    pub audio_comparator: Arc<dyn FormatComparator>,
+    /// What to embed into the videos when their audio and video are combined.
+    pub embed: EmbedOptions,
 }
 
 impl Youtube {
@@ -71,7 +103,8 @@ impl Youtube {
     ///
     /// # Errors
     ///
-    /// This function will return an error if the parent directories of the executables and output directory could not be created.
+    /// This function will return an error if the parent directories of the executables and output directory could not be created,
+    /// or if the HTTP client could not be built.
     ///
     /// # Examples
     ///
@@ -101,12 +134,24 @@ impl Youtube {
         tracing::debug!("Creating a new video fetcher");
 
         file_system::create_parent_dir(&output_dir)?;
+        let client_options = ClientOptions::default();
 
         Ok(Self {
             libraries,
 
             output_dir: output_dir.as_ref().to_path_buf(),
             args: Vec::new(),
+            cancellation: CancellationToken::new(),
+            parallel_chunks: 4,
+            concurrent_downloads: 1,
+            progress: None,
+            retry: RetryPolicy::default(),
+            cookies_file: None,
+            client: client_options.build()?,
+            client_options,
+            video_comparator: Arc::new(FormatSorter::video()),
+            audio_comparator: Arc::new(FormatSorter::audio()),
+            embed: EmbedOptions::default(),
         })
     }
 
@@ -141,16 +186,67 @@ impl Youtube {
     pub async fn with_new_binaries(
         executables_dir: impl AsRef<Path> + std::fmt::Debug,
         output_dir: impl AsRef<Path> + std::fmt::Debug,
+    ) -> Result<Self> {
+        Self::with_new_binaries_and_client_options(
+            executables_dir,
+            output_dir,
+            ClientOptions::default(),
+        )
+        .await
+    }
+
+    /// Creates a new YouTube fetcher with the given HTTP client options, and installs the yt-dlp and ffmpeg binaries.
+    /// The binaries are downloaded with these options, e.g. through their proxy, which is then used by the fetcher.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `executables_dir` - The directory where the binaries will be installed.
+    /// * `output_dir` - The directory where the video will be downloaded.
+    /// * `client_options` - The options to build the HTTP client with.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the HTTP client could not be built, or if the executables could not be installed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::client::{ClientOptions, ProxyConfig};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let executables_dir = PathBuf::from("libs");
+    /// let output_dir = PathBuf::from("output");
+    ///
+    /// let options = ClientOptions {
+    ///     proxy: Some(ProxyConfig::parse("socks5://proxy.local:1080")?),
+    ///     ..ClientOptions::default()
+    /// };
+    /// let fetcher = Youtube::with_new_binaries_and_client_options(executables_dir, output_dir, options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug"))]
+    pub async fn with_new_binaries_and_client_options(
+        executables_dir: impl AsRef<Path> + std::fmt::Debug,
+        output_dir: impl AsRef<Path> + std::fmt::Debug,
+        client_options: ClientOptions,
     ) -> Result<Self> {
         #[cfg(feature = "tracing")]
         tracing::debug!("Creating a new video fetcher with binaries installation");
 
-        let installer = LibraryInstaller::new(executables_dir.as_ref().to_path_buf());
+        let installer = LibraryInstaller::new(executables_dir.as_ref().to_path_buf())
+            .with_client(client_options.build()?);
         let youtube = installer.install_youtube(None).await?;
         let ffmpeg = installer.install_ffmpeg(None).await?;
 
         let libraries = Libraries::new(youtube, ffmpeg);
-        Self::new(libraries, output_dir)
+        let mut fetcher = Self::new(libraries, output_dir)?;
+        fetcher.with_client_options(client_options)?;
+
+        Ok(fetcher)
     }
 
     /// Sets the arguments to pass to yt-dlp.
@@ -214,6 +310,511 @@ impl Youtube {
         self
     }
 
+    /// Sets the token to cancel the running operations with.
+    /// When cancelled, the running 'yt-dlp' and 'ffmpeg' processes are killed, the downloads are aborted,
+    /// and the partially written files are removed. The operations then return [`Error::Cancelled`].
+    ///
+    /// A cancelled token stays cancelled, so a new token must be set to run new operations.
+    ///
+    /// On Unix, the 'yt-dlp' and 'ffmpeg' processes run in their own process group, so they can be killed along with
+    /// their children. As a consequence, they do not receive the SIGINT sent by the terminal on Ctrl+C:
+    /// to stop them with the program, cancel the token on Ctrl+C, e.g. with 'tokio::signal::ctrl_c'.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation` - The token to cancel the running operations with.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use tokio_util::sync::CancellationToken;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let cancellation = CancellationToken::new();
+    /// fetcher.with_cancellation(cancellation.clone());
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let download = tokio::spawn(async move {
+    ///     fetcher.download_video_from_url(url, "my-video.mp4").await
+    /// });
+    ///
+    /// cancellation.cancel();
+    /// assert!(download.await?.is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cancellation(&mut self, cancellation: CancellationToken) -> &mut Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Sets the maximum number of chunks downloaded at the same time, for each format.
+    /// Formats using the HTTPS protocol are split in chunks of their 'http_chunk_size', and fetched concurrently.
+    /// The default is 4 chunks, a value of 1 downloads the chunks one after the other.
+    ///
+    /// # Arguments
+    ///
+    /// * `parallel_chunks` - The maximum number of chunks downloaded at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// fetcher.with_parallel_chunks(8);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_parallel_chunks(&mut self, parallel_chunks: usize) -> &mut Self {
+        self.parallel_chunks = parallel_chunks;
+        self
+    }
+
+    /// Sets the maximum number of videos downloaded or resolved at the same time, for playlists.
+    /// The default is 1 video, i.e. the videos are downloaded one after the other.
+    /// The progress of the concurrent downloads is reported to the same observer, see [`Youtube::with_progress`],
+    /// so their updates are interleaved and can't be told apart: raise it only if the progress is not observed.
+    ///
+    /// # Arguments
+    ///
+    /// * `concurrent_downloads` - The maximum number of videos downloaded at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// fetcher.with_concurrent_downloads(4);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_concurrent_downloads(&mut self, concurrent_downloads: usize) -> &mut Self {
+        self.concurrent_downloads = concurrent_downloads;
+        self
+    }
+
+    /// Sets the observer notified with the progress of the downloads.
+    /// It reports the downloaded bytes, total size, speed and remaining time of formats and thumbnails.
+    /// When downloading a video with its audio, the progress of both streams is combined, followed by the merging stage.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - The observer notified with the progress of the downloads.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::progress::ProgressObserver;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// fetcher.with_progress(ProgressObserver::new(|progress| {
+    ///     if let Some(fraction) = progress.fraction() {
+    ///         println!("{:?}: {:.1}%, ETA {:?}", progress.stage, fraction * 100.0, progress.eta);
+    ///     }
+    /// }));
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// fetcher.download_video_from_url(url, "my-video.mp4").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_progress(&mut self, progress: ProgressObserver) -> &mut Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Sets the policy to retry failed 'yt-dlp' invocations and downloads with.
+    /// By default, transient failures (network errors, server errors, extractor failures) are retried twice,
+    /// with an exponential backoff. Interrupted downloads are resumed from where they stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The policy to retry failed operations with.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::utils::retry::RetryPolicy;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// fetcher.with_retry(RetryPolicy {
+    ///     max_attempts: 5,
+    ///     initial_backoff: Duration::from_millis(500),
+    ///     ..RetryPolicy::default()
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_retry(&mut self, retry: RetryPolicy) -> &mut Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the comparator ranking the video formats, used by all the downloads picking the best video format,
+    /// unless another comparator is given, e.g. to [`Youtube::download_video_by`].
+    /// By default, the formats are sorted by "quality", "video resolution", "fps", "video bitrate".
+    ///
+    /// # Arguments
+    ///
+    /// * `comparator` - The comparator ranking the video formats, e.g. a [`FormatSorter`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sorting::FormatSorter;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let sorter = FormatSorter::parse("+hdr,vcodec:avc1,res,fps")?;
+    /// fetcher.with_video_comparator(sorter);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_video_comparator(
+        &mut self,
+        comparator: impl FormatComparator + 'static,
+    ) -> &mut Self {
+        self.video_comparator = Arc::new(comparator);
+        self
+    }
+
+    /// Sets the comparator ranking the audio formats, used by all the downloads picking the best audio format,
+    /// unless another comparator is given, e.g. to [`Youtube::download_audio_stream_by`].
+    /// By default, the formats are sorted by "quality", "audio bitrate", "sample rate", "audio channels".
+    ///
+    /// # Arguments
+    ///
+    /// * `comparator` - The comparator ranking the audio formats, e.g. a [`FormatSorter`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sorting::{FormatSorter, SortKey};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let sorter = FormatSorter::new()
+    ///     .by(SortKey::PreferLanguage("en".to_string()))
+    ///     .then(FormatSorter::audio());
+    /// fetcher.with_audio_comparator(sorter);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_audio_comparator(
+        &mut self,
+        comparator: impl FormatComparator + 'static,
+    ) -> &mut Self {
+        self.audio_comparator = Arc::new(comparator);
+        self
+    }
+
+    /// Sets what to embed into the downloaded videos when their audio and video are combined:
+    /// subtitles, thumbnail and metadata. By default, nothing is embedded.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options of what to embed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::postprocess::EmbedOptions;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// fetcher.with_embed(EmbedOptions {
+    ///     subtitles: vec![String::from("en")],
+    ///     thumbnail: true,
+    ///     metadata: true,
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_embed(&mut self, options: EmbedOptions) -> &mut Self {
+        self.embed = options;
+        self
+    }
+
+    /// Sets the HTTP client used to download the formats and thumbnails.
+    /// The client is used as is, so it is not affected by [`Youtube::with_proxy`].
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The HTTP client to use, e.g. built from [`ClientOptions`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let client = reqwest::Client::new();
+    /// fetcher.with_client(client);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_client(&mut self, client: reqwest::Client) -> &mut Self {
+        self.client = client;
+        self
+    }
+
+    /// Builds the HTTP client used to download the formats and thumbnails from the given options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options to build the HTTP client with.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the HTTP client could not be built.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use yt_dlp::fetcher::client::ClientOptions;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// fetcher.with_client_options(ClientOptions {
+    ///     read_timeout: Some(Duration::from_secs(20)),
+    ///     ..ClientOptions::default()
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_client_options(&mut self, options: ClientOptions) -> Result<&mut Self> {
+        self.client = options.build()?;
+        self.client_options = options;
+        Ok(self)
+    }
+
+    /// Sets the proxy to send all the requests through, for 'yt-dlp' and for the native downloads.
+    /// The HTTP client is rebuilt from the current options with this proxy.
+    ///
+    /// # Arguments
+    ///
+    /// * `proxy` - The proxy to use.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the proxy is invalid, or if the HTTP client could not be built.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::client::{ProxyConfig, ProxyScheme};
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let proxy = ProxyConfig::new(ProxyScheme::Socks5, "proxy.local", 1080)
+    ///     .with_credentials("user", "password");
+    /// fetcher.with_proxy(proxy)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_proxy(&mut self, proxy: ProxyConfig) -> Result<&mut Self> {
+        let options = ClientOptions {
+            proxy: Some(proxy),
+            ..self.client_options.clone()
+        };
+
+        self.with_client_options(options)
+    }
+
+    /// Sets the cookies to authenticate the requests with, e.g. to access members-only or age-restricted videos.
+    /// The cookies are passed to 'yt-dlp', and loaded into the HTTP client, which is rebuilt from the current options.
+    ///
+    /// The cookies are written to a private file in the temporary directory, see [`CookieFile`],
+    /// so the cookie file given is never rewritten by 'yt-dlp'.
+    /// The previous private file, if any, is removed once no clone of the fetcher uses it anymore.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The cookie file or jar to use.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cookie file could not be read or is invalid,
+    /// if the jar could not be written, or if the HTTP client could not be built.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::cookies::CookieSource;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let cookies = PathBuf::from("cookies.txt");
+    /// fetcher.with_cookies(CookieSource::File(cookies)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn with_cookies(&mut self, source: CookieSource) -> Result<&mut Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Setting the cookies from {}", source);
+
+        let jar = match source {
+            CookieSource::File(path) => CookieJar::load(&path).await?,
+            CookieSource::Jar(jar) => jar,
+        };
+        let file = CookieFile::write(&jar).await?;
+
+        let options = ClientOptions {
+            cookies: Some(jar),
+            ..self.client_options.clone()
+        };
+
+        self.with_client_options(options)?;
+        self.cookies_file = Some(file);
+
+        Ok(self)
+    }
+
+    /// Returns the arguments passed to every 'yt-dlp' invocation reaching the network, e.g. the cookies.
+    pub(crate) fn network_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if let Some(cookies) = &self.cookies_file {
+            args.push(String::from("--cookies"));
+            args.push(cookies.path().display().to_string());
+        }
+
+        args
+    }
+
+    /// Returns the environment variables of every 'yt-dlp' invocation reaching the network, e.g. the proxy.
+    /// The proxy is not passed as an argument, since its credentials would be visible to the other users of the system.
+    pub(crate) fn network_env(&self) -> Result<Vec<(String, String)>> {
+        let Some(proxy) = &self.client_options.proxy else {
+            return Ok(Vec::new());
+        };
+
+        // The lowercase variables take precedence over the uppercase ones in 'yt-dlp'
+        let url = proxy.to_url()?;
+        let env = ["http_proxy", "https_proxy", "all_proxy"]
+            .into_iter()
+            .map(|key| (key.to_string(), url.clone()))
+            .collect();
+
+        Ok(env)
+    }
+
     /// Updates the yt-dlp executable.
     /// Be careful, this function may take a while to execute.
     ///
@@ -245,15 +846,21 @@ impl Youtube {
         #[cfg(feature = "tracing")]
         tracing::debug!("Updating the downloader");
 
-        let args = vec!["--update"];
+        let mut args = self.network_args();
+        args.push(String::from("--update"));
 
-        let executor = Executor {
-            executable_path: self.libraries.youtube.clone(),
-            timeout: Duration::from_secs(30),
-            args: utils::to_owned(args),
-        };
+        let executor = Executor::new(
+            self.libraries.youtube.clone(),
+            Duration::from_secs(30),
+            args,
+        )
+        .with_env(self.network_env()?);
 
-        executor.execute().await?;
+        self.retry
+            .run_with_cancellation(&self.cancellation, || {
+                executor.execute_with_cancellation(&self.cancellation)
+            })
+            .await?;
         Ok(())
     }
 
@@ -331,13 +938,19 @@ impl Youtube {
             "-i", audio, "-i", video, "-c:v", "copy", "-c:a", "aac", output,
         ];
 
-        let executor = Executor {
-            executable_path: self.libraries.ffmpeg.clone(),
-            timeout: Duration::from_secs(30),
-            args: utils::to_owned(args),
-        };
+        let executor = Executor::new(
+            self.libraries.ffmpeg.clone(),
+            Duration::from_secs(30),
+            utils::to_owned(args),
+        );
+
+        let result = executor.execute_with_cancellation(&self.cancellation).await;
+
+        if let Err(Error::Cancelled) = result {
+            file_system::remove_file(&output_path).await?;
+        }
 
-        executor.execute().await?;
+        result?;
         Ok(output_path)
     }
 }
@@ -1,10 +1,13 @@
 //! Captions-related models.
+//!
+//! The caption tracks listed by 'yt-dlp' come in several formats, which are parsed into cues.
 
 use serde::{Deserialize, Serialize};
 
-/// Represents an automatic caption of a YouTube video.
+/// Represents a caption file of a YouTube video, either uploaded by its author or automatically generated.
+/// The same track is usually available in several formats, each of them being a caption file.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct AutomaticCaption {
+pub struct Caption {
     /// The extension of the caption file.
     #[serde(rename = "ext")]
     pub extension: Extension,
@@ -14,8 +17,74 @@ pub struct AutomaticCaption {
     pub name: Option<String>,
 }
 
-/// The available extensions for automatic caption files.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The former name of [`Caption`], when only the automatic captions were modeled.
+pub type AutomaticCaption = Caption;
+
+/// The origin of a caption track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionKind {
+    /// The track was uploaded by the author of the video, listed in the subtitles of the video.
+    Manual,
+    /// The track was generated by YouTube, listed in the automatic captions of the video.
+    Automatic,
+}
+
+/// Represents a caption track of a video in a language, available in several formats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptionTrack<'a> {
+    /// The language code of the track, e.g. 'en', 'en-US', 'en-orig' or 'en-fr'.
+    pub language: &'a str,
+    /// The origin of the track.
+    pub kind: CaptionKind,
+    /// The files of the track, one per format.
+    pub captions: &'a [Caption],
+}
+
+impl<'a> CaptionTrack<'a> {
+    /// Returns the file of the track in the richest format, which keeps the styling and the exact timings.
+    /// The files in an unknown format, e.g. the live chat replay listed as a subtitles track, are ignored.
+    pub fn best_caption(&self) -> Option<&'a Caption> {
+        self.captions
+            .iter()
+            .filter(|caption| caption.extension != Extension::Unknown)
+            .max_by_key(|caption| caption.extension.preference())
+    }
+
+    /// Checks if the track is an automatic translation of a track in another language,
+    /// i.e. an automatic track whose code is '{language}-{source language}', like 'en-fr'.
+    pub fn is_translated(&self) -> bool {
+        self.source_language().is_some()
+    }
+
+    /// Returns the language the track was translated from, e.g. 'fr' for 'en-fr', if it is an automatic translation.
+    /// Only a bare lowercase language code is a source language, so the scripts and regions of the tracks,
+    /// e.g. 'zh-Hans', 'pt-BR' or 'en-GB', are not mistaken for one.
+    pub fn source_language(&self) -> Option<&'a str> {
+        if self.kind != CaptionKind::Automatic {
+            return None;
+        }
+
+        let (_, source) = self.language.rsplit_once('-')?;
+        let is_code =
+            (2..=3).contains(&source.len()) && source.bytes().all(|byte| byte.is_ascii_lowercase());
+
+        Some(source).filter(|_| is_code)
+    }
+
+    /// Checks if the track is in the given language, e.g. 'en' for 'en', 'en-US', 'en-orig' or 'en-fr'.
+    pub fn is_in(&self, language: &str) -> bool {
+        let base = self
+            .language
+            .split_once('-')
+            .map_or(self.language, |(base, _)| base);
+
+        self.language.eq_ignore_ascii_case(language) || base.eq_ignore_ascii_case(language)
+    }
+}
+
+/// The available extensions for caption files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Extension {
     /// The JSON extension.
@@ -30,4 +99,86 @@ pub enum Extension {
     Ttml,
     /// The Vtt extension.
     Vtt,
+    /// The Srt extension, used by some uploaded subtitles.
+    Srt,
+
+    /// An unknown extension, which can not be parsed.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Extension {
+    /// Returns the preference of the extension when several are available, the higher the better.
+    /// The richer formats come first, as they keep the styling and the exact timings of the captions.
+    pub fn preference(&self) -> u8 {
+        match self {
+            Extension::Json3 => 6,
+            Extension::Srv3 => 5,
+            Extension::Ttml => 4,
+            Extension::Vtt => 3,
+            Extension::Srt => 3,
+            Extension::Srv2 => 2,
+            Extension::Srv1 => 1,
+            Extension::Unknown => 0,
+        }
+    }
+}
+
+/// Represents a cue of a caption track: a text displayed during a part of the video.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cue {
+    /// The time the cue starts being displayed at, in seconds.
+    pub start: f64,
+    /// The time the cue stops being displayed at, in seconds.
+    pub end: f64,
+    /// The text of the cue, its lines being separated by '\n'.
+    pub text: String,
+    /// The styling of the cue.
+    #[serde(default)]
+    pub style: CueStyle,
+}
+
+/// Represents the styling of a cue.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CueStyle {
+    /// If the text is bold.
+    pub bold: bool,
+    /// If the text is italic.
+    pub italic: bool,
+    /// If the text is underlined.
+    pub underline: bool,
+    /// The color of the text, e.g. '#FFFF00'.
+    pub color: Option<String>,
+}
+
+impl CueStyle {
+    /// Returns the styling shared by both styles, e.g. to style a cue made of differently styled parts.
+    pub fn common(&self, other: &CueStyle) -> CueStyle {
+        CueStyle {
+            bold: self.bold && other.bold,
+            italic: self.italic && other.italic,
+            underline: self.underline && other.underline,
+            color: self.color.clone().filter(|_| self.color == other.color),
+        }
+    }
+}
+
+/// The available formats to write caption tracks in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionFormat {
+    /// The SubRip format, with a '.srt' extension.
+    Srt,
+    /// The WebVTT format, with a '.vtt' extension.
+    WebVtt,
+}
+
+impl CaptionFormat {
+    /// Returns the extension of the files in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CaptionFormat::Srt => "srt",
+            CaptionFormat::WebVtt => "vtt",
+        }
+    }
 }
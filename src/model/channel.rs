@@ -0,0 +1,134 @@
+//! Channels-related models.
+//!
+//! A channel is fetched tab by tab, each tab listing flat entries: videos, shorts, live streams or playlists.
+
+use crate::model::playlist::PlaylistEntry;
+use crate::model::thumbnail::Thumbnail;
+use serde::{Deserialize, Serialize};
+
+/// Represents a YouTube channel, with the entries of its fetched tabs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Channel {
+    /// The channel ID, not the @username.
+    #[serde(rename = "channel_id")]
+    pub id: String,
+    /// The channel display name.
+    #[serde(rename = "channel")]
+    pub name: String,
+    /// The handle of the channel, e.g. '@username'.
+    #[serde(rename = "uploader_id")]
+    pub handle: Option<String>,
+    /// The URL of the channel.
+    #[serde(rename = "channel_url")]
+    pub url: Option<String>,
+    /// The description of the channel.
+    pub description: Option<String>,
+    /// The number of subscribers the channel has.
+    #[serde(rename = "channel_follower_count")]
+    pub follower_count: Option<i64>,
+
+    /// The tags of the channel.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The thumbnails of the channel, including its avatar and banners.
+    #[serde(default)]
+    pub thumbnails: Vec<Thumbnail>,
+
+    /// The entries of the videos tab, newest first.
+    #[serde(default)]
+    pub videos: Vec<PlaylistEntry>,
+    /// The entries of the shorts tab, newest first.
+    #[serde(default)]
+    pub shorts: Vec<PlaylistEntry>,
+    /// The entries of the live tab, newest first.
+    #[serde(default)]
+    pub streams: Vec<PlaylistEntry>,
+    /// The entries of the playlists tab, which are playlists.
+    #[serde(default)]
+    pub playlists: Vec<PlaylistEntry>,
+}
+
+/// The tabs of a channel, listing its entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelTab {
+    /// The videos tab, at '/videos'.
+    Videos,
+    /// The shorts tab, at '/shorts'.
+    Shorts,
+    /// The live tab, at '/streams'.
+    Streams,
+    /// The playlists tab, at '/playlists'.
+    Playlists,
+}
+
+impl Channel {
+    /// Returns the entries of the given tab.
+    ///
+    /// # Arguments
+    ///
+    /// * `tab` - The tab to get the entries of.
+    pub fn entries(&self, tab: ChannelTab) -> &[PlaylistEntry] {
+        match tab {
+            ChannelTab::Videos => &self.videos,
+            ChannelTab::Shorts => &self.shorts,
+            ChannelTab::Streams => &self.streams,
+            ChannelTab::Playlists => &self.playlists,
+        }
+    }
+
+    /// Returns the mutable entries of the given tab.
+    pub(crate) fn entries_mut(&mut self, tab: ChannelTab) -> &mut Vec<PlaylistEntry> {
+        match tab {
+            ChannelTab::Videos => &mut self.videos,
+            ChannelTab::Shorts => &mut self.shorts,
+            ChannelTab::Streams => &mut self.streams,
+            ChannelTab::Playlists => &mut self.playlists,
+        }
+    }
+
+    /// Returns the avatar of the channel, in its original size.
+    pub fn avatar(&self) -> Option<&Thumbnail> {
+        self.thumbnails
+            .iter()
+            .find(|thumbnail| thumbnail.id == "avatar_uncropped")
+    }
+
+    /// Returns the banner of the channel, in its original size, or the widest one otherwise.
+    pub fn banner(&self) -> Option<&Thumbnail> {
+        self.thumbnails
+            .iter()
+            .find(|thumbnail| thumbnail.id == "banner_uncropped")
+            .or_else(|| {
+                self.thumbnails
+                    .iter()
+                    .filter(|thumbnail| !thumbnail.id.starts_with("avatar"))
+                    .max_by_key(|thumbnail| thumbnail.width.unwrap_or_default())
+            })
+    }
+}
+
+impl ChannelTab {
+    /// All the tabs, in the order they are displayed on YouTube.
+    pub const ALL: [ChannelTab; 4] = [
+        ChannelTab::Videos,
+        ChannelTab::Shorts,
+        ChannelTab::Streams,
+        ChannelTab::Playlists,
+    ];
+
+    /// Returns the path of the tab, appended to the URL of the channel, e.g. 'videos'.
+    pub fn path(&self) -> &'static str {
+        match self {
+            ChannelTab::Videos => "videos",
+            ChannelTab::Shorts => "shorts",
+            ChannelTab::Streams => "streams",
+            ChannelTab::Playlists => "playlists",
+        }
+    }
+
+    /// Checks if the entries of the tab are videos, with an upload date.
+    pub fn has_videos(&self) -> bool {
+        !matches!(self, ChannelTab::Playlists)
+    }
+}
@@ -0,0 +1,286 @@
+//! Comments-related models.
+//!
+//! The comments are listed flat by 'yt-dlp', each reply referencing its parent, and can be arranged into threads.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The parent of the top-level comments.
+const ROOT: &str = "root";
+
+/// Represents a comment of a YouTube video, or a reply to another comment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    /// The ID of the comment.
+    pub id: String,
+    /// The ID of the comment this one replies to, or 'root' for the top-level comments.
+    pub parent: String,
+    /// The text of the comment.
+    pub text: String,
+    /// The number of likes the comment has.
+    pub like_count: Option<i64>,
+    /// The approximate date of the comment, computed from its relative date, e.g. '2 weeks ago'.
+    #[serde(rename = "timestamp")]
+    pub date: Option<i64>,
+
+    /// The display name of the author.
+    pub author: Option<String>,
+    /// The channel ID of the author.
+    #[serde(rename = "author_id")]
+    pub author_channel_id: Option<String>,
+    /// The URL of the channel of the author.
+    #[serde(rename = "author_url")]
+    pub author_channel_url: Option<String>,
+    /// The URL of the avatar of the author.
+    pub author_thumbnail: Option<String>,
+    /// If the author is the uploader of the video.
+    #[serde(default)]
+    pub author_is_uploader: bool,
+    /// If the author has a verified channel.
+    #[serde(default)]
+    pub author_is_verified: bool,
+
+    /// If the comment is pinned by the uploader of the video.
+    #[serde(default)]
+    pub is_pinned: bool,
+    /// If the comment is hearted by the uploader of the video.
+    #[serde(default)]
+    pub is_favorited: bool,
+}
+
+/// Represents a comment with its replies, and their own replies.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommentThread {
+    /// The comment starting the thread.
+    pub comment: Comment,
+    /// The replies to the comment, in the order they were listed.
+    pub replies: Vec<CommentThread>,
+}
+
+impl Comment {
+    /// Checks if the comment is a reply to another comment.
+    pub fn is_reply(&self) -> bool {
+        self.parent != ROOT
+    }
+
+    /// Returns the ID of the comment this one replies to, if it is a reply.
+    pub fn parent_id(&self) -> Option<&str> {
+        self.is_reply().then_some(self.parent.as_str())
+    }
+}
+
+impl CommentThread {
+    /// Arranges flat comments into threads, keeping their order.
+    /// The replies whose parent is not listed, e.g. because of the limits of the extraction, start their own thread.
+    /// The comments replying to each other in a cycle have no top-level comment,
+    /// so the first listed comment of the cycle starts their thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `comments` - The comments to arrange, as listed by 'yt-dlp'.
+    pub fn from_comments(comments: Vec<Comment>) -> Vec<CommentThread> {
+        let ids: HashMap<&str, usize> = comments
+            .iter()
+            .enumerate()
+            .map(|(index, comment)| (comment.id.as_str(), index))
+            .collect();
+
+        let parents: Vec<Option<usize>> = comments
+            .iter()
+            .enumerate()
+            .map(|(index, comment)| {
+                comment
+                    .parent_id()
+                    .and_then(|parent| ids.get(parent).copied())
+                    .filter(|&parent| parent != index)
+            })
+            .collect();
+
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); comments.len()];
+        let mut roots = Vec::new();
+
+        for (index, parent) in parents.iter().enumerate() {
+            match parent {
+                Some(parent) => children[*parent].push(index),
+                None => roots.push(index),
+            }
+        }
+
+        let mut comments: Vec<Option<Comment>> = comments.into_iter().map(Some).collect();
+        let mut threads: Vec<(usize, CommentThread)> = roots
+            .into_iter()
+            .filter_map(|index| Some((index, thread(index, &mut comments, &children)?)))
+            .collect();
+
+        // The comments left are in a cycle, or reply to a comment in a cycle
+        for index in 0..comments.len() {
+            if comments[index].is_some() {
+                let start = cycle_start(index, &parents);
+                threads
+                    .extend(thread(start, &mut comments, &children).map(|thread| (start, thread)));
+            }
+        }
+
+        threads.sort_by_key(|(index, _)| *index);
+        threads.into_iter().map(|(_, thread)| thread).collect()
+    }
+
+    /// Returns the number of comments in the thread, including the first one.
+    pub fn comment_count(&self) -> usize {
+        1 + self
+            .replies
+            .iter()
+            .map(CommentThread::comment_count)
+            .sum::<usize>()
+    }
+
+    /// Returns the comments of the thread, in depth-first order.
+    pub fn flatten(&self) -> Vec<&Comment> {
+        let mut comments = vec![&self.comment];
+        for reply in &self.replies {
+            comments.extend(reply.flatten());
+        }
+
+        comments
+    }
+}
+
+/// Builds the thread starting with the comment at the given index, taking the comments out of the list.
+/// A comment already taken, which only happens when a cycle of replies comes back to its first comment, is skipped.
+fn thread(
+    index: usize,
+    comments: &mut [Option<Comment>],
+    children: &[Vec<usize>],
+) -> Option<CommentThread> {
+    let comment = comments[index].take()?;
+    let replies = children[index]
+        .iter()
+        .filter_map(|&child| thread(child, comments, children))
+        .collect();
+
+    Some(CommentThread { comment, replies })
+}
+
+/// Returns the index of the first listed comment of the cycle of replies the given comment is in, or leads to.
+fn cycle_start(index: usize, parents: &[Option<usize>]) -> usize {
+    // Going up as many parents as there are comments always ends in the cycle
+    let mut current = index;
+    for _ in 0..parents.len() {
+        current = parents[current].unwrap_or(current);
+    }
+
+    let mut start = current;
+    let mut member = parents[current].unwrap_or(current);
+    while member != current {
+        start = start.min(member);
+        member = parents[member].unwrap_or(current);
+    }
+
+    start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed_comment(id: &str, parent: &str) -> Comment {
+        Comment {
+            id: id.to_string(),
+            parent: parent.to_string(),
+            text: format!("Comment {}", id),
+            like_count: None,
+            date: None,
+            author: None,
+            author_channel_id: None,
+            author_channel_url: None,
+            author_thumbnail: None,
+            author_is_uploader: false,
+            author_is_verified: false,
+            is_pinned: false,
+            is_favorited: false,
+        }
+    }
+
+    fn ids(thread: &CommentThread) -> Vec<&str> {
+        thread
+            .flatten()
+            .into_iter()
+            .map(|comment| comment.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn nest_replies() {
+        let threads = CommentThread::from_comments(vec![
+            listed_comment("a", ROOT),
+            listed_comment("b", "a"),
+            listed_comment("c", "b"),
+            listed_comment("d", "a"),
+        ]);
+
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].comment.id, "a");
+        assert_eq!(threads[0].replies.len(), 2);
+        assert_eq!(threads[0].replies[0].replies[0].comment.id, "c");
+        assert_eq!(threads[0].comment_count(), 4);
+        assert_eq!(ids(&threads[0]), ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn keep_order() {
+        let threads = CommentThread::from_comments(vec![
+            listed_comment("a", ROOT),
+            listed_comment("b", ROOT),
+            listed_comment("c", "b"),
+            listed_comment("d", "a"),
+            listed_comment("e", "a"),
+        ]);
+
+        let threads: Vec<Vec<&str>> = threads.iter().map(ids).collect();
+        assert_eq!(threads, [vec!["a", "d", "e"], vec!["b", "c"]]);
+    }
+
+    #[test]
+    fn orphan_replies() {
+        let threads = CommentThread::from_comments(vec![
+            listed_comment("a", ROOT),
+            listed_comment("b", "missing"),
+            listed_comment("c", "b"),
+            listed_comment("d", "d"),
+        ]);
+
+        let threads: Vec<Vec<&str>> = threads.iter().map(ids).collect();
+        assert_eq!(threads, [vec!["a"], vec!["b", "c"], vec!["d"]]);
+    }
+
+    #[test]
+    fn cyclic_replies() {
+        let threads = CommentThread::from_comments(vec![
+            listed_comment("a", ROOT),
+            listed_comment("b", "d"),
+            listed_comment("c", "b"),
+            listed_comment("d", "c"),
+            listed_comment("e", "f"),
+            listed_comment("f", "e"),
+            listed_comment("g", ROOT),
+        ]);
+
+        let threads: Vec<Vec<&str>> = threads.iter().map(ids).collect();
+        assert_eq!(
+            threads,
+            [vec!["a"], vec!["b", "c", "d"], vec!["e", "f"], vec!["g"]]
+        );
+    }
+
+    #[test]
+    fn replies_leading_to_cycle() {
+        let threads = CommentThread::from_comments(vec![
+            listed_comment("a", "c"),
+            listed_comment("b", "c"),
+            listed_comment("c", "b"),
+        ]);
+
+        let threads: Vec<Vec<&str>> = threads.iter().map(ids).collect();
+        assert_eq!(threads, [vec!["b", "c", "a"]]);
+    }
+}
@@ -1,6 +1,7 @@
 //! Formats-related models.
 
 use crate::model::utils::serde::json_none;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, COOKIE};
 use serde::{Deserialize, Serialize};
 
 /// Represents an available format of a video.
@@ -146,6 +147,50 @@ pub struct DownloadInfo {
     pub manifest_url: Option<String>,
     /// The options used by the downloader.
     pub downloader_options: Option<DownloaderOptions>,
+    /// The cookies used by the downloader, in the 'Set-Cookie' format, e.g. 'name=value; Domain=.youtube.com; Path=/'.
+    #[serde(default)]
+    pub cookies: Option<String>,
+}
+
+impl DownloadInfo {
+    /// Returns the HTTP headers to send when downloading the format, the way 'yt-dlp' would.
+    /// It contains the format HTTP headers, and a 'Cookie' header if the format has cookies.
+    /// Headers with invalid values are skipped.
+    pub fn headers(&self) -> HeaderMap {
+        let mut headers = self.http_headers.to_header_map();
+
+        let cookie = self
+            .cookies
+            .as_deref()
+            .map(cookie_header)
+            .unwrap_or_default();
+        if !cookie.is_empty() {
+            if let Ok(value) = HeaderValue::from_str(&cookie) {
+                headers.insert(COOKIE, value);
+            }
+        }
+
+        headers
+    }
+}
+
+/// Converts cookies in the 'Set-Cookie' format to a 'Cookie' header value, dropping the cookies attributes.
+/// For example, 'a=1; Domain=.youtube.com; Path=/; Secure; b=2' becomes 'a=1; b=2'.
+fn cookie_header(cookies: &str) -> String {
+    const ATTRIBUTES: [&str; 9] = [
+        "domain", "path", "expires", "max-age", "secure", "httponly", "samesite", "version",
+        "comment",
+    ];
+
+    let pairs = cookies
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .filter(|(name, _)| !ATTRIBUTES.contains(&name.trim().to_lowercase().as_str()));
+
+    pairs
+        .map(|(name, value)| format!("{}={}", name.trim(), value.trim()))
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 /// Represents the quality information of a format.
@@ -167,6 +212,15 @@ pub struct FileInfo {
     pub filesize: Option<i64>,
 }
 
+impl FileInfo {
+    /// Returns the exact file size of the format if known, or its approximate file size otherwise.
+    pub fn size(&self) -> Option<u64> {
+        let size = self.filesize.or(self.filesize_approx)?;
+
+        u64::try_from(size).ok()
+    }
+}
+
 /// Represents the rates information of a format.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RatesInfo {
@@ -225,11 +279,35 @@ pub struct HttpHeaders {
     pub sec_fetch_mode: String,
 }
 
+impl HttpHeaders {
+    /// Converts the headers to a header map, usable with 'reqwest'.
+    /// Headers with empty or invalid values are skipped.
+    pub fn to_header_map(&self) -> HeaderMap {
+        let headers = [
+            ("user-agent", &self.user_agent),
+            ("accept", &self.accept),
+            ("accept-language", &self.accept_language),
+            ("sec-fetch-mode", &self.sec_fetch_mode),
+        ];
+
+        let mut map = HeaderMap::new();
+        for (name, value) in headers.into_iter().filter(|(_, value)| !value.is_empty()) {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                map.insert(HeaderName::from_static(name), value);
+            }
+        }
+
+        map
+    }
+}
+
 /// The available extensions of a format.
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Extension {
     /// The M4A extension.
+    /// Renamed, since 'snake_case' would expect 'm4_a' and the M4A formats would be unknown.
+    #[serde(rename = "m4a")]
     M4A,
     /// The MP3 extension.
     Mp4,
@@ -273,9 +351,14 @@ pub enum Container {
 pub enum Protocol {
     /// The HTTP protocol, used for audio and video formats.
     Https,
-    /// The M3U8 protocol, used for manifest formats.
+    /// The HLS protocol, downloaded by 'ffmpeg' in 'yt-dlp', used for manifest formats.
+    #[serde(rename = "m3u8")]
+    M3U8,
+    /// The HLS protocol, downloaded natively, used for manifest formats.
     #[serde(rename = "m3u8_native")]
     M3U8Native,
+    /// The DASH protocol, whose segments are downloaded natively.
+    HttpDashSegments,
     /// The MHTML protocol, used for storyboard formats.
     Mhtml,
 
@@ -347,3 +430,27 @@ impl FormatType {
         matches!(self, FormatType::Manifest)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension_names() {
+        let extensions = [
+            ("\"m4a\"", Extension::M4A),
+            ("\"mp4\"", Extension::Mp4),
+            ("\"webm\"", Extension::Webm),
+            ("\"mhtml\"", Extension::Mhtml),
+            ("\"none\"", Extension::None),
+        ];
+
+        for (name, extension) in extensions {
+            assert_eq!(serde_json::from_str::<Extension>(name).unwrap(), extension);
+            assert_eq!(serde_json::to_string(&extension).unwrap(), name);
+        }
+
+        let unknown = serde_json::from_str::<Extension>("\"flv\"").unwrap();
+        assert_eq!(unknown, Extension::Unknown);
+    }
+}
@@ -1,16 +1,24 @@
 //! The models used to represent the data fetched by 'yt-dlp'.
 //!
-//! The represented data is the video information, thumbnails, automatic captions, and formats.
+//! The represented data is the video, playlist and channel information, thumbnails, subtitles and automatic captions, comments, and formats.
+//! The formats can be picked with selectors and sorted with comparators, similar to the '-f' and '-S' options of 'yt-dlp'.
 
-use crate::model::caption::AutomaticCaption;
+use crate::model::caption::{Caption, CaptionKind, CaptionTrack};
+use crate::model::comment::Comment;
 use crate::model::format::Format;
+use crate::model::selector::{FormatSelector, Selection};
+use crate::model::sorting::{FormatComparator, FormatSorter};
 use crate::model::thumbnail::Thumbnail;
-use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub mod caption;
+pub mod channel;
+pub mod comment;
 pub mod format;
+pub mod playlist;
+pub mod selector;
+pub mod sorting;
 pub mod thumbnail;
 pub mod utils;
 
@@ -30,6 +38,9 @@ pub struct Video {
     /// The upload date of the video.
     #[serde(rename = "timestamp")]
     pub upload_date: i64,
+    /// The URL of the video page, e.g. 'https://www.youtube.com/watch?v=dQw4w9WgXcQ'.
+    #[serde(default)]
+    pub webpage_url: Option<String>,
 
     /// The number of views the video has.
     pub view_count: i64,
@@ -37,6 +48,9 @@ pub struct Video {
     pub like_count: Option<i64>,
     /// The number of comments the video has. None, when the author has disabled comments.
     pub comment_count: Option<i64>,
+    /// The comments of the video, only extracted with the '--write-comments' option, see [`crate::Youtube::fetch_comments`].
+    #[serde(default)]
+    pub comments: Option<Vec<Comment>>,
 
     /// The channel display name.
     pub channel: String,
@@ -51,8 +65,11 @@ pub struct Video {
     pub formats: Vec<Format>,
     /// The thumbnails of the video.
     pub thumbnails: Vec<Thumbnail>,
-    /// The automatic captions of the video.
-    pub automatic_captions: HashMap<String, Vec<AutomaticCaption>>,
+    /// The subtitles of the video, uploaded by its author, by language code.
+    #[serde(default)]
+    pub subtitles: HashMap<String, Vec<Caption>>,
+    /// The automatic captions of the video, by language code, including their automatic translations.
+    pub automatic_captions: HashMap<String, Vec<Caption>>,
 
     /// The tags of the video.
     pub tags: Vec<String>,
@@ -64,8 +81,9 @@ pub struct Video {
     /// If the video is available in the country.
     #[serde(rename = "_has_drm")]
     pub has_drm: Option<bool>,
-    /// If the video was a live stream.
-    pub live_status: String,
+    /// If the video is, was, or will be a live stream.
+    #[serde(default)]
+    pub live_status: LiveStatus,
     /// If the video is playable in an embed.
     pub playable_in_embed: bool,
 
@@ -77,6 +95,34 @@ pub struct Video {
     pub version: Version,
 }
 
+/// The live status of a video.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveStatus {
+    /// The video is not a live stream.
+    NotLive,
+    /// The video is a live stream, currently broadcasting.
+    IsLive,
+    /// The video is a scheduled live stream, which has not started yet.
+    IsUpcoming,
+    /// The video was a live stream, and is now available as a regular video.
+    WasLive,
+    /// The video was a live stream which just ended, and is still being processed.
+    PostLive,
+
+    /// An unknown live status.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl LiveStatus {
+    /// Checks if the video is currently broadcasting, or just ended and can still be recorded from its manifest.
+    pub fn is_recordable(&self) -> bool {
+        matches!(self, LiveStatus::IsLive | LiveStatus::PostLive)
+    }
+}
+
 /// Represents the extractor information.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExtractorInfo {
@@ -100,105 +146,336 @@ pub struct Version {
 }
 
 impl Video {
+    /// Checks if the video is a live stream, currently broadcasting.
+    pub fn is_live(&self) -> bool {
+        self.live_status == LiveStatus::IsLive
+    }
+
     /// Returns the best format available.
     /// Formats sorting : "quality", "video resolution", "fps", "video bitrate"
     /// If the video has no formats video formats, it returns None.
     pub fn best_video_format(&self) -> Option<&Format> {
-        let video_formats = self.formats.iter().filter(|format| format.is_video());
-
-        video_formats.max_by(|a, b| self.compare_video_formats(a, b))
+        self.best_video_format_by(&FormatSorter::video())
     }
 
     /// Returns the best audio format available.
     /// Formats sorting : "quality", "audio bitrate", "sample rate", "audio channels"
     /// If the video has no formats audio formats, it returns None.
     pub fn best_audio_format(&self) -> Option<&Format> {
-        let audio_formats = self.formats.iter().filter(|format| format.is_audio());
-
-        audio_formats.max_by(|a, b| self.compare_audio_formats(a, b))
+        self.best_audio_format_by(&FormatSorter::audio())
     }
 
     /// Returns the worst video format available.
     /// Formats sorting : "quality", "video resolution", "fps", "video bitrate"
     /// If the video has no formats video formats, it returns None.
     pub fn worst_video_format(&self) -> Option<&Format> {
-        let video_formats = self.formats.iter().filter(|format| format.is_video());
-
-        video_formats.min_by(|a, b| self.compare_video_formats(a, b))
+        self.worst_video_format_by(&FormatSorter::video())
     }
 
     /// Returns the worst audio format available.
     /// Formats sorting : "quality", "audio bitrate", "sample rate", "audio channels"
     /// If the video has no formats audio formats, it returns None.
     pub fn worst_audio_format(&self) -> Option<&Format> {
+        self.worst_audio_format_by(&FormatSorter::audio())
+    }
+
+    /// Returns the best video format available, according to the given comparator.
+    /// If the video has no formats video formats, it returns None.
+    ///
+    /// # Arguments
+    ///
+    /// * `comparator` - The comparator ordering the formats, e.g. a [`FormatSorter`].
+    pub fn best_video_format_by(&self, comparator: &dyn FormatComparator) -> Option<&Format> {
+        let video_formats = self.formats.iter().filter(|format| format.is_video());
+
+        video_formats.max_by(|a, b| comparator.compare(a, b))
+    }
+
+    /// Returns the best audio format available, according to the given comparator.
+    /// If the video has no formats audio formats, it returns None.
+    ///
+    /// # Arguments
+    ///
+    /// * `comparator` - The comparator ordering the formats, e.g. a [`FormatSorter`].
+    pub fn best_audio_format_by(&self, comparator: &dyn FormatComparator) -> Option<&Format> {
         let audio_formats = self.formats.iter().filter(|format| format.is_audio());
 
-        audio_formats.min_by(|a, b| self.compare_audio_formats(a, b))
+        audio_formats.max_by(|a, b| comparator.compare(a, b))
     }
 
-    /// Compares two video formats.
-    /// Formats sorting : "quality", "video resolution", "fps", "video bitrate"
-    pub fn compare_video_formats(&self, a: &Format, b: &Format) -> std::cmp::Ordering {
-        let a_quality = a.quality_info.quality.unwrap_or(0.0);
-        let b_quality = b.quality_info.quality.unwrap_or(0.0);
+    /// Returns the worst video format available, according to the given comparator.
+    /// If the video has no formats video formats, it returns None.
+    ///
+    /// # Arguments
+    ///
+    /// * `comparator` - The comparator ordering the formats, e.g. a [`FormatSorter`].
+    pub fn worst_video_format_by(&self, comparator: &dyn FormatComparator) -> Option<&Format> {
+        let video_formats = self.formats.iter().filter(|format| format.is_video());
+
+        video_formats.min_by(|a, b| comparator.compare(a, b))
+    }
+
+    /// Returns the worst audio format available, according to the given comparator.
+    /// If the video has no formats audio formats, it returns None.
+    ///
+    /// # Arguments
+    ///
+    /// * `comparator` - The comparator ordering the formats, e.g. a [`FormatSorter`].
+    pub fn worst_audio_format_by(&self, comparator: &dyn FormatComparator) -> Option<&Format> {
+        let audio_formats = self.formats.iter().filter(|format| format.is_audio());
 
-        let cmp_quality = OrderedFloat(a_quality).cmp(&OrderedFloat(b_quality));
-        if cmp_quality != std::cmp::Ordering::Equal {
-            return cmp_quality;
-        }
+        audio_formats.min_by(|a, b| comparator.compare(a, b))
+    }
+
+    /// Returns all the caption tracks of the video, the subtitles first, then the automatic captions.
+    pub fn caption_tracks(&self) -> Vec<CaptionTrack<'_>> {
+        let mut caption_tracks = tracks(&self.subtitles, CaptionKind::Manual);
+        caption_tracks.extend(tracks(&self.automatic_captions, CaptionKind::Automatic));
 
-        let a_height = a.video_resolution.height.unwrap_or(0);
-        let b_height = b.video_resolution.height.unwrap_or(0);
+        caption_tracks
+    }
 
-        let cmp_height = a_height.cmp(&b_height);
-        if cmp_height != std::cmp::Ordering::Equal {
-            return cmp_height;
-        }
+    /// Returns the best caption track in the given language.
+    /// Tracks ranking : "subtitles", "regional subtitles" (e.g. 'en-US'), "original automatic captions" (e.g. 'en-orig'),
+    /// "automatic captions", "automatic translations" (e.g. 'en-fr')
+    /// If the video has no caption tracks in the language, it returns None.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The language code of the track, e.g. 'en' or 'en-US'.
+    pub fn best_caption_track(&self, language: &str) -> Option<CaptionTrack<'_>> {
+        let rank = |track: &CaptionTrack| {
+            let exact = track.language.eq_ignore_ascii_case(language);
+
+            match track.kind {
+                CaptionKind::Manual if exact => 5,
+                CaptionKind::Manual => 4,
+                CaptionKind::Automatic if track.language.ends_with("-orig") => 3,
+                CaptionKind::Automatic if !track.is_translated() => 2,
+                CaptionKind::Automatic => 1,
+            }
+        };
+
+        let tracks = self.caption_tracks().into_iter();
+        let matching =
+            tracks.filter(|track| track.is_in(language) && track.best_caption().is_some());
+
+        // The first track of equal rank is kept, so the tracks are sorted by language code
+        matching.rev().max_by_key(rank)
+    }
 
-        let a_fps = a.video_resolution.fps.unwrap_or(0.0);
-        let b_fps = b.video_resolution.fps.unwrap_or(0.0);
+    /// Returns the storyboard format with the largest frames.
+    /// If the video has no storyboard formats, it returns None.
+    pub fn best_storyboard_format(&self) -> Option<&Format> {
+        let storyboard_formats = self
+            .formats
+            .iter()
+            .filter(|format| format.format_type().is_storyboard());
+
+        storyboard_formats.max_by_key(|format| {
+            let resolution = &format.video_resolution;
+            resolution.width.unwrap_or(0) * resolution.height.unwrap_or(0)
+        })
+    }
 
-        let cmp_fps = OrderedFloat(a_fps).cmp(&OrderedFloat(b_fps));
-        if cmp_fps != std::cmp::Ordering::Equal {
-            return cmp_fps;
-        }
+    /// Returns the formats picked by the given selector, or none if no alternative matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The selector to evaluate, e.g. parsed from 'bv[height<=1080]+ba/b'.
+    pub fn select_formats(&self, selector: &FormatSelector) -> Option<Selection<'_>> {
+        selector.select(self)
+    }
 
-        let a_vbr = a.rates_info.video_rate.unwrap_or(0.0);
-        let b_vbr = b.rates_info.video_rate.unwrap_or(0.0);
+    /// Returns the formats picked by the given selector, ranking the formats with the given comparators.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The selector to evaluate.
+    /// * `video_comparator` - The comparator ordering the formats with video.
+    /// * `audio_comparator` - The comparator ordering the audio-only formats.
+    pub fn select_formats_by(
+        &self,
+        selector: &FormatSelector,
+        video_comparator: &dyn FormatComparator,
+        audio_comparator: &dyn FormatComparator,
+    ) -> Option<Selection<'_>> {
+        selector.select_by(self, video_comparator, audio_comparator)
+    }
 
-        OrderedFloat(a_vbr).cmp(&OrderedFloat(b_vbr))
+    /// Compares two video formats.
+    /// Formats sorting : "quality", "video resolution", "fps", "video bitrate"
+    pub fn compare_video_formats(&self, a: &Format, b: &Format) -> std::cmp::Ordering {
+        FormatSorter::video().compare(a, b)
     }
 
     /// Compares two audio formats.
     /// Formats sorting : "quality", "audio bitrate", "sample rate", "audio channels"
     pub fn compare_audio_formats(&self, a: &Format, b: &Format) -> std::cmp::Ordering {
-        let a_quality = a.quality_info.quality.unwrap_or(0.0);
-        let b_quality = b.quality_info.quality.unwrap_or(0.0);
+        FormatSorter::audio().compare(a, b)
+    }
+}
 
-        let cmp_quality = OrderedFloat(a_quality).cmp(&OrderedFloat(b_quality));
-        if cmp_quality != std::cmp::Ordering::Equal {
-            return cmp_quality;
-        }
+/// Lists the caption tracks of a captions map, sorted by language code.
+fn tracks(captions: &HashMap<String, Vec<Caption>>, kind: CaptionKind) -> Vec<CaptionTrack<'_>> {
+    let mut tracks: Vec<CaptionTrack<'_>> = captions
+        .iter()
+        .map(|(language, captions)| CaptionTrack {
+            language,
+            kind,
+            captions,
+        })
+        .collect();
+
+    tracks.sort_by_key(|track| track.language);
+    tracks
+}
 
-        let a_abr = a.rates_info.audio_rate.unwrap_or(0.0);
-        let b_abr = b.rates_info.audio_rate.unwrap_or(0.0);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// Returns the files of a caption track, in the given formats.
+    fn captions(language: &str, extensions: &[&str]) -> Value {
+        let captions: Vec<_> = extensions
+            .iter()
+            .map(|extension| {
+                json!({
+                    "ext": extension,
+                    "url": format!("https://example.com/{}.{}", language, extension),
+                })
+            })
+            .collect();
+
+        Value::from(captions)
+    }
 
-        let cmp_abr = OrderedFloat(a_abr).cmp(&OrderedFloat(b_abr));
-        if cmp_abr != std::cmp::Ordering::Equal {
-            return cmp_abr;
-        }
+    /// Returns a video with the given subtitles and automatic captions, available in 'vtt'.
+    fn captioned_video(subtitles: &[&str], automatic_captions: &[&str]) -> Video {
+        let tracks = |languages: &[&str]| {
+            let tracks = languages
+                .iter()
+                .map(|language| (language.to_string(), captions(language, &["vtt"])));
+            Value::Object(tracks.collect())
+        };
+
+        let video = json!({
+            "id": "dQw4w9WgXcQ",
+            "title": "Rick Astley - Never Gonna Give You Up",
+            "thumbnail": "",
+            "description": "",
+            "availability": "public",
+            "timestamp": 1256453853,
+            "view_count": 0,
+            "channel": "Rick Astley",
+            "channel_id": "UCuAXFkgsw1L7xaCfnd5JJOw",
+            "channel_url": "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw",
+            "channel_follower_count": 0,
+            "formats": [],
+            "thumbnails": [],
+            "subtitles": tracks(subtitles),
+            "automatic_captions": tracks(automatic_captions),
+            "tags": [],
+            "categories": [],
+            "age_limit": 0,
+            "playable_in_embed": true,
+            "extractor": "youtube",
+            "extractor_key": "Youtube",
+            "_version": {
+                "version": "2024.10.22",
+                "release_git_head": "",
+                "repository": "yt-dlp/yt-dlp",
+            },
+        });
+
+        serde_json::from_value(video).unwrap()
+    }
 
-        let a_asr = a.codec_info.asr.unwrap_or(0);
-        let b_asr = b.codec_info.asr.unwrap_or(0);
+    /// Returns the language and the kind of the best caption track in the given language.
+    fn best(video: &Video, language: &str) -> Option<(String, CaptionKind)> {
+        video
+            .best_caption_track(language)
+            .map(|track| (track.language.to_string(), track.kind))
+    }
 
-        let cmp_asr = a_asr.cmp(&b_asr);
-        if cmp_asr != std::cmp::Ordering::Equal {
-            return cmp_asr;
-        }
+    #[test]
+    fn source_languages() {
+        let track = |language, kind| CaptionTrack {
+            language,
+            kind,
+            captions: &[],
+        };
+
+        let automatic = |language| track(language, CaptionKind::Automatic).source_language();
+        assert_eq!(automatic("en-fr"), Some("fr"));
+        assert_eq!(automatic("zh-Hans-en"), Some("en"));
+        assert_eq!(automatic("en-fil"), Some("fil"));
+        assert_eq!(automatic("en"), None);
+        assert_eq!(automatic("en-orig"), None);
+        assert_eq!(automatic("zh-Hans"), None);
+        assert_eq!(automatic("pt-BR"), None);
+        assert_eq!(automatic("en-GB"), None);
+        assert_eq!(automatic("es-419"), None);
+
+        assert!(track("en-fr", CaptionKind::Automatic).is_translated());
+        assert!(!track("en-fr", CaptionKind::Manual).is_translated());
+        assert!(!track("en-GB", CaptionKind::Automatic).is_translated());
+    }
 
-        let a_channels = a.codec_info.audio_channels.unwrap_or(0);
-        let b_channels = b.codec_info.audio_channels.unwrap_or(0);
+    #[test]
+    fn best_caption_track_ranking() {
+        let manual = CaptionKind::Manual;
+        let automatic = CaptionKind::Automatic;
+
+        let video = captioned_video(
+            &["en", "en-US", "fr"],
+            &["en", "en-GB", "en-fr", "en-orig", "fr"],
+        );
+        assert_eq!(best(&video, "en"), Some(("en".to_string(), manual)));
+        assert_eq!(best(&video, "en-US"), Some(("en-US".to_string(), manual)));
+        assert_eq!(best(&video, "EN-us"), Some(("en-US".to_string(), manual)));
+        assert_eq!(best(&video, "de"), None);
+
+        // The regional subtitles come before the automatic captions
+        let video = captioned_video(&["en-US"], &["en", "en-fr", "en-orig"]);
+        assert_eq!(best(&video, "en"), Some(("en-US".to_string(), manual)));
+
+        // The original automatic captions come before the other ones
+        let video = captioned_video(&["fr"], &["en", "en-GB", "en-fr", "en-orig"]);
+        assert_eq!(best(&video, "en"), Some(("en-orig".to_string(), automatic)));
+
+        // A regional track is not a translation, and the first track by code is kept among equals
+        let video = captioned_video(&[], &["en-fr", "en-GB", "en"]);
+        assert_eq!(best(&video, "en"), Some(("en".to_string(), automatic)));
+        let video = captioned_video(&[], &["en-fr", "en-GB"]);
+        assert_eq!(best(&video, "en"), Some(("en-GB".to_string(), automatic)));
+
+        // The automatic translations come last
+        let video = captioned_video(&[], &["en-fr", "en-de"]);
+        assert_eq!(best(&video, "en"), Some(("en-de".to_string(), automatic)));
+        assert_eq!(
+            best(&video, "en-fr"),
+            Some(("en-fr".to_string(), automatic))
+        );
+    }
 
-        a_channels.cmp(&b_channels)
+    #[test]
+    fn best_caption_track_skips_unknown_formats() {
+        // The live chat replay is listed as a subtitles track, in a format which can not be parsed
+        let mut video = captioned_video(&[], &["en"]);
+        let live_chat = serde_json::from_value(captions("en", &["json"])).unwrap();
+        video.subtitles.insert("en".to_string(), live_chat);
+
+        assert_eq!(
+            best(&video, "en"),
+            Some(("en".to_string(), CaptionKind::Automatic))
+        );
+
+        let track = video.best_caption_track("en").unwrap();
+        assert_eq!(
+            track.best_caption().map(|caption| caption.extension),
+            Some(caption::Extension::Vtt)
+        );
     }
 }
@@ -0,0 +1,119 @@
+//! Playlists-related models.
+//!
+//! A playlist is fetched flat: its entries only hold the basic information of their videos,
+//! which are resolved into complete videos when needed.
+
+use crate::model::thumbnail::Thumbnail;
+use crate::model::{LiveStatus, Video};
+use serde::{Deserialize, Serialize};
+
+/// Represents a YouTube playlist, the output of 'yt-dlp' with the '--flat-playlist' option.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Playlist {
+    /// The ID of the playlist.
+    pub id: String,
+    /// The title of the playlist.
+    pub title: Option<String>,
+    /// The description of the playlist.
+    pub description: Option<String>,
+    /// If the playlist is public, unlisted, or private.
+    pub availability: Option<String>,
+    /// The last modification date of the playlist, e.g. '20241022'.
+    pub modified_date: Option<String>,
+    /// The number of views the playlist has.
+    pub view_count: Option<i64>,
+    /// The URL of the playlist page, e.g. 'https://www.youtube.com/playlist?list=PL...'.
+    pub webpage_url: Option<String>,
+
+    /// The display name of the uploader of the playlist.
+    pub uploader: Option<String>,
+    /// The ID of the uploader, e.g. '@username'.
+    pub uploader_id: Option<String>,
+    /// The URL of the uploader.
+    pub uploader_url: Option<String>,
+    /// The channel display name.
+    pub channel: Option<String>,
+    /// The channel ID, not the @username.
+    pub channel_id: Option<String>,
+    /// The URL of the channel.
+    pub channel_url: Option<String>,
+
+    /// The total number of entries of the playlist, including the ones not fetched.
+    #[serde(rename = "playlist_count")]
+    pub entry_count: Option<usize>,
+    /// The thumbnails of the playlist.
+    #[serde(default)]
+    pub thumbnails: Vec<Thumbnail>,
+    /// The fetched entries of the playlist, in the requested order.
+    pub entries: Vec<PlaylistEntry>,
+    /// The indices of the fetched entries in the playlist, starting at 1, listed by 'yt-dlp' when the items are filtered.
+    #[serde(default)]
+    pub requested_entries: Option<Vec<usize>>,
+}
+
+/// Represents an entry of a playlist, with the basic information of its video.
+/// The entries of the playlists tab of a channel are playlists, whose URL can be fetched as such.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    /// The ID of the video.
+    pub id: String,
+    /// The URL of the video.
+    pub url: String,
+    /// The title of the video.
+    pub title: Option<String>,
+    /// The description of the video, usually truncated.
+    pub description: Option<String>,
+    /// The duration of the video, in seconds.
+    pub duration: Option<f64>,
+    /// The upload date of the video, usually only known for the entries of channels.
+    #[serde(default)]
+    #[serde(rename = "timestamp")]
+    pub upload_date: Option<i64>,
+    /// The number of views the video has.
+    pub view_count: Option<i64>,
+    /// If the video is, was, or will be a live stream.
+    pub live_status: Option<LiveStatus>,
+
+    /// The channel display name.
+    pub channel: Option<String>,
+    /// The channel ID, not the @username.
+    pub channel_id: Option<String>,
+    /// The URL of the channel.
+    pub channel_url: Option<String>,
+
+    /// The thumbnails of the video.
+    #[serde(default)]
+    pub thumbnails: Vec<Thumbnail>,
+    /// The index of the entry in the playlist, starting at 1.
+    #[serde(default)]
+    pub playlist_index: Option<usize>,
+
+    /// The complete video, if it was resolved when fetching the playlist.
+    #[serde(skip)]
+    pub video: Option<Box<Video>>,
+}
+
+impl Playlist {
+    /// Returns the entry of the given video, if it was fetched.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the video.
+    pub fn entry(&self, id: impl AsRef<str>) -> Option<&PlaylistEntry> {
+        self.entries.iter().find(|entry| entry.id == id.as_ref())
+    }
+}
+
+impl PlaylistEntry {
+    /// Checks if the video of the entry was resolved.
+    pub fn is_resolved(&self) -> bool {
+        self.video.is_some()
+    }
+
+    /// Returns the largest thumbnail of the video, if any.
+    pub fn thumbnail(&self) -> Option<&Thumbnail> {
+        self.thumbnails.iter().max_by_key(|thumbnail| {
+            thumbnail.width.unwrap_or_default() * thumbnail.height.unwrap_or_default()
+        })
+    }
+}
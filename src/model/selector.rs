@@ -0,0 +1,1343 @@
+//! Format selection, with an expression language similar to the '-f' option of 'yt-dlp'.
+//!
+//! A selector is made of alternatives separated by '/', tried in order until one of them matches.
+//! Each alternative is a single format, or two formats merged with '+', the video one first.
+//! A format is chosen with a keyword ('best', 'bv', 'ba*', ...) or with its ID, and narrowed with filters between brackets.
+//! Parentheses group alternatives, and filters after a group apply to each of its formats.
+//!
+//! For example, `bv[height<=1080][vcodec^=avc1]+ba/b[filesize<500M]` selects the best video up to 1080p encoded with AVC,
+//! merged with the best audio, or else the best format with audio and video under 500 MB.
+
+use crate::error::{Error, Result};
+use crate::model::format::{Container, DynamicRange, Extension, Format, Protocol};
+use crate::model::sorting::{FormatComparator, FormatSorter};
+use crate::model::Video;
+use crate::ternary;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Selects formats of a video, like the '-f' option of 'yt-dlp'.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::model::selector::{Comparison, FormatField, FormatFilter, FormatSelector};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let selector: FormatSelector = "bv[height<=1080][vcodec^=avc1]+ba/b[filesize<500M]".parse()?;
+///
+/// // The same selector, built in Rust
+/// let selector = FormatSelector::best_video()
+///     .filter(FormatFilter::new(FormatField::Height, Comparison::LessOrEqual, 1080))
+///     .filter(FormatFilter::new(FormatField::VideoCodec, Comparison::StartsWith, "avc1"))
+///     .merge(FormatSelector::best_audio())
+///     .or(FormatSelector::best().filter(FormatFilter::new(
+///         FormatField::FileSize,
+///         Comparison::Less,
+///         500_000_000,
+///     )));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatSelector {
+    /// A single format of the given kind, matching all the filters.
+    Single {
+        /// The kind of format to select.
+        kind: FormatKind,
+        /// The filters the format must match.
+        filters: Vec<FormatFilter>,
+    },
+    /// Two formats merged together, the video one first, then the audio one.
+    Merge(Box<FormatSelector>, Box<FormatSelector>),
+    /// Alternatives tried in order, until one of them matches.
+    Fallback(Vec<FormatSelector>),
+}
+
+/// The kinds of formats a selector can pick.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatKind {
+    /// The best format with the given streams.
+    Best(Streams),
+    /// The worst format with the given streams.
+    Worst(Streams),
+    /// The format with the given ID, e.g. '303'.
+    Id(String),
+}
+
+/// The streams a format must contain to be selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Streams {
+    /// Formats with both audio and video, e.g. 'best' or 'b'.
+    AudioAndVideo,
+    /// Formats with video and no audio, e.g. 'bestvideo' or 'bv'.
+    VideoOnly,
+    /// Formats with video, with or without audio, e.g. 'bestvideo*' or 'bv*'.
+    Video,
+    /// Formats with audio and no video, e.g. 'bestaudio' or 'ba'.
+    AudioOnly,
+    /// Formats with audio, with or without video, e.g. 'bestaudio*' or 'ba*'.
+    Audio,
+    /// Formats with audio or video, e.g. 'best*' or 'b*'.
+    Any,
+}
+
+/// A filter on a field of the formats, e.g. 'height<=1080'.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatFilter {
+    /// The field to compare.
+    pub field: FormatField,
+    /// The comparison to apply.
+    pub comparison: Comparison,
+    /// The value to compare the field with.
+    pub value: FilterValue,
+    /// Whether the comparison is inverted, e.g. '!=' or '!^='.
+    pub negated: bool,
+    /// Whether the formats with an unknown value also match, e.g. 'height<=?1080'.
+    pub optional: bool,
+}
+
+/// The fields of a format that can be filtered on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatField {
+    /// The height of the video, 'height'.
+    Height,
+    /// The width of the video, 'width'.
+    Width,
+    /// The frame rate of the video, 'fps'.
+    Fps,
+    /// The exact or approximate file size, in bytes, 'filesize'.
+    FileSize,
+    /// The total bitrate, in kbit/s, 'tbr'.
+    TotalBitrate,
+    /// The video bitrate, in kbit/s, 'vbr'.
+    VideoBitrate,
+    /// The audio bitrate, in kbit/s, 'abr'.
+    AudioBitrate,
+    /// The audio sample rate, in Hz, 'asr'.
+    SampleRate,
+    /// The number of audio channels, 'audio_channels'.
+    AudioChannels,
+    /// The video codec, e.g. 'avc1.640028', 'vcodec'.
+    VideoCodec,
+    /// The audio codec, e.g. 'opus', 'acodec'.
+    AudioCodec,
+    /// The file extension, e.g. 'mp4', 'ext'.
+    Extension,
+    /// The container, e.g. 'mp4_dash', 'container'.
+    Container,
+    /// The protocol, e.g. 'https', 'protocol'.
+    Protocol,
+    /// The dynamic range, 'SDR' or 'HDR', 'dynamic_range'.
+    DynamicRange,
+    /// The format ID, 'format_id'.
+    FormatId,
+    /// The language, 'language'.
+    Language,
+    /// The format note, e.g. '1080p60', 'format_note'.
+    FormatNote,
+}
+
+/// The comparisons a filter can apply.
+/// Numeric fields support the equality and ordering comparisons, text fields support the equality and substring comparisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// '='.
+    Equal,
+    /// '<'.
+    Less,
+    /// '<='.
+    LessOrEqual,
+    /// '>'.
+    Greater,
+    /// '>='.
+    GreaterOrEqual,
+    /// '^='.
+    StartsWith,
+    /// '$='.
+    EndsWith,
+    /// '*='.
+    Contains,
+}
+
+/// The value a field is compared with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    /// A number, for numeric fields.
+    Number(f64),
+    /// A text, for text fields.
+    Text(String),
+}
+
+/// The formats picked by a selector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Selection<'a> {
+    /// A single format.
+    Single(&'a Format),
+    /// A video format and an audio format, to merge together.
+    Merge {
+        /// The video format.
+        video: &'a Format,
+        /// The audio format.
+        audio: &'a Format,
+    },
+}
+
+impl FormatSelector {
+    /// Selects the best format with both audio and video, 'best'.
+    pub fn best() -> Self {
+        Self::of(FormatKind::Best(Streams::AudioAndVideo))
+    }
+
+    /// Selects the worst format with both audio and video, 'worst'.
+    pub fn worst() -> Self {
+        Self::of(FormatKind::Worst(Streams::AudioAndVideo))
+    }
+
+    /// Selects the best video-only format, 'bestvideo'.
+    pub fn best_video() -> Self {
+        Self::of(FormatKind::Best(Streams::VideoOnly))
+    }
+
+    /// Selects the worst video-only format, 'worstvideo'.
+    pub fn worst_video() -> Self {
+        Self::of(FormatKind::Worst(Streams::VideoOnly))
+    }
+
+    /// Selects the best audio-only format, 'bestaudio'.
+    pub fn best_audio() -> Self {
+        Self::of(FormatKind::Best(Streams::AudioOnly))
+    }
+
+    /// Selects the worst audio-only format, 'worstaudio'.
+    pub fn worst_audio() -> Self {
+        Self::of(FormatKind::Worst(Streams::AudioOnly))
+    }
+
+    /// Selects the format with the given ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the format, e.g. '303'.
+    pub fn id(id: impl AsRef<str>) -> Self {
+        Self::of(FormatKind::Id(id.as_ref().to_string()))
+    }
+
+    /// Selects a single format of the given kind, without filters.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of format to select.
+    pub fn of(kind: FormatKind) -> Self {
+        Self::Single {
+            kind,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Adds a filter to each format of the selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The filter the formats must match.
+    pub fn filter(self, filter: FormatFilter) -> Self {
+        match self {
+            Self::Single { kind, mut filters } => {
+                filters.push(filter);
+                Self::Single { kind, filters }
+            }
+            Self::Merge(video, audio) => Self::Merge(
+                Box::new(video.filter(filter.clone())),
+                Box::new(audio.filter(filter)),
+            ),
+            Self::Fallback(alternatives) => Self::Fallback(
+                alternatives
+                    .into_iter()
+                    .map(|alternative| alternative.filter(filter.clone()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Merges the format of this selector, the video one, with the format of the given selector, the audio one.
+    ///
+    /// # Arguments
+    ///
+    /// * `audio` - The selector of the audio format.
+    pub fn merge(self, audio: FormatSelector) -> Self {
+        Self::Merge(Box::new(self), Box::new(audio))
+    }
+
+    /// Falls back to the given selector if this one does not match.
+    ///
+    /// # Arguments
+    ///
+    /// * `fallback` - The selector to try next.
+    pub fn or(self, fallback: FormatSelector) -> Self {
+        let mut alternatives = match self {
+            Self::Fallback(alternatives) => alternatives,
+            selector => vec![selector],
+        };
+
+        alternatives.push(fallback);
+        Self::Fallback(alternatives)
+    }
+
+    /// Parses a selector from the '-f' syntax of 'yt-dlp', e.g. 'bv[height<=1080]+ba/b'.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The selector to parse.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the selector is invalid.
+    pub fn parse(selector: impl AsRef<str>) -> Result<Self> {
+        let mut parser = Parser::new(selector.as_ref());
+
+        let selector = parser.fallback()?;
+        parser.skip_whitespace();
+
+        match parser.peek() {
+            None => Ok(selector),
+            Some(character) => Err(parser.error(format!("unexpected '{}'", character))),
+        }
+    }
+
+    /// Selects the formats of the given video, or none if no alternative matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to select the formats of.
+    pub fn select<'a>(&self, video: &'a Video) -> Option<Selection<'a>> {
+        self.select_by(video, &FormatSorter::video(), &FormatSorter::audio())
+    }
+
+    /// Selects the formats of the given video, ranking the formats with the given comparators.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to select the formats of.
+    /// * `video_comparator` - The comparator ordering the formats with video.
+    /// * `audio_comparator` - The comparator ordering the audio-only formats.
+    pub fn select_by<'a>(
+        &self,
+        video: &'a Video,
+        video_comparator: &dyn FormatComparator,
+        audio_comparator: &dyn FormatComparator,
+    ) -> Option<Selection<'a>> {
+        let comparators = (video_comparator, audio_comparator);
+
+        match self {
+            Self::Single { kind, filters } => {
+                Self::select_single(kind, filters, video, comparators).map(Selection::Single)
+            }
+            Self::Merge(video_selector, audio_selector) => {
+                let Selection::Single(video_format) =
+                    video_selector.select_by(video, video_comparator, audio_comparator)?
+                else {
+                    return None;
+                };
+                let Selection::Single(audio_format) =
+                    audio_selector.select_by(video, video_comparator, audio_comparator)?
+                else {
+                    return None;
+                };
+
+                Some(Selection::Merge {
+                    video: video_format,
+                    audio: audio_format,
+                })
+            }
+            Self::Fallback(alternatives) => alternatives.iter().find_map(|alternative| {
+                alternative.select_by(video, video_comparator, audio_comparator)
+            }),
+        }
+    }
+
+    /// Selects the single format of the given kind, matching all the filters.
+    fn select_single<'a>(
+        kind: &FormatKind,
+        filters: &[FormatFilter],
+        video: &'a Video,
+        comparators: (&dyn FormatComparator, &dyn FormatComparator),
+    ) -> Option<&'a Format> {
+        let mut candidates = video
+            .formats
+            .iter()
+            .filter(|format| filters.iter().all(|filter| filter.matches(format)));
+
+        match kind {
+            FormatKind::Id(id) => candidates.rfind(|format| format.format_id == *id),
+            FormatKind::Best(streams) => candidates
+                .filter(|format| streams.matches(format))
+                .max_by(|a, b| streams.compare(comparators, a, b)),
+            FormatKind::Worst(streams) => candidates
+                .filter(|format| streams.matches(format))
+                .min_by(|a, b| streams.compare(comparators, a, b)),
+        }
+    }
+}
+
+impl FromStr for FormatSelector {
+    type Err = Error;
+
+    fn from_str(selector: &str) -> Result<Self> {
+        Self::parse(selector)
+    }
+}
+
+impl Display for FormatSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Single { kind, filters } => {
+                write!(f, "{}", kind)?;
+                filters
+                    .iter()
+                    .try_for_each(|filter| write!(f, "[{}]", filter))
+            }
+            Self::Merge(video, audio) => {
+                write_grouped(f, video)?;
+                f.write_str("+")?;
+                write_grouped(f, audio)
+            }
+            Self::Fallback(alternatives) => {
+                for (index, alternative) in alternatives.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str("/")?;
+                    }
+
+                    write!(f, "{}", alternative)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Writes a selector, with parentheses if it is a fallback.
+fn write_grouped(f: &mut Formatter<'_>, selector: &FormatSelector) -> std::fmt::Result {
+    match selector {
+        FormatSelector::Fallback(_) => write!(f, "({})", selector),
+        _ => write!(f, "{}", selector),
+    }
+}
+
+impl Display for FormatKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Best(streams) => write!(f, "best{}", streams.suffix()),
+            Self::Worst(streams) => write!(f, "worst{}", streams.suffix()),
+            Self::Id(id) => f.write_str(id),
+        }
+    }
+}
+
+impl Streams {
+    /// Checks if the format contains the streams.
+    pub fn matches(&self, format: &Format) -> bool {
+        let audio = format.codec_info.audio_codec.is_some();
+        let video = format.codec_info.video_codec.is_some();
+
+        match self {
+            Self::AudioAndVideo => audio && video,
+            Self::VideoOnly => video && !audio,
+            Self::Video => video,
+            Self::AudioOnly => audio && !video,
+            Self::Audio => audio,
+            Self::Any => audio || video,
+        }
+    }
+
+    /// Compares two formats with the streams, with the audio comparator for audio formats,
+    /// or with the video comparator first otherwise.
+    fn compare(
+        &self,
+        (video, audio): (&dyn FormatComparator, &dyn FormatComparator),
+        a: &Format,
+        b: &Format,
+    ) -> Ordering {
+        match self {
+            Self::AudioOnly | Self::Audio => audio.compare(a, b),
+            _ => video.compare(a, b).then_with(|| audio.compare(a, b)),
+        }
+    }
+
+    /// The suffix of the 'best' and 'worst' keywords selecting the streams.
+    fn suffix(&self) -> &'static str {
+        match self {
+            Self::AudioAndVideo => "",
+            Self::VideoOnly => "video",
+            Self::Video => "video*",
+            Self::AudioOnly => "audio",
+            Self::Audio => "audio*",
+            Self::Any => "*",
+        }
+    }
+
+    /// Parses the suffix of a 'best' or 'worst' keyword, in its long or short form.
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "" => Some(Self::AudioAndVideo),
+            "video" | "v" => Some(Self::VideoOnly),
+            "video*" | "v*" => Some(Self::Video),
+            "audio" | "a" => Some(Self::AudioOnly),
+            "audio*" | "a*" => Some(Self::Audio),
+            "*" => Some(Self::Any),
+            _ => None,
+        }
+    }
+}
+
+impl FormatFilter {
+    /// Creates a new filter, comparing the field with the value.
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The field to compare.
+    /// * `comparison` - The comparison to apply.
+    /// * `value` - The value to compare the field with.
+    pub fn new(field: FormatField, comparison: Comparison, value: impl Into<FilterValue>) -> Self {
+        Self {
+            field,
+            comparison,
+            value: value.into(),
+            negated: false,
+            optional: false,
+        }
+    }
+
+    /// Inverts the comparison of the filter.
+    pub fn negate(mut self) -> Self {
+        self.negated = !self.negated;
+        self
+    }
+
+    /// Lets the formats with an unknown value match the filter.
+    pub fn or_unknown(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// Checks if the format matches the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format to check.
+    pub fn matches(&self, format: &Format) -> bool {
+        let matched = match &self.value {
+            FilterValue::Number(expected) => self
+                .field
+                .number(format)
+                .map(|actual| self.comparison.numbers(actual, *expected)),
+            FilterValue::Text(expected) => self
+                .field
+                .text(format)
+                .map(|actual| self.comparison.texts(&actual, expected)),
+        };
+
+        match matched {
+            Some(matched) => matched != self.negated,
+            None => self.optional,
+        }
+    }
+}
+
+impl Display for FormatFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let negation = if self.negated { "!" } else { "" };
+        let optional = if self.optional { "?" } else { "" };
+
+        write!(
+            f,
+            "{}{}{}{}{}",
+            self.field.name(),
+            negation,
+            self.comparison.operator(),
+            optional,
+            self.value
+        )
+    }
+}
+
+impl FormatField {
+    /// All the fields, to parse them by name.
+    const ALL: [FormatField; 18] = [
+        Self::Height,
+        Self::Width,
+        Self::Fps,
+        Self::FileSize,
+        Self::TotalBitrate,
+        Self::VideoBitrate,
+        Self::AudioBitrate,
+        Self::SampleRate,
+        Self::AudioChannels,
+        Self::VideoCodec,
+        Self::AudioCodec,
+        Self::Extension,
+        Self::Container,
+        Self::Protocol,
+        Self::DynamicRange,
+        Self::FormatId,
+        Self::Language,
+        Self::FormatNote,
+    ];
+
+    /// Returns the name of the field, as used by 'yt-dlp'.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Height => "height",
+            Self::Width => "width",
+            Self::Fps => "fps",
+            Self::FileSize => "filesize",
+            Self::TotalBitrate => "tbr",
+            Self::VideoBitrate => "vbr",
+            Self::AudioBitrate => "abr",
+            Self::SampleRate => "asr",
+            Self::AudioChannels => "audio_channels",
+            Self::VideoCodec => "vcodec",
+            Self::AudioCodec => "acodec",
+            Self::Extension => "ext",
+            Self::Container => "container",
+            Self::Protocol => "protocol",
+            Self::DynamicRange => "dynamic_range",
+            Self::FormatId => "format_id",
+            Self::Language => "language",
+            Self::FormatNote => "format_note",
+        }
+    }
+
+    /// Parses a field from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.name() == name)
+    }
+
+    /// Checks if the field is numeric.
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Self::Height
+                | Self::Width
+                | Self::Fps
+                | Self::FileSize
+                | Self::TotalBitrate
+                | Self::VideoBitrate
+                | Self::AudioBitrate
+                | Self::SampleRate
+                | Self::AudioChannels
+        )
+    }
+
+    /// Returns the numeric value of the field for the format, if known.
+    fn number(&self, format: &Format) -> Option<f64> {
+        match self {
+            Self::Height => format.video_resolution.height.map(|height| height as f64),
+            Self::Width => format.video_resolution.width.map(|width| width as f64),
+            Self::Fps => format.video_resolution.fps,
+            Self::FileSize => format.file_info.size().map(|size| size as f64),
+            Self::TotalBitrate => format.rates_info.total_rate,
+            Self::VideoBitrate => format.rates_info.video_rate,
+            Self::AudioBitrate => format.rates_info.audio_rate,
+            Self::SampleRate => format.codec_info.asr.map(|asr| asr as f64),
+            Self::AudioChannels => format
+                .codec_info
+                .audio_channels
+                .map(|channels| channels as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the text value of the field for the format, if known.
+    fn text(&self, format: &Format) -> Option<String> {
+        match self {
+            Self::VideoCodec => format.codec_info.video_codec.clone(),
+            Self::AudioCodec => format.codec_info.audio_codec.clone(),
+            Self::Extension => extension_name(&format.download_info.ext).map(String::from),
+            Self::Container => format
+                .container
+                .as_ref()
+                .and_then(container_name)
+                .map(String::from),
+            Self::Protocol => protocol_name(&format.protocol).map(String::from),
+            Self::DynamicRange => format
+                .quality_info
+                .dynamic_range
+                .as_ref()
+                .and_then(dynamic_range_name)
+                .map(String::from),
+            Self::FormatId => Some(format.format_id.clone()),
+            Self::Language => format.language.clone(),
+            Self::FormatNote => format.format_note.clone(),
+            _ => None,
+        }
+    }
+}
+
+impl Comparison {
+    /// The operators, longest first so they can be parsed greedily.
+    const OPERATORS: [(&'static str, Comparison); 8] = [
+        ("<=", Self::LessOrEqual),
+        (">=", Self::GreaterOrEqual),
+        ("^=", Self::StartsWith),
+        ("$=", Self::EndsWith),
+        ("*=", Self::Contains),
+        ("<", Self::Less),
+        (">", Self::Greater),
+        ("=", Self::Equal),
+    ];
+
+    /// Returns the operator of the comparison.
+    pub fn operator(&self) -> &'static str {
+        Self::OPERATORS
+            .iter()
+            .find(|(_, comparison)| comparison == self)
+            .map(|(operator, _)| *operator)
+            .unwrap_or("=")
+    }
+
+    /// Checks if the comparison applies to numeric fields.
+    pub fn is_numeric(&self) -> bool {
+        !matches!(self, Self::StartsWith | Self::EndsWith | Self::Contains)
+    }
+
+    /// Checks if the comparison applies to text fields.
+    pub fn is_textual(&self) -> bool {
+        matches!(
+            self,
+            Self::Equal | Self::StartsWith | Self::EndsWith | Self::Contains
+        )
+    }
+
+    /// Compares two numbers.
+    fn numbers(&self, actual: f64, expected: f64) -> bool {
+        match self {
+            Self::Equal => actual == expected,
+            Self::Less => actual < expected,
+            Self::LessOrEqual => actual <= expected,
+            Self::Greater => actual > expected,
+            Self::GreaterOrEqual => actual >= expected,
+            _ => false,
+        }
+    }
+
+    /// Compares two texts.
+    fn texts(&self, actual: &str, expected: &str) -> bool {
+        match self {
+            Self::Equal => actual == expected,
+            Self::StartsWith => actual.starts_with(expected),
+            Self::EndsWith => actual.ends_with(expected),
+            Self::Contains => actual.contains(expected),
+            _ => false,
+        }
+    }
+}
+
+impl From<f64> for FilterValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(value: i64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<i32> for FilterValue {
+    fn from(value: i32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl Display for FilterValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{}", number),
+            Self::Text(text) if text.contains([']', '"', '\'']) => write!(f, "\"{}\"", text),
+            Self::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+impl<'a> Selection<'a> {
+    /// Returns the selected formats, the video one first.
+    pub fn formats(&self) -> Vec<&'a Format> {
+        match self {
+            Self::Single(format) => vec![format],
+            Self::Merge { video, audio } => vec![video, audio],
+        }
+    }
+}
+
+/// Returns the name of the extension, as used by 'yt-dlp'.
+pub(crate) fn extension_name(extension: &Extension) -> Option<&'static str> {
+    match extension {
+        Extension::M4A => Some("m4a"),
+        Extension::Mp4 => Some("mp4"),
+        Extension::Webm => Some("webm"),
+        Extension::Mhtml => Some("mhtml"),
+        Extension::None => Some("none"),
+        Extension::Unknown => None,
+    }
+}
+
+/// Returns the name of the container, as used by 'yt-dlp'.
+fn container_name(container: &Container) -> Option<&'static str> {
+    match container {
+        Container::Webm => Some("webm_dash"),
+        Container::M4A => Some("m4a_dash"),
+        Container::Mp4 => Some("mp4_dash"),
+        Container::Unknown => None,
+    }
+}
+
+/// Returns the name of the protocol, as used by 'yt-dlp'.
+fn protocol_name(protocol: &Protocol) -> Option<&'static str> {
+    match protocol {
+        Protocol::Https => Some("https"),
+        Protocol::M3U8 => Some("m3u8"),
+        Protocol::M3U8Native => Some("m3u8_native"),
+        Protocol::HttpDashSegments => Some("http_dash_segments"),
+        Protocol::Mhtml => Some("mhtml"),
+        Protocol::Unknown => None,
+    }
+}
+
+/// Returns the name of the dynamic range, as used by 'yt-dlp'.
+fn dynamic_range_name(dynamic_range: &DynamicRange) -> Option<&'static str> {
+    match dynamic_range {
+        DynamicRange::SDR => Some("SDR"),
+        DynamicRange::HDR => Some("HDR"),
+        DynamicRange::Unknown => None,
+    }
+}
+
+/// Parses a number with an optional unit, e.g. '500M' or '1.5GiB'.
+/// Decimal units ('k', 'M', 'G', 'T', optionally followed by 'B') are powers of 1000, binary units ('KiB', 'MiB', ...) of 1024.
+fn parse_number(value: &str) -> Option<f64> {
+    let split = value
+        .find(|character: char| {
+            !(character.is_ascii_digit() || character == '.' || character == '-')
+        })
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let multiplier = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "t" | "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+
+    number.parse::<f64>().ok().map(|number| number * multiplier)
+}
+
+/// A recursive descent parser for the selector syntax.
+struct Parser {
+    /// The characters of the selector.
+    characters: Vec<char>,
+    /// The position of the next character to read.
+    position: usize,
+}
+
+impl Parser {
+    /// Creates a new parser for the given selector.
+    fn new(selector: &str) -> Self {
+        Self {
+            characters: selector.chars().collect(),
+            position: 0,
+        }
+    }
+
+    /// Parses alternatives separated by '/'.
+    fn fallback(&mut self) -> Result<FormatSelector> {
+        let mut alternatives = vec![self.merge()?];
+
+        while self.eat('/') {
+            alternatives.push(self.merge()?);
+        }
+
+        match alternatives.len() {
+            1 => Ok(alternatives.remove(0)),
+            _ => Ok(FormatSelector::Fallback(alternatives)),
+        }
+    }
+
+    /// Parses a term, or two terms merged with '+'.
+    fn merge(&mut self) -> Result<FormatSelector> {
+        let video = self.term()?;
+        if !self.eat('+') {
+            return Ok(video);
+        }
+
+        let audio = self.term()?;
+        if self.eat('+') {
+            return Err(self.error("only two formats can be merged"));
+        }
+
+        Ok(video.merge(audio))
+    }
+
+    /// Parses a keyword, a format ID or a group between parentheses, followed by filters.
+    fn term(&mut self) -> Result<FormatSelector> {
+        let mut selector = if self.eat('(') {
+            let group = self.fallback()?;
+            if !self.eat(')') {
+                return Err(self.error("expected ')'"));
+            }
+
+            group
+        } else {
+            self.single()?
+        };
+
+        while self.eat('[') {
+            let filter = self.filter()?;
+            selector = selector.filter(filter);
+        }
+
+        Ok(selector)
+    }
+
+    /// Parses a keyword or a format ID. Filters without a keyword apply to 'best'.
+    fn single(&mut self) -> Result<FormatSelector> {
+        self.skip_whitespace();
+        let word = self.take_while(|character| {
+            character.is_ascii_alphanumeric() || matches!(character, '_' | '-' | '.' | '*')
+        });
+
+        if word.is_empty() {
+            return match self.peek() {
+                Some('[') => Ok(FormatSelector::best()),
+                Some(character) => Err(self.error(format!("unexpected '{}'", character))),
+                None => Err(self.error("expected a format")),
+            };
+        }
+
+        let kind = if let Some(suffix) = keyword_suffix(&word, "best", "b") {
+            Streams::from_suffix(suffix).map(FormatKind::Best)
+        } else if let Some(suffix) = keyword_suffix(&word, "worst", "w") {
+            Streams::from_suffix(suffix).map(FormatKind::Worst)
+        } else {
+            None
+        };
+
+        match kind {
+            Some(kind) => Ok(FormatSelector::of(kind)),
+            None if word.contains('*') => Err(self.error(format!("unknown keyword '{}'", word))),
+            None => Ok(FormatSelector::id(word)),
+        }
+    }
+
+    /// Parses a filter, after its opening bracket.
+    fn filter(&mut self) -> Result<FormatFilter> {
+        self.skip_whitespace();
+        let name =
+            self.take_while(|character| character.is_ascii_alphanumeric() || character == '_');
+        let field = FormatField::from_name(&name)
+            .ok_or_else(|| self.error(format!("unknown field '{}'", name)))?;
+
+        self.skip_whitespace();
+        let negated = self.eat_raw('!');
+        let comparison = self.comparison()?;
+        let optional = self.eat_raw('?');
+
+        let raw = self.value()?;
+        if !self.eat(']') {
+            return Err(self.error("expected ']'"));
+        }
+
+        let value = match field.is_numeric() {
+            true if !comparison.is_numeric() || (negated && comparison != Comparison::Equal) => {
+                return Err(self.error(format!(
+                    "invalid comparison '{}{}' for numeric field '{}'",
+                    ternary!(negated, "!", ""),
+                    comparison.operator(),
+                    name
+                )));
+            }
+            true => parse_number(&raw)
+                .map(FilterValue::Number)
+                .ok_or_else(|| self.error(format!("invalid number '{}'", raw)))?,
+            false if !comparison.is_textual() => {
+                return Err(self.error(format!(
+                    "invalid comparison '{}' for text field '{}'",
+                    comparison.operator(),
+                    name
+                )));
+            }
+            false => FilterValue::Text(raw),
+        };
+
+        Ok(FormatFilter {
+            field,
+            comparison,
+            value,
+            negated,
+            optional,
+        })
+    }
+
+    /// Parses a comparison operator, after its optional negation.
+    fn comparison(&mut self) -> Result<Comparison> {
+        for (operator, comparison) in Comparison::OPERATORS {
+            let characters: Vec<char> = operator.chars().collect();
+            let end = self.position + characters.len();
+
+            if self.characters.get(self.position..end) == Some(&characters[..]) {
+                self.position = end;
+                return Ok(comparison);
+            }
+        }
+
+        Err(self.error("expected a comparison operator"))
+    }
+
+    /// Parses a filter value, quoted or not, up to the closing bracket.
+    fn value(&mut self) -> Result<String> {
+        self.skip_whitespace();
+
+        let Some(quote) = self
+            .peek()
+            .filter(|character| matches!(character, '"' | '\''))
+        else {
+            let value = self.take_while(|character| character != ']');
+            return Ok(value.trim().to_string());
+        };
+
+        self.position += 1;
+        let value = self.take_while(|character| character != quote);
+        if !self.eat_raw(quote) {
+            return Err(self.error("unterminated quote"));
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the next character, without consuming it.
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    /// Consumes the given character if it is next, after skipping the whitespace.
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        self.eat_raw(expected)
+    }
+
+    /// Consumes the given character if it is next.
+    fn eat_raw(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += 1;
+        }
+
+        found
+    }
+
+    /// Consumes the characters while they match the predicate.
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+
+        self.characters[start..self.position].iter().collect()
+    }
+
+    /// Consumes the whitespace.
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Creates an error at the current position.
+    fn error(&self, reason: impl AsRef<str>) -> Error {
+        let selector: String = self.characters.iter().collect();
+
+        Error::Selector(format!(
+            "{} at position {} in '{}'",
+            reason.as_ref(),
+            self.position,
+            selector
+        ))
+    }
+}
+
+/// Returns the suffix of a 'best' or 'worst' keyword, in its long or short form, e.g. 'video*' for 'bestvideo*' or 'v*' for 'bv*'.
+fn keyword_suffix<'a>(word: &'a str, long: &str, short: &str) -> Option<&'a str> {
+    word.strip_prefix(long).or_else(|| {
+        word.strip_prefix(short)
+            .filter(|suffix| Streams::from_suffix(suffix).is_some())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Creates a format with the given codecs, 'none' for a missing stream.
+    fn format(
+        id: &str,
+        ext: &str,
+        vcodec: &str,
+        acodec: &str,
+        height: Option<i64>,
+        tbr: f64,
+    ) -> Format {
+        let format = json!({
+            "format": id,
+            "format_id": id,
+            "ext": ext,
+            "vcodec": vcodec,
+            "acodec": acodec,
+            "height": height,
+            "resolution": height.map_or(String::from("audio only"), |height| format!("{}p", height)),
+            "tbr": tbr,
+            "vbr": ternary!(acodec == "none", Some(tbr), None),
+            "abr": ternary!(acodec == "none", None, Some(tbr.min(160.0))),
+            "filesize": (tbr * 1000.0) as i64,
+            "protocol": "https",
+            "url": format!("https://example.com/{}", id),
+            "http_headers": {
+                "User-Agent": "",
+                "Accept": "",
+                "Accept-Language": "",
+                "Sec-Fetch-Mode": "",
+            },
+        });
+
+        serde_json::from_value(format).unwrap()
+    }
+
+    fn video() -> Video {
+        let formats = vec![
+            format("18", "mp4", "avc1.42001E", "mp4a.40.2", Some(360), 500.0),
+            format("22", "mp4", "avc1.64001F", "mp4a.40.2", Some(720), 1500.0),
+            format("136", "mp4", "avc1.4d401f", "none", Some(720), 2000.0),
+            format("137", "mp4", "avc1.640028", "none", Some(1080), 4000.0),
+            format("248", "webm", "vp9", "none", Some(1080), 2500.0),
+            format("140", "m4a", "none", "mp4a.40.2", None, 128.0),
+            format("251", "webm", "none", "opus", None, 160.0),
+        ];
+
+        let video = json!({
+            "id": "dQw4w9WgXcQ",
+            "title": "Rick Astley - Never Gonna Give You Up",
+            "thumbnail": "",
+            "description": "",
+            "availability": "public",
+            "timestamp": 1256453853,
+            "view_count": 0,
+            "channel": "Rick Astley",
+            "channel_id": "UCuAXFkgsw1L7xaCfnd5JJOw",
+            "channel_url": "https://www.youtube.com/channel/UCuAXFkgsw1L7xaCfnd5JJOw",
+            "channel_follower_count": 0,
+            "formats": formats,
+            "thumbnails": [],
+            "automatic_captions": {},
+            "tags": [],
+            "categories": [],
+            "age_limit": 0,
+            "playable_in_embed": true,
+            "extractor": "youtube",
+            "extractor_key": "Youtube",
+            "_version": {
+                "version": "2024.10.22",
+                "release_git_head": "",
+                "repository": "yt-dlp/yt-dlp",
+            },
+        });
+
+        serde_json::from_value(video).unwrap()
+    }
+
+    /// Returns the IDs of the formats selected in the test video.
+    fn select(selector: &str) -> Option<Vec<String>> {
+        let video = video();
+        let selection = FormatSelector::parse(selector).unwrap().select(&video)?;
+
+        let ids = selection
+            .formats()
+            .into_iter()
+            .map(|format| format.format_id.clone());
+        Some(ids.collect())
+    }
+
+    #[test]
+    fn parse_precedence() {
+        // '+' binds tighter than '/'
+        assert_eq!(
+            FormatSelector::parse("bv+ba/b").unwrap(),
+            FormatSelector::best_video()
+                .merge(FormatSelector::best_audio())
+                .or(FormatSelector::best())
+        );
+
+        // Parentheses group alternatives
+        assert_eq!(
+            FormatSelector::parse("(bv/b)+ba").unwrap(),
+            FormatSelector::best_video()
+                .or(FormatSelector::best())
+                .merge(FormatSelector::best_audio())
+        );
+
+        // Keywords, in their long and short forms
+        assert_eq!(
+            FormatSelector::parse("bestvideo*").unwrap(),
+            FormatSelector::parse("bv*").unwrap()
+        );
+        assert_eq!(
+            FormatSelector::parse("wa").unwrap(),
+            FormatSelector::worst_audio()
+        );
+        assert_eq!(
+            FormatSelector::parse("137").unwrap(),
+            FormatSelector::id("137")
+        );
+    }
+
+    #[test]
+    fn parse_filters() {
+        assert_eq!(
+            FormatSelector::parse("bv[height<=1080][vcodec^=avc1]").unwrap(),
+            FormatSelector::best_video()
+                .filter(FormatFilter::new(
+                    FormatField::Height,
+                    Comparison::LessOrEqual,
+                    1080
+                ))
+                .filter(FormatFilter::new(
+                    FormatField::VideoCodec,
+                    Comparison::StartsWith,
+                    "avc1"
+                ))
+        );
+
+        // Filters without keyword apply to 'best', and sizes have units
+        assert_eq!(
+            FormatSelector::parse("[filesize<1.5MiB]").unwrap(),
+            FormatSelector::best().filter(FormatFilter::new(
+                FormatField::FileSize,
+                Comparison::Less,
+                1.5 * 1024.0 * 1024.0
+            ))
+        );
+
+        // Negated, optional and quoted filters
+        assert_eq!(
+            FormatSelector::parse("ba[acodec!=opus][abr>=?128][format_note='Medium [en]']")
+                .unwrap(),
+            FormatSelector::best_audio()
+                .filter(
+                    FormatFilter::new(FormatField::AudioCodec, Comparison::Equal, "opus").negate()
+                )
+                .filter(
+                    FormatFilter::new(FormatField::AudioBitrate, Comparison::GreaterOrEqual, 128)
+                        .or_unknown()
+                )
+                .filter(FormatFilter::new(
+                    FormatField::FormatNote,
+                    Comparison::Equal,
+                    "Medium [en]"
+                ))
+        );
+
+        // Filters after a group apply to each of its formats
+        let height = FormatFilter::new(FormatField::Height, Comparison::LessOrEqual, 720);
+        assert_eq!(
+            FormatSelector::parse("(bv/b)[height<=720]").unwrap(),
+            FormatSelector::best_video()
+                .filter(height.clone())
+                .or(FormatSelector::best().filter(height))
+        );
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let selectors = [
+            "bestvideo[height<=1080][vcodec^=avc1]+bestaudio/best[filesize<500000000]",
+            "(bestvideo/best)+bestaudio[acodec!=opus]",
+            "worstaudio*[abr>=?128]",
+            "137+140/22",
+        ];
+
+        for selector in selectors {
+            let parsed = FormatSelector::parse(selector).unwrap();
+            assert_eq!(parsed.to_string(), selector);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let invalid = [
+            "",
+            "bv+ba+b",
+            "(bv/b",
+            "bv)",
+            "bv[height<=1080",
+            "bv[resolution=1080]",
+            "bv[height<=tall]",
+            "bv[height^=1]",
+            "bv[height!<1080]",
+            "bv[vcodec<avc1]",
+            "ba[format_note='Medium]",
+            "bestfoo*",
+            "bv/",
+        ];
+
+        for selector in invalid {
+            let error = FormatSelector::parse(selector).unwrap_err();
+            assert!(matches!(error, Error::Selector(_)), "{}", selector);
+        }
+    }
+
+    #[test]
+    fn select_formats() {
+        let ids = |ids: &[&str]| Some(ids.iter().map(|id| id.to_string()).collect());
+
+        assert_eq!(select("b"), ids(&["22"]));
+        assert_eq!(select("w"), ids(&["18"]));
+        assert_eq!(select("bv+ba"), ids(&["137", "251"]));
+        assert_eq!(select("bv[ext=webm]+ba[ext=m4a]"), ids(&["248", "140"]));
+        assert_eq!(select("bv[height<=720]+wa"), ids(&["136", "140"]));
+        assert_eq!(select("bv*[height<=720]"), ids(&["136"]));
+        assert_eq!(select("ba[acodec!=opus]"), ids(&["140"]));
+        assert_eq!(select("b[filesize<1M]"), ids(&["18"]));
+        assert_eq!(select("248"), ids(&["248"]));
+    }
+
+    #[test]
+    fn select_fallbacks() {
+        let ids = |ids: &[&str]| Some(ids.iter().map(|id| id.to_string()).collect());
+
+        // The first matching alternative is selected
+        assert_eq!(select("bv[height>1080]+ba/b"), ids(&["22"]));
+        assert_eq!(select("999/bv[vcodec^=vp9]"), ids(&["248"]));
+        // A merge only matches if both of its formats do
+        assert_eq!(select("bv+ba[acodec=flac]/ba"), ids(&["251"]));
+        // The formats with an unknown value match the optional filters only
+        assert_eq!(select("ba[height<=?720]"), ids(&["251"]));
+        assert_eq!(select("ba[height<=720]"), None);
+    }
+}
@@ -0,0 +1,820 @@
+//! Format sorting, to decide which format is the best.
+//!
+//! A [`FormatComparator`] orders formats from the worst to the best.
+//! The [`FormatSorter`] implements it with a list of sort keys, compared in order until one of them differs,
+//! and can be parsed from a subset of the '-S' syntax of 'yt-dlp'.
+
+use crate::error::{Error, Result};
+use crate::model::format::{DynamicRange, Format};
+use crate::model::selector::extension_name;
+use crate::ternary;
+use ordered_float::OrderedFloat;
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+/// Orders formats from the worst to the best.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::model::format::Format;
+/// # use yt_dlp::model::sorting::FormatComparator;
+/// # use std::cmp::Ordering;
+/// /// Prefers the formats with the most frames per second.
+/// #[derive(Debug)]
+/// struct Smoothest;
+///
+/// impl FormatComparator for Smoothest {
+///     fn compare(&self, a: &Format, b: &Format) -> Ordering {
+///         let a_fps = a.video_resolution.fps.unwrap_or(0.0);
+///         let b_fps = b.video_resolution.fps.unwrap_or(0.0);
+///
+///         a_fps.total_cmp(&b_fps)
+///     }
+/// }
+/// ```
+pub trait FormatComparator: Debug + Send + Sync {
+    /// Compares two formats, the greater one being the best.
+    fn compare(&self, a: &Format, b: &Format) -> Ordering;
+}
+
+/// The keys formats can be sorted by. Each key orders the formats from the worst to the best.
+/// Missing numeric values count as 0, like in [`Video::compare_video_formats`](crate::model::Video::compare_video_formats).
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortKey {
+    /// Prefers the higher relative quality score, 'quality'.
+    Quality,
+    /// Prefers the higher video height, 'res'.
+    Height,
+    /// Prefers the higher frame rate, 'fps'.
+    Fps,
+    /// Prefers the higher total bitrate, 'tbr'.
+    TotalBitrate,
+    /// Prefers the higher video bitrate, 'vbr'.
+    VideoBitrate,
+    /// Prefers the higher audio bitrate, 'abr'.
+    AudioBitrate,
+    /// Prefers the higher audio sample rate, 'asr'.
+    SampleRate,
+    /// Prefers more audio channels, 'channels'.
+    AudioChannels,
+    /// Prefers the larger file size, 'size'.
+    FileSize,
+    /// Prefers the smaller file size, the formats with an unknown size being the worst, '+size'.
+    PreferSmallerSize,
+    /// Prefers the video codecs starting with the given prefixes, the first one being the best, 'vcodec:avc1'.
+    PreferVideoCodec(Vec<String>),
+    /// Prefers the audio codecs starting with the given prefixes, the first one being the best, 'acodec:opus'.
+    PreferAudioCodec(Vec<String>),
+    /// Prefers the given file extensions, e.g. 'mp4', the first one being the best, 'ext:mp4'.
+    PreferContainer(Vec<String>),
+    /// Prefers the standard dynamic range over the high dynamic range, '+hdr'.
+    PreferSdr,
+    /// Prefers the formats in the given language, 'lang:en'.
+    PreferLanguage(String),
+    /// Prefers the highest value of the numeric key up to the given limit, then the lowest value above it,
+    /// e.g. the highest resolution up to 1080p, 'res:1080'.
+    AtMost(Box<SortKey>, f64),
+    /// Prefers the lowest value of the numeric key from the given limit, then the highest value below it,
+    /// e.g. the lowest resolution from 480p, '+res:480'.
+    AtLeast(Box<SortKey>, f64),
+    /// Reverses the order of the given key, e.g. '+fps' to prefer the lowest frame rate.
+    Reverse(Box<SortKey>),
+}
+
+/// Sorts formats by a list of keys, compared in order until one of them differs.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::model::sorting::{FormatSorter, SortKey};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let sorter = FormatSorter::new()
+///     .by(SortKey::PreferSdr)
+///     .by(SortKey::PreferVideoCodec(vec!["avc1".to_string(), "vp9".to_string()]))
+///     .by(SortKey::Height)
+///     .by(SortKey::PreferSmallerSize);
+///
+/// // The same sorter, with the '-S' syntax of 'yt-dlp'
+/// let sorter = FormatSorter::parse("+hdr,vcodec:avc1:vp9,res,+size")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormatSorter {
+    /// The keys to sort by, the first one being the most important.
+    pub keys: Vec<SortKey>,
+}
+
+impl FormatSorter {
+    /// Creates a new sorter, without keys, considering all the formats equal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The default sorter of video formats: "quality", "video resolution", "fps", "video bitrate".
+    pub fn video() -> Self {
+        Self::new()
+            .by(SortKey::Quality)
+            .by(SortKey::Height)
+            .by(SortKey::Fps)
+            .by(SortKey::VideoBitrate)
+    }
+
+    /// The default sorter of audio formats: "quality", "audio bitrate", "sample rate", "audio channels".
+    pub fn audio() -> Self {
+        Self::new()
+            .by(SortKey::Quality)
+            .by(SortKey::AudioBitrate)
+            .by(SortKey::SampleRate)
+            .by(SortKey::AudioChannels)
+    }
+
+    /// Adds a key to sort by, less important than the previous ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key to sort by.
+    pub fn by(mut self, key: SortKey) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Adds the keys of the given sorter, less important than the current ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `sorter` - The sorter to append.
+    pub fn then(mut self, sorter: FormatSorter) -> Self {
+        self.keys.extend(sorter.keys);
+        self
+    }
+
+    /// Parses a sorter from a subset of the '-S' syntax of 'yt-dlp', e.g. 'res,fps,vcodec:avc1,+size'.
+    ///
+    /// The keys are separated by commas. A '+' prefix reverses the order of a key.
+    /// The supported keys are 'quality', 'res', 'fps', 'tbr', 'vbr', 'abr', 'asr', 'channels', 'size', 'hdr',
+    /// and 'vcodec', 'acodec', 'ext' and 'lang' followed by the preferred values separated by colons.
+    /// The numeric keys, all but 'quality', take an optional limit, e.g. 'res:1080' or 'size:500M',
+    /// see [`SortKey::AtMost`] and [`SortKey::AtLeast`].
+    ///
+    /// # Arguments
+    ///
+    /// * `sorting` - The sorting to parse.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a key is unknown, misses its values, or has an invalid limit.
+    pub fn parse(sorting: impl AsRef<str>) -> Result<Self> {
+        let mut sorter = Self::new();
+
+        for key in sorting.as_ref().split(',').map(str::trim) {
+            if key.is_empty() {
+                continue;
+            }
+
+            sorter = sorter.by(SortKey::parse(key)?);
+        }
+
+        Ok(sorter)
+    }
+}
+
+impl FromStr for FormatSorter {
+    type Err = Error;
+
+    fn from_str(sorting: &str) -> Result<Self> {
+        Self::parse(sorting)
+    }
+}
+
+impl FormatComparator for FormatSorter {
+    fn compare(&self, a: &Format, b: &Format) -> Ordering {
+        self.keys
+            .iter()
+            .map(|key| key.compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl SortKey {
+    /// Parses a single key of the '-S' syntax.
+    fn parse(key: &str) -> Result<Self> {
+        let (reversed, key) = match key.strip_prefix('+') {
+            Some(key) => (true, key),
+            None => (false, key),
+        };
+
+        let mut parts = key.split(':');
+        let name = parts.next().unwrap_or_default();
+        let values: Vec<String> = parts
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+            .collect();
+
+        let preference = |key: fn(Vec<String>) -> SortKey| match values.is_empty() {
+            true => Err(Error::Sorting(format!(
+                "'{}' needs a preferred value",
+                name
+            ))),
+            false => Ok(key(values.clone())),
+        };
+
+        let parsed = match name {
+            "quality" => SortKey::Quality,
+            "res" | "height" => SortKey::Height,
+            "fps" => SortKey::Fps,
+            "tbr" | "br" => SortKey::TotalBitrate,
+            "vbr" => SortKey::VideoBitrate,
+            "abr" => SortKey::AudioBitrate,
+            "asr" => SortKey::SampleRate,
+            "channels" => SortKey::AudioChannels,
+            "size" | "filesize" => SortKey::FileSize,
+            "hdr" => SortKey::PreferSdr,
+            "vcodec" => return Self::reverse(preference(SortKey::PreferVideoCodec)?, reversed),
+            "acodec" => return Self::reverse(preference(SortKey::PreferAudioCodec)?, reversed),
+            "ext" => return Self::reverse(preference(SortKey::PreferContainer)?, reversed),
+            "lang" => {
+                let key = preference(|values| SortKey::PreferLanguage(values[0].clone()))?;
+                return Self::reverse(key, reversed);
+            }
+            _ => return Err(Error::Sorting(format!("unknown key '{}'", name))),
+        };
+
+        let limit = match values.as_slice() {
+            [] => None,
+            [limit] if parsed.is_numeric() => Some(parse_limit(&parsed, limit).ok_or(
+                Error::Sorting(format!("invalid limit '{}' of '{}'", limit, name)),
+            )?),
+            _ if parsed.is_numeric() => {
+                return Err(Error::Sorting(format!("'{}' takes a single limit", name)));
+            }
+            _ => return Err(Error::Sorting(format!("'{}' takes no value", name))),
+        };
+
+        Ok(match (parsed, limit, reversed) {
+            (key, Some(limit), false) => SortKey::AtMost(Box::new(key), limit),
+            (key, Some(limit), true) => SortKey::AtLeast(Box::new(key), limit),
+            // The file size and the dynamic range have dedicated keys, as their unknown values are the worst
+            (SortKey::FileSize, None, true) => SortKey::PreferSmallerSize,
+            (SortKey::PreferSdr, None, true) => SortKey::PreferSdr,
+            (SortKey::PreferSdr, None, false) => SortKey::Reverse(Box::new(SortKey::PreferSdr)),
+            (key, None, reversed) => Self::reverse(key, reversed)?,
+        })
+    }
+
+    /// Reverses the given key if requested.
+    fn reverse(key: SortKey, reversed: bool) -> Result<Self> {
+        match reversed {
+            true => Ok(SortKey::Reverse(Box::new(key))),
+            false => Ok(key),
+        }
+    }
+
+    /// Checks if the key compares a numeric value, which can be limited.
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Self::Height
+                | Self::Fps
+                | Self::TotalBitrate
+                | Self::VideoBitrate
+                | Self::AudioBitrate
+                | Self::SampleRate
+                | Self::AudioChannels
+                | Self::FileSize
+        )
+    }
+
+    /// Returns the numeric value compared by the key, missing values counting as 0, or none if the key is not numeric.
+    fn number(&self, format: &Format) -> Option<f64> {
+        let number = match self {
+            Self::Height => format.video_resolution.height.unwrap_or(0) as f64,
+            Self::Fps => format.video_resolution.fps.unwrap_or(0.0),
+            Self::TotalBitrate => format.rates_info.total_rate.unwrap_or(0.0),
+            Self::VideoBitrate => format.rates_info.video_rate.unwrap_or(0.0),
+            Self::AudioBitrate => format.rates_info.audio_rate.unwrap_or(0.0),
+            Self::SampleRate => format.codec_info.asr.unwrap_or(0) as f64,
+            Self::AudioChannels => format.codec_info.audio_channels.unwrap_or(0) as f64,
+            Self::FileSize => format.file_info.size().unwrap_or(0) as f64,
+            _ => return None,
+        };
+
+        Some(number)
+    }
+
+    /// Compares two formats by this key, the greater one being the best.
+    pub fn compare(&self, a: &Format, b: &Format) -> Ordering {
+        match self {
+            Self::Quality => compare_numbers(a.quality_info.quality, b.quality_info.quality),
+            Self::Height => a
+                .video_resolution
+                .height
+                .unwrap_or(0)
+                .cmp(&b.video_resolution.height.unwrap_or(0)),
+            Self::Fps => compare_numbers(a.video_resolution.fps, b.video_resolution.fps),
+            Self::TotalBitrate => compare_numbers(a.rates_info.total_rate, b.rates_info.total_rate),
+            Self::VideoBitrate => compare_numbers(a.rates_info.video_rate, b.rates_info.video_rate),
+            Self::AudioBitrate => compare_numbers(a.rates_info.audio_rate, b.rates_info.audio_rate),
+            Self::SampleRate => a
+                .codec_info
+                .asr
+                .unwrap_or(0)
+                .cmp(&b.codec_info.asr.unwrap_or(0)),
+            Self::AudioChannels => a
+                .codec_info
+                .audio_channels
+                .unwrap_or(0)
+                .cmp(&b.codec_info.audio_channels.unwrap_or(0)),
+            Self::FileSize => a
+                .file_info
+                .size()
+                .unwrap_or(0)
+                .cmp(&b.file_info.size().unwrap_or(0)),
+            Self::PreferSmallerSize => {
+                let a_size = a.file_info.size().unwrap_or(u64::MAX);
+                let b_size = b.file_info.size().unwrap_or(u64::MAX);
+
+                b_size.cmp(&a_size)
+            }
+            Self::PreferVideoCodec(codecs) => compare_preferences(
+                codecs,
+                a.codec_info.video_codec.as_deref(),
+                b.codec_info.video_codec.as_deref(),
+            ),
+            Self::PreferAudioCodec(codecs) => compare_preferences(
+                codecs,
+                a.codec_info.audio_codec.as_deref(),
+                b.codec_info.audio_codec.as_deref(),
+            ),
+            Self::PreferContainer(extensions) => compare_preferences(
+                extensions,
+                extension_name(&a.download_info.ext),
+                extension_name(&b.download_info.ext),
+            ),
+            Self::PreferSdr => {
+                let sdr = |format: &Format| {
+                    !matches!(format.quality_info.dynamic_range, Some(DynamicRange::HDR))
+                };
+
+                sdr(a).cmp(&sdr(b))
+            }
+            Self::PreferLanguage(language) => {
+                let matches = |format: &Format| {
+                    format.language.as_deref().is_some_and(|format_language| {
+                        format_language == language
+                            || format_language.starts_with(&format!("{}-", language))
+                    })
+                };
+
+                matches(a).cmp(&matches(b))
+            }
+            Self::AtMost(key, limit) => compare_limited(key, a, b, |value| {
+                ternary!(value <= *limit, (1, value), (0, -value))
+            }),
+            Self::AtLeast(key, limit) => compare_limited(key, a, b, |value| {
+                ternary!(value >= *limit, (1, -value), (0, value))
+            }),
+            Self::Reverse(key) => key.compare(b, a),
+        }
+    }
+}
+
+/// Parses the limit of a numeric key, the file size accepting a binary unit, e.g. '500M'.
+fn parse_limit(key: &SortKey, limit: &str) -> Option<f64> {
+    const UNITS: [char; 4] = ['K', 'M', 'G', 'T'];
+
+    let (number, exponent) = match (key, limit.chars().last()) {
+        (SortKey::FileSize, Some(unit)) if unit.is_ascii_alphabetic() => {
+            let exponent = UNITS
+                .iter()
+                .position(|candidate| *candidate == unit.to_ascii_uppercase())?;
+            (&limit[..limit.len() - 1], exponent as i32 + 1)
+        }
+        _ => (limit, 0),
+    };
+
+    let number: f64 = number.parse().ok()?;
+    (number.is_finite() && number >= 0.0).then(|| number * 1024f64.powi(exponent))
+}
+
+/// Compares two formats by the rank of the value of a numeric key relative to a limit.
+/// Non-numeric keys are compared as is.
+fn compare_limited(
+    key: &SortKey,
+    a: &Format,
+    b: &Format,
+    rank: impl Fn(f64) -> (u8, f64),
+) -> Ordering {
+    let (Some(a_value), Some(b_value)) = (key.number(a), key.number(b)) else {
+        return key.compare(a, b);
+    };
+
+    let (a_group, a_value) = rank(a_value);
+    let (b_group, b_value) = rank(b_value);
+
+    a_group
+        .cmp(&b_group)
+        .then(OrderedFloat(a_value).cmp(&OrderedFloat(b_value)))
+}
+
+/// Compares two optional numbers, missing values counting as 0.
+fn compare_numbers(a: Option<f64>, b: Option<f64>) -> Ordering {
+    OrderedFloat(a.unwrap_or(0.0)).cmp(&OrderedFloat(b.unwrap_or(0.0)))
+}
+
+/// Compares two values by their rank in a list of preferred prefixes, the first prefix being the best.
+/// Values matching no prefix are the worst.
+fn compare_preferences(preferences: &[String], a: Option<&str>, b: Option<&str>) -> Ordering {
+    let rank = |value: Option<&str>| {
+        let value = value?;
+
+        preferences
+            .iter()
+            .position(|preference| value.starts_with(preference.as_str()))
+            .map(|position| preferences.len() - position)
+    };
+
+    rank(a).cmp(&rank(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_keys() {
+        let keys = [
+            ("quality", SortKey::Quality),
+            ("res", SortKey::Height),
+            ("height", SortKey::Height),
+            ("fps", SortKey::Fps),
+            ("tbr", SortKey::TotalBitrate),
+            ("br", SortKey::TotalBitrate),
+            ("vbr", SortKey::VideoBitrate),
+            ("abr", SortKey::AudioBitrate),
+            ("asr", SortKey::SampleRate),
+            ("channels", SortKey::AudioChannels),
+            ("size", SortKey::FileSize),
+            ("filesize", SortKey::FileSize),
+            ("vcodec:avc1", SortKey::PreferVideoCodec(strings(&["avc1"]))),
+            (
+                "acodec:opus:mp4a",
+                SortKey::PreferAudioCodec(strings(&["opus", "mp4a"])),
+            ),
+            (
+                "ext:mp4:webm",
+                SortKey::PreferContainer(strings(&["mp4", "webm"])),
+            ),
+            ("lang:en", SortKey::PreferLanguage(String::from("en"))),
+        ];
+
+        for (key, expected) in keys {
+            assert_eq!(SortKey::parse(key).unwrap(), expected, "{}", key);
+        }
+    }
+
+    #[test]
+    fn parse_reversed_keys() {
+        let reversed = |key: SortKey| SortKey::Reverse(Box::new(key));
+
+        assert_eq!(SortKey::parse("+res").unwrap(), reversed(SortKey::Height));
+        assert_eq!(
+            SortKey::parse("+vcodec:vp9").unwrap(),
+            reversed(SortKey::PreferVideoCodec(strings(&["vp9"])))
+        );
+
+        // The file size and the dynamic range have dedicated keys, as their unknown values are the worst
+        assert_eq!(SortKey::parse("+size").unwrap(), SortKey::PreferSmallerSize);
+        assert_eq!(SortKey::parse("+hdr").unwrap(), SortKey::PreferSdr);
+        assert_eq!(SortKey::parse("hdr").unwrap(), reversed(SortKey::PreferSdr));
+    }
+
+    #[test]
+    fn parse_limits() {
+        let at_most = |key: SortKey, limit: f64| SortKey::AtMost(Box::new(key), limit);
+        let at_least = |key: SortKey, limit: f64| SortKey::AtLeast(Box::new(key), limit);
+
+        assert_eq!(
+            SortKey::parse("res:1080").unwrap(),
+            at_most(SortKey::Height, 1080.0)
+        );
+        assert_eq!(
+            SortKey::parse("+res:480").unwrap(),
+            at_least(SortKey::Height, 480.0)
+        );
+        assert_eq!(
+            SortKey::parse("fps:30").unwrap(),
+            at_most(SortKey::Fps, 30.0)
+        );
+        assert_eq!(
+            SortKey::parse("abr:128.5").unwrap(),
+            at_most(SortKey::AudioBitrate, 128.5)
+        );
+
+        // The file size accepts binary units
+        assert_eq!(
+            SortKey::parse("size:500M").unwrap(),
+            at_most(SortKey::FileSize, 500.0 * 1024.0 * 1024.0)
+        );
+        assert_eq!(
+            SortKey::parse("+filesize:2k").unwrap(),
+            at_least(SortKey::FileSize, 2048.0)
+        );
+        assert_eq!(
+            SortKey::parse("size:1000").unwrap(),
+            at_most(SortKey::FileSize, 1000.0)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let invalid = [
+            "resolution",
+            "",
+            "+",
+            "vcodec",
+            "acodec:",
+            "lang",
+            "ext::",
+            "res:high",
+            "res:720:1080",
+            "res:720p",
+            "fps:-30",
+            "size:500X",
+            "quality:5",
+            "hdr:10",
+            "+hdr:dv",
+        ];
+
+        for key in invalid {
+            let error = SortKey::parse(key).unwrap_err();
+            assert!(matches!(error, Error::Sorting(_)), "{}", key);
+        }
+    }
+
+    #[test]
+    fn parse_sorter() {
+        let sorter = FormatSorter::parse("+hdr, vcodec:avc1,res,,fps").unwrap();
+        assert_eq!(
+            sorter,
+            FormatSorter::new()
+                .by(SortKey::PreferSdr)
+                .by(SortKey::PreferVideoCodec(strings(&["avc1"])))
+                .by(SortKey::Height)
+                .by(SortKey::Fps)
+        );
+
+        assert_eq!(FormatSorter::parse("").unwrap(), FormatSorter::new());
+        assert!("res,unknown".parse::<FormatSorter>().is_err());
+    }
+
+    /// Returns a format with the given fields, on top of the required ones.
+    fn format(fields: serde_json::Value) -> Format {
+        let mut format = serde_json::json!({
+            "format": "test",
+            "format_id": "test",
+            "ext": "mp4",
+            "resolution": "audio only",
+            "protocol": "https",
+            "url": "https://example.com/test",
+            "http_headers": {
+                "User-Agent": "",
+                "Accept": "",
+                "Accept-Language": "",
+                "Sec-Fetch-Mode": "",
+            },
+        });
+
+        let (Some(format_fields), Some(fields)) = (format.as_object_mut(), fields.as_object())
+        else {
+            unreachable!();
+        };
+        format_fields.extend(fields.clone());
+
+        serde_json::from_value(format).unwrap()
+    }
+
+    /// Asserts that the key ranks the formats from the worst to the best, the missing field being the worst.
+    fn assert_ranks(key: &SortKey, formats: &[Format]) {
+        for (index, worse) in formats.iter().enumerate() {
+            assert_eq!(key.compare(worse, worse), Ordering::Equal, "{:?}", key);
+
+            for better in &formats[index + 1..] {
+                assert_eq!(
+                    key.compare(worse, better),
+                    Ordering::Less,
+                    "{:?}: {:?} should be worse than {:?}",
+                    key,
+                    worse,
+                    better
+                );
+                assert_eq!(key.compare(better, worse), Ordering::Greater, "{:?}", key);
+            }
+        }
+    }
+
+    #[test]
+    fn compare_numeric_keys() {
+        let keys = [
+            (SortKey::Quality, "quality", 1.0, 2.0),
+            (SortKey::Height, "height", 720.0, 1080.0),
+            (SortKey::Fps, "fps", 30.0, 60.0),
+            (SortKey::TotalBitrate, "tbr", 500.0, 1500.0),
+            (SortKey::VideoBitrate, "vbr", 500.0, 1500.0),
+            (SortKey::AudioBitrate, "abr", 64.0, 128.0),
+            (SortKey::SampleRate, "asr", 44100.0, 48000.0),
+            (SortKey::AudioChannels, "audio_channels", 2.0, 6.0),
+            (SortKey::FileSize, "filesize", 1000.0, 2000.0),
+        ];
+
+        for (key, field, low, high) in keys {
+            let number = |value: f64| match field {
+                "height" | "asr" | "audio_channels" | "filesize" => serde_json::json!(value as i64),
+                _ => serde_json::json!(value),
+            };
+            let formats = [
+                format(serde_json::json!({})),
+                format(serde_json::json!({ field: number(low) })),
+                format(serde_json::json!({ field: number(high) })),
+            ];
+
+            assert_ranks(&key, &formats);
+
+            // The reversed key prefers the lowest value, the missing one counting as 0
+            let reversed: Vec<Format> = formats.iter().rev().cloned().collect();
+            assert_ranks(&SortKey::Reverse(Box::new(key)), &reversed);
+        }
+    }
+
+    #[test]
+    fn compare_file_sizes() {
+        let unknown = format(serde_json::json!({}));
+        let approximate = format(serde_json::json!({ "filesize_approx": 3000 }));
+        let exact = format(serde_json::json!({ "filesize": 2000, "filesize_approx": 5000 }));
+
+        // The exact size takes precedence over the approximate one
+        let formats = [unknown, approximate, exact];
+        assert_ranks(
+            &SortKey::FileSize,
+            &[formats[0].clone(), formats[2].clone(), formats[1].clone()],
+        );
+
+        // The unknown sizes are the worst, even when preferring the smallest ones
+        assert_ranks(
+            &SortKey::PreferSmallerSize,
+            &[formats[0].clone(), formats[1].clone(), formats[2].clone()],
+        );
+    }
+
+    #[test]
+    fn compare_limits() {
+        let heights: Vec<Format> = [
+            None,
+            Some(360),
+            Some(480),
+            Some(720),
+            Some(1080),
+            Some(2160),
+        ]
+        .into_iter()
+        .map(|height| format(serde_json::json!({ "height": height })))
+        .collect();
+        let pick = |indices: &[usize]| -> Vec<Format> {
+            indices
+                .iter()
+                .map(|index| heights[*index].clone())
+                .collect()
+        };
+
+        // Up to 720p, the highest resolution is the best, then the lowest one above it
+        let key = SortKey::parse("res:720").unwrap();
+        assert_ranks(&key, &pick(&[5, 4, 0, 1, 2, 3]));
+
+        // From 480p, the lowest resolution is the best, then the highest one below it
+        let key = SortKey::parse("+res:480").unwrap();
+        assert_ranks(&key, &pick(&[0, 1, 5, 4, 3, 2]));
+
+        // The limit is inclusive
+        let key = SortKey::parse("res:1080").unwrap();
+        assert_eq!(key.compare(&heights[4], &heights[3]), Ordering::Greater);
+
+        let sizes: Vec<Format> = [100, 900, 2000]
+            .into_iter()
+            .map(|size| format(serde_json::json!({ "filesize": size * 1024 })))
+            .collect();
+        let key = SortKey::parse("size:1000K").unwrap();
+        assert_ranks(
+            &key,
+            &[sizes[2].clone(), sizes[0].clone(), sizes[1].clone()],
+        );
+    }
+
+    #[test]
+    fn compare_preferences() {
+        let codecs: Vec<Format> = [
+            None,
+            Some("vp9"),
+            Some("av01.0.08M.08"),
+            Some("avc1.64001F"),
+        ]
+        .into_iter()
+        .map(|codec| format(serde_json::json!({ "vcodec": codec })))
+        .collect();
+
+        // The codecs matching no prefix are the worst, and the first prefix is the best
+        let key = SortKey::parse("vcodec:avc1:av01").unwrap();
+        assert_ranks(&key, &codecs[1..]);
+        assert_eq!(key.compare(&codecs[0], &codecs[1]), Ordering::Equal);
+
+        let key = SortKey::parse("acodec:opus:mp4a").unwrap();
+        assert_ranks(
+            &key,
+            &[
+                format(serde_json::json!({ "acodec": "none" })),
+                format(serde_json::json!({ "acodec": "mp4a.40.2" })),
+                format(serde_json::json!({ "acodec": "opus" })),
+            ],
+        );
+
+        let key = SortKey::parse("ext:webm:mp4").unwrap();
+        assert_ranks(
+            &key,
+            &[
+                format(serde_json::json!({ "ext": "3gp" })),
+                format(serde_json::json!({ "ext": "mp4" })),
+                format(serde_json::json!({ "ext": "webm" })),
+            ],
+        );
+
+        // The language matches its regional variants
+        let key = SortKey::parse("lang:en").unwrap();
+        let english = format(serde_json::json!({ "language": "en-US" }));
+        assert_ranks(
+            &key,
+            &[
+                format(serde_json::json!({ "language": "fr" })),
+                english.clone(),
+            ],
+        );
+        assert_eq!(
+            key.compare(&english, &format(serde_json::json!({ "language": "en" }))),
+            Ordering::Equal
+        );
+        assert_ranks(
+            &key,
+            &[format(serde_json::json!({ "language": "eng" })), english],
+        );
+
+        // The reversed preference prefers the values matching no prefix
+        let key = SortKey::parse("+vcodec:avc1:av01").unwrap();
+        assert_ranks(
+            &key,
+            &[codecs[3].clone(), codecs[2].clone(), codecs[1].clone()],
+        );
+    }
+
+    #[test]
+    fn compare_dynamic_range() {
+        let hdr = format(serde_json::json!({ "dynamic_range": "HDR" }));
+        let sdr = format(serde_json::json!({ "dynamic_range": "SDR" }));
+        let unknown = format(serde_json::json!({}));
+
+        // The unknown dynamic range counts as SDR
+        let key = SortKey::parse("+hdr").unwrap();
+        assert_ranks(&key, &[hdr.clone(), sdr.clone()]);
+        assert_eq!(key.compare(&sdr, &unknown), Ordering::Equal);
+
+        let key = SortKey::parse("hdr").unwrap();
+        assert_ranks(&key, &[sdr, hdr]);
+    }
+
+    #[test]
+    fn compare_sorter() {
+        let sorter = FormatSorter::parse("res:1080,fps,+size").unwrap();
+        let formats = [
+            format(serde_json::json!({ "height": 2160, "fps": 60.0, "filesize": 100 })),
+            format(serde_json::json!({ "height": 1080, "fps": 30.0, "filesize": 100 })),
+            format(serde_json::json!({ "height": 1080, "fps": 60.0, "filesize": 300 })),
+            format(serde_json::json!({ "height": 1080, "fps": 60.0, "filesize": 200 })),
+        ];
+
+        // The keys are compared in order until one of them differs
+        for (index, worse) in formats.iter().enumerate() {
+            for better in &formats[index + 1..] {
+                assert_eq!(sorter.compare(worse, better), Ordering::Less);
+            }
+        }
+
+        assert_eq!(
+            FormatSorter::new().compare(&formats[0], &formats[1]),
+            Ordering::Equal
+        );
+    }
+}
@@ -8,9 +8,12 @@ pub struct Thumbnail {
     /// The URL of the thumbnail.
     pub url: String,
     /// The preference index of the thumbnail, e.g. '-35' or '0'.
+    /// It is 0 for the thumbnails of playlists and their entries.
+    #[serde(default)]
     pub preference: i64,
 
-    /// The ID of the thumbnail.
+    /// The ID of the thumbnail, empty for the thumbnails of playlists and their entries.
+    #[serde(default)]
     pub id: String,
     /// The height of the thumbnail, can be `None`.
     pub height: Option<i64>,
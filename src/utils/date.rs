@@ -0,0 +1,80 @@
+//! Conversions between the days since the Unix epoch and the civil dates, in the proleptic Gregorian calendar.
+//!
+//! The calendar is split into eras of 400 years, each one starting on March 1st so that the leap day is the last one.
+
+use crate::ternary;
+
+/// The number of days in an era of 400 years.
+const DAYS_PER_ERA: i64 = 146_097;
+
+/// The number of days from March 1st, 0000 to the Unix epoch.
+const EPOCH_SHIFT: i64 = 719_468;
+
+/// Returns the number of days since the Unix epoch of a civil date.
+///
+/// # Arguments
+///
+/// * `year` - The year, e.g. 2009.
+/// * `month` - The month, from 1 to 12.
+/// * `day` - The day of the month, from 1 to 31.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = ternary!(month <= 2, year - 1, year);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * DAYS_PER_ERA + day_of_era - EPOCH_SHIFT
+}
+
+/// Returns the civil date of a number of days since the Unix epoch, as its year, month and day.
+///
+/// # Arguments
+///
+/// * `days` - The number of days since the Unix epoch.
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + EPOCH_SHIFT;
+    let era = days.div_euclid(DAYS_PER_ERA);
+    let day_of_era = days.rem_euclid(DAYS_PER_ERA);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = ternary!(shifted_month < 10, shifted_month + 3, shifted_month - 9);
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_known_dates() {
+        let dates = [
+            (0, (1970, 1, 1)),
+            (-1, (1969, 12, 31)),
+            (11_016, (2000, 2, 29)),
+            (11_017, (2000, 3, 1)),
+            (14_542, (2009, 10, 25)),
+            (19_723, (2024, 1, 1)),
+            (-719_468, (0, 3, 1)),
+        ];
+
+        for (days, (year, month, day)) in dates {
+            assert_eq!(civil_from_days(days), (year, month, day));
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for days in (-1_000_000..1_000_000).step_by(97) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
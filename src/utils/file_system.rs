@@ -31,6 +31,19 @@ pub fn try_without_extension(path: impl AsRef<Path>) -> Result<String> {
     Ok(name.to_string())
 }
 
+/// Returns the name of the given path without its last extension, e.g. 'my.video' for 'my.video.mp4'.
+pub fn try_stem(path: impl AsRef<Path>) -> Result<String> {
+    let stem = path
+        .as_ref()
+        .file_stem()
+        .ok_or(Error::Path("Failed to get stem".to_string()))?;
+    let stem = stem
+        .to_str()
+        .ok_or(Error::Path("Failed to convert stem".to_string()))?;
+
+    Ok(stem.to_string())
+}
+
 /// Returns the parent directory of the given path.
 pub fn try_parent(path: impl AsRef<Path>) -> Result<PathBuf> {
     let parent = path
@@ -41,7 +54,29 @@ pub fn try_parent(path: impl AsRef<Path>) -> Result<PathBuf> {
     Ok(parent.to_path_buf())
 }
 
+/// Returns the path of the partial file used while downloading to the given path, e.g. 'video.mp4.part'.
+pub fn try_part(path: impl AsRef<Path>) -> Result<PathBuf> {
+    let name = try_name(&path)?;
+    let part = path.as_ref().with_file_name(format!("{}.part", name));
+
+    Ok(part)
+}
+
+/// Returns the size of the file at the given path, or 0 if the file does not exist.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+pub async fn file_size(path: impl AsRef<Path>) -> Result<u64> {
+    match tokio::fs::metadata(path).await {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(error) => Err(Error::IO(error)),
+    }
+}
+
 /// Creates a new file at the given destination.
+/// If the file already exists, it is truncated.
 ///
 /// # Arguments
 ///
@@ -51,6 +86,7 @@ pub async fn create_file(destination: impl AsRef<Path>) -> Result<File> {
     open_options.read(true);
     open_options.write(true);
     open_options.create(true);
+    open_options.truncate(true);
 
     #[cfg(not(target_os = "windows"))]
     {
@@ -61,6 +97,33 @@ pub async fn create_file(destination: impl AsRef<Path>) -> Result<File> {
     Ok(file)
 }
 
+/// Opens the file at the given destination for appending, creating it if it does not exist.
+///
+/// # Arguments
+///
+/// * `destination` - The path of the file to append to.
+pub async fn append_file(destination: impl AsRef<Path>) -> Result<File> {
+    let mut open_options = OpenOptions::new();
+    open_options.append(true);
+    open_options.create(true);
+
+    let file = open_options.open(destination).await?;
+    Ok(file)
+}
+
+/// Removes the file at the given path.
+/// If the file does not exist, nothing is done.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to remove.
+pub async fn remove_file(path: impl AsRef<Path>) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(Error::IO(error)),
+        _ => Ok(()),
+    }
+}
+
 /// Creates a new directory at the given destination.
 /// If the directory already exists, nothing is done.
 ///
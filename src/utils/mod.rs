@@ -6,8 +6,10 @@ use crate::error::Result;
 use platform::Platform;
 use tokio::task::JoinHandle;
 
+pub(crate) mod date;
 pub mod file_system;
 pub mod platform;
+pub mod retry;
 
 /// Converts a vector of string slices to a vector of owned strings.
 pub fn to_owned(vec: Vec<impl AsRef<str>>) -> Vec<String> {
@@ -0,0 +1,153 @@
+//! Retry failed operations with exponential backoff.
+
+use crate::error::{Error, Result};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// A policy to retry failed operations, with exponential backoff and jitter.
+///
+/// The delay before the n-th retry is `initial_backoff * multiplier^(n - 1)`, capped to `max_backoff`,
+/// and randomly shifted by up to `jitter` (a fraction of the delay) to avoid retrying in lockstep.
+///
+/// # Examples
+///
+/// ```rust, no_run
+/// # use yt_dlp::utils::retry::RetryPolicy;
+/// # use std::time::Duration;
+/// let policy = RetryPolicy {
+///     max_attempts: 5,
+///     initial_backoff: Duration::from_millis(500),
+///     ..RetryPolicy::default()
+/// };
+///
+/// let no_retry = RetryPolicy::none();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one. A value of 1 disables the retries.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The maximum delay between two attempts.
+    pub max_backoff: Duration,
+    /// The factor applied to the delay after each retry.
+    pub multiplier: f64,
+    /// The maximum random variation of the delay, as a fraction of it, between 0 and 1.
+    pub jitter: f64,
+    /// Decides if an error is worth retrying, by default [`Error::is_transient`].
+    pub retryable: fn(&Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            retryable: Error::is_transient,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the delay to wait before the given retry, starting at 1.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The number of the retry, starting at 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let delay = delay.min(self.max_backoff.as_secs_f64());
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        let delay = delay * (1.0 + jitter * (2.0 * random - 1.0));
+
+        Duration::from_secs_f64(delay.max(0.0))
+    }
+
+    /// Runs the given operation, retrying it while it fails with a retryable error.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation to run, called once per attempt.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the last error if all attempts failed, or the first non-retryable error.
+    pub async fn run<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.run_with_cancellation(&CancellationToken::new(), operation)
+            .await
+    }
+
+    /// Runs the given operation, retrying it while it fails with a retryable error, unless the given token is cancelled.
+    /// The token only interrupts the waiting between attempts, the operation itself should also observe it.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation` - The token to stop retrying with.
+    /// * `operation` - The operation to run, called once per attempt.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the last error if all attempts failed, the first non-retryable error,
+    /// or [`Error::Cancelled`] if the token was cancelled while waiting.
+    pub async fn run_with_cancellation<T, F, Fut>(
+        &self,
+        cancellation: &CancellationToken,
+        mut operation: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            let error = match operation().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            if attempt >= self.max_attempts || !(self.retryable)(&error) {
+                return Err(error);
+            }
+
+            let backoff = self.backoff(attempt);
+
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                "Attempt {}/{} failed: {}, retrying in {:?}",
+                attempt,
+                self.max_attempts,
+                error,
+                backoff
+            );
+
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {},
+                _ = cancellation.cancelled() => return Err(Error::Cancelled),
+            }
+
+            attempt += 1;
+        }
+    }
+}